@@ -0,0 +1,322 @@
+//! ADR - architecture decision records from harvested decisions
+//!
+//! Writes each decision as a Nygard-style record (context, decision, status)
+//! either as one file per decision or as a single decision log. Context comes
+//! from the summary of the session that made the decision. Once `profundo
+//! consolidate` has run, repeated decisions become one record and reversed
+//! ones link to the record that superseded them.
+
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::consolidate::{CanonicalItem, ItemSource};
+use crate::export::{ExportScope, ExportStats, Exporter};
+use crate::harvest::Learning;
+use crate::session::short_id;
+use crate::Paths;
+
+/// First line of every generated record; only files starting with it are
+/// removed when a rerun no longer produces them
+const GENERATED_MARKER: &str = "<!-- Generated by profundo export --format adr -->";
+
+/// Longest slug in a record's file name
+const MAX_SLUG_LEN: usize = 60;
+
+/// One decision record
+#[derive(Debug, Clone)]
+pub struct DecisionRecord {
+    /// 1-based, in date order
+    pub number: usize,
+    pub decision: String,
+    /// Date first made
+    pub date: String,
+    /// Summary of the session that first made it
+    pub context: String,
+    /// Sessions that made it, oldest first
+    pub sources: Vec<ItemSource>,
+    /// Number of the record that reverses this one
+    pub superseded_by: Option<usize>,
+    /// Numbers of records this one reverses
+    pub supersedes: Vec<usize>,
+}
+
+impl DecisionRecord {
+    /// File name for one-file-per-decision output, e.g. `0003-use-postgres-for-billing.md`
+    pub fn file_name(&self) -> String {
+        format!("{:04}-{}.md", self.number, slug(&self.decision))
+    }
+
+    fn title(&self) -> String {
+        format!("{}. {}", self.number, first_line(&self.decision))
+    }
+}
+
+/// A decision before numbering
+struct Entry {
+    decision: String,
+    sources: Vec<ItemSource>,
+    /// Set when the decision is a consolidated entry
+    canonical_id: Option<i64>,
+    /// Canonical id of the entry that reverses it
+    superseded_by: Option<i64>,
+}
+
+/// Build decision records from all learnings, oldest first.
+///
+/// Once consolidated, each current or superseded canonical decision is one
+/// record; decisions no canonical entry covers (harvested since, or in no
+/// cluster) are added as they are.
+pub fn collect_records(learnings: &[Learning], canonical: &[CanonicalItem]) -> Vec<DecisionRecord> {
+    let summaries: HashMap<&str, &str> = learnings
+        .iter()
+        .map(|l| (l.session_id.as_str(), l.summary.as_str()))
+        .collect();
+    let covered = crate::consolidate::covered(canonical);
+
+    let mut entries: Vec<Entry> = canonical
+        .iter()
+        .filter(|item| item.kind == "decisions")
+        .map(|item| Entry {
+            decision: item.text.clone(),
+            sources: item.sources.clone(),
+            canonical_id: Some(item.id),
+            superseded_by: item.superseded_by,
+        })
+        .collect();
+    for learning in learnings {
+        let uncovered = |d: &&String| !covered.contains(&("decisions", learning.session_id.as_str(), d.as_str()));
+        for decision in learning.decisions.iter().filter(|d| !d.trim().is_empty()).filter(uncovered) {
+            let source = ItemSource {
+                session_id: learning.session_id.clone(),
+                date: learning.date.clone(),
+                text: decision.clone(),
+            };
+            entries.push(Entry {
+                decision: decision.clone(),
+                sources: vec![source],
+                canonical_id: None,
+                superseded_by: None,
+            });
+        }
+    }
+
+    let first_date = |sources: &[ItemSource]| sources.first().map(|s| s.date.clone()).unwrap_or_default();
+    entries.sort_by(|a, b| {
+        first_date(&a.sources)
+            .cmp(&first_date(&b.sources))
+            .then_with(|| a.decision.cmp(&b.decision))
+    });
+
+    let numbers: HashMap<i64, usize> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entry)| entry.canonical_id.map(|id| (id, i + 1)))
+        .collect();
+
+    let mut records: Vec<DecisionRecord> = entries
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| DecisionRecord {
+            number: i + 1,
+            date: first_date(&entry.sources),
+            context: entry
+                .sources
+                .first()
+                .and_then(|s| summaries.get(s.session_id.as_str()))
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            superseded_by: entry.superseded_by.and_then(|id| numbers.get(&id).copied()),
+            supersedes: Vec::new(),
+            decision: entry.decision,
+            sources: entry.sources,
+        })
+        .collect();
+
+    for i in 0..records.len() {
+        if let Some(newer) = records[i].superseded_by {
+            let number = records[i].number;
+            records[newer - 1].supersedes.push(number);
+        }
+    }
+
+    records
+}
+
+/// Write one file per decision into `dir`. Returns the records written.
+pub fn export_records(paths: &Paths, scope: &ExportScope, dir: &Path) -> Result<Vec<DecisionRecord>> {
+    let records = collect_records(scope.learnings, &scope.canonical_items(paths)?);
+    if records.is_empty() {
+        return Ok(records);
+    }
+
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let mut written: HashSet<String> = HashSet::new();
+    for record in &records {
+        let content = format!(
+            "{}\n# {}\n\nDate: {}\n\n{}",
+            GENERATED_MARKER,
+            record.title(),
+            record.date,
+            render_body(paths, record, &records, "##", true)
+        );
+        fs::write(dir.join(record.file_name()), content)
+            .with_context(|| format!("Failed to write {}", record.file_name()))?;
+        written.insert(record.file_name());
+    }
+
+    // Records renumbered or removed since the last export leave stale files behind
+    for entry in fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.ends_with(".md") || written.contains(&name) {
+            continue;
+        }
+        let generated = fs::read_to_string(entry.path())
+            .map(|c| c.starts_with(GENERATED_MARKER))
+            .unwrap_or(false);
+        if generated {
+            fs::remove_file(entry.path())?;
+        }
+    }
+
+    Ok(records)
+}
+
+/// Write all decisions to a single decision log. Returns the records written.
+pub fn export_log(paths: &Paths, scope: &ExportScope, output_path: &Path) -> Result<Vec<DecisionRecord>> {
+    let records = collect_records(scope.learnings, &scope.canonical_items(paths)?);
+    if records.is_empty() {
+        return Ok(records);
+    }
+
+    let mut content = String::from("# Decision Log\n\nDecisions extracted from conversation sessions, oldest first.\n\n");
+    for record in &records {
+        content.push_str(&format!("## {}\n\nDate: {}\n\n", record.title(), record.date));
+        content.push_str(&render_body(paths, record, &records, "###", false));
+        content.push('\n');
+    }
+
+    fs::write(output_path, content.trim_end().to_string() + "\n").context("Failed to write decision log")?;
+    Ok(records)
+}
+
+/// Decision records as an export format: one file per decision in a
+/// directory, or a single log when `log` is set
+pub struct AdrExporter {
+    pub log: bool,
+}
+
+impl Exporter for AdrExporter {
+    fn default_path(&self, paths: &Paths) -> PathBuf {
+        if self.log {
+            paths.memory_dir.join("decisions.md")
+        } else {
+            paths.memory_dir.join("decisions")
+        }
+    }
+
+    fn export(&self, paths: &Paths, scope: &ExportScope, output: &Path) -> Result<ExportStats> {
+        let records = if self.log {
+            export_log(paths, scope, output)?
+        } else {
+            export_records(paths, scope, output)?
+        };
+
+        let sessions: HashSet<&str> = records
+            .iter()
+            .flat_map(|r| r.sources.iter().map(|s| s.session_id.as_str()))
+            .collect();
+        Ok(ExportStats {
+            sessions: sessions.len(),
+            decisions: records.len(),
+            superseded: records.iter().filter(|r| r.superseded_by.is_some()).count(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Status, context, decision and sources. Links point at sibling files when
+/// each record has its own file, and at headings in a single log.
+fn render_body(paths: &Paths, record: &DecisionRecord, records: &[DecisionRecord], heading: &str, files: bool) -> String {
+    let link = |number: usize| {
+        let other = &records[number - 1];
+        if files {
+            format!("[{}]({})", other.title(), other.file_name())
+        } else {
+            format!("[{}](#{})", other.title(), anchor(&other.title()))
+        }
+    };
+
+    let mut body = format!("{} Status\n\n", heading);
+    match record.superseded_by {
+        Some(newer) => body.push_str(&format!("Superseded by {}\n", link(newer))),
+        None => body.push_str("Accepted\n"),
+    }
+    for older in &record.supersedes {
+        body.push_str(&format!("\nSupersedes {}\n", link(*older)));
+    }
+
+    if !record.context.is_empty() {
+        body.push_str(&format!("\n{} Context\n\n{}\n", heading, record.context));
+    }
+
+    body.push_str(&format!("\n{} Decision\n\n{}\n", heading, record.decision));
+
+    body.push_str(&format!("\n{} Sources\n\n", heading));
+    for source in &record.sources {
+        match paths.find_session_file(&source.session_id) {
+            Some(path) => body.push_str(&format!(
+                "- {} [`{}`]({})\n",
+                source.date,
+                short_id(&source.session_id),
+                file_url(&path)
+            )),
+            None => body.push_str(&format!("- {} `{}`\n", source.date, source.session_id)),
+        }
+    }
+
+    body
+}
+
+pub(crate) fn file_url(path: &Path) -> String {
+    format!("file://{}", path.display().to_string().replace(' ', "%20"))
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or("").trim()
+}
+
+/// Lowercase words joined by dashes, cut at a word boundary
+fn slug(text: &str) -> String {
+    let mut slug = String::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+    {
+        if !slug.is_empty() && slug.len() + word.len() + 1 > MAX_SLUG_LEN {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word);
+    }
+    if slug.is_empty() {
+        "decision".to_string()
+    } else {
+        slug
+    }
+}
+
+/// GitHub-style heading anchor
+fn anchor(heading: &str) -> String {
+    heading
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
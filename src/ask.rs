@@ -0,0 +1,505 @@
+//! Ask - retrieval-augmented answers with citations
+//!
+//! Runs hybrid recall plus learnings search, packs the results into a
+//! token-budgeted context, and asks the chat model to answer from it.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::collections::HashSet;
+
+use crate::categories;
+use crate::db::Database;
+use crate::harvest::Learning;
+use crate::openrouter::{ChatModel, Embedder, OpenRouterClient};
+use crate::recall::{self, RecallConfig, SearchResult};
+use crate::session::short_id;
+use crate::Paths;
+
+/// Configuration for ask
+#[derive(Clone, Debug)]
+pub struct AskConfig {
+    /// Number of conversation chunks to retrieve
+    pub top_k: usize,
+    /// Number of learnings to retrieve
+    pub learnings_k: usize,
+    /// Approximate token budget for the context pack
+    pub token_budget: usize,
+    /// Chat model used to answer
+    pub model: String,
+    /// Expand the query before retrieval
+    pub expand: bool,
+}
+
+impl Default for AskConfig {
+    fn default() -> Self {
+        Self {
+            top_k: 8,
+            learnings_k: 5,
+            token_budget: 6000,
+            model: "deepseek/deepseek-v3.2".to_string(),
+            expand: false,
+        }
+    }
+}
+
+/// A single citable source in the context pack
+#[derive(Debug, Clone)]
+pub struct ContextSource {
+    /// Citation label, e.g. `3f2a9c1b:12-17` or `3f2a9c1b:learnings`
+    pub label: String,
+    pub session_id: String,
+    pub date: String,
+    pub text: String,
+}
+
+/// Retrieved sources that fit within the token budget
+#[derive(Debug, Default)]
+pub struct ContextPack {
+    pub sources: Vec<ContextSource>,
+    /// Sources dropped because the budget ran out
+    pub dropped: usize,
+    pub estimated_tokens: usize,
+}
+
+impl ContextPack {
+    /// Render the pack as the context block sent to the model
+    pub fn render(&self) -> String {
+        self.sources
+            .iter()
+            .map(|s| format!("### [{}] ({})\n{}", s.label, s.date, s.text.trim()))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// A claim in the answer that isn't backed by the context
+#[derive(Debug)]
+pub struct FlaggedClaim {
+    pub text: String,
+    pub reason: ClaimIssue,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ClaimIssue {
+    /// No citation at all
+    Uncited,
+    /// Cites a label that isn't in the context pack
+    UnknownSource(String),
+}
+
+/// The model's answer plus citation checks
+#[derive(Debug)]
+pub struct Answer {
+    pub text: String,
+    /// Labels of sources the answer actually cites
+    pub cited: Vec<String>,
+    pub flagged: Vec<FlaggedClaim>,
+}
+
+const ASK_SYSTEM_PROMPT: &str = r#"You answer questions about the user's past conversations using ONLY the provided context.
+
+Rules:
+- Every sentence that states a fact must end with one or more citations in square brackets, copied exactly from the context headers, e.g. [3f2a9c1b:12-17]
+- Do not cite anything that is not a context header
+- If the context does not contain the answer, say so plainly instead of guessing
+- Be concise"#;
+
+/// Retrieve chunks and learnings for a question and pack them into a budget
+pub async fn retrieve(paths: &Paths, question: &str, config: &AskConfig) -> Result<ContextPack> {
+    let client = OpenRouterClient::from_env()?;
+    let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+    retrieve_with(&db, &client, Some(&client), question, config).await
+}
+
+/// Retrieve with explicit embedding and query-expansion backends.
+///
+/// Expansion is skipped when no chat backend is given. With
+/// `embed::HashEmbedder` and `CannedChat`, `ask` runs fully offline:
+///
+/// ```
+/// use profundo::ask::{self, AskConfig};
+/// use profundo::db::Database;
+/// use profundo::embed::HashEmbedder;
+/// use profundo::openrouter::CannedChat;
+///
+/// # tokio::runtime::Runtime::new()?.block_on(async {
+/// let db = Database::open_in_memory()?;
+/// let config = AskConfig::default();
+/// let pack = ask::retrieve_with(&db, &HashEmbedder::default(), None::<&CannedChat>, "Which database?", &config).await?;
+///
+/// let chat = CannedChat::new("Billing uses Postgres [3f2a9c1b:learnings].");
+/// let answer = ask::answer(&chat, "Which database?", &pack, &config.model).await?;
+/// assert_eq!(answer.flagged.len(), 1); // the empty pack has no such source
+/// # Ok::<_, anyhow::Error>(())
+/// # })?;
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub async fn retrieve_with<E: Embedder, C: ChatModel>(
+    db: &Database,
+    embedder: &E,
+    chat: Option<&C>,
+    question: &str,
+    config: &AskConfig,
+) -> Result<ContextPack> {
+    let recall_config = RecallConfig {
+        top_k: config.top_k,
+        expand: config.expand,
+        ..RecallConfig::default()
+    }
+    .with_env();
+    let results = recall::search_with(db, embedder, chat, question, recall_config).await?;
+
+    let learnings: Vec<Learning> = db
+        .search_learnings(question, config.learnings_k, None)?
+        .into_iter()
+        .map(|(l, _)| l)
+        .collect();
+
+    Ok(build_context(&results, &learnings, config.token_budget))
+}
+
+/// Pack learnings first (dense, cheap), then chunks in rank order, until the budget is spent
+pub fn build_context(
+    results: &[SearchResult],
+    learnings: &[Learning],
+    token_budget: usize,
+) -> ContextPack {
+    let mut candidates: Vec<ContextSource> = Vec::new();
+
+    for learning in learnings {
+        let mut text = learning.summary.clone();
+        for decision in &learning.decisions {
+            text.push_str(&format!("\nDecision: {}", decision));
+        }
+        for fact in &learning.facts_learned {
+            text.push_str(&format!("\nFact: {}", fact));
+        }
+        for action in &learning.action_items {
+            text.push_str(&format!("\nAction item: {}", action));
+        }
+        for (name, items) in &learning.categories {
+            for item in items {
+                text.push_str(&format!("\n{}: {}", categories::label(name), item));
+            }
+        }
+
+        candidates.push(ContextSource {
+            label: format!("{}:learnings", short_id(&learning.session_id)),
+            session_id: learning.session_id.clone(),
+            date: learning.date.clone(),
+            text,
+        });
+    }
+
+    for result in results {
+        let chunk = &result.chunk;
+        let date = chunk
+            .timestamp
+            .as_deref()
+            .and_then(|t| t.split('T').next())
+            .unwrap_or("unknown")
+            .to_string();
+
+        candidates.push(ContextSource {
+            label: format!(
+                "{}:{}-{}",
+                short_id(&chunk.session_id),
+                chunk.turn_start,
+                chunk.turn_end
+            ),
+            session_id: chunk.session_id.clone(),
+            date,
+            text: chunk.text.clone(),
+        });
+    }
+
+    let mut pack = ContextPack::default();
+    for source in candidates {
+        let tokens = estimate_tokens(&source.text) + 16;
+        if pack.estimated_tokens + tokens > token_budget {
+            pack.dropped += 1;
+            continue;
+        }
+        pack.estimated_tokens += tokens;
+        pack.sources.push(source);
+    }
+
+    pack
+}
+
+/// Ask the chat model to answer from the context pack, then check its citations
+pub async fn answer<C: ChatModel>(
+    chat: &C,
+    question: &str,
+    pack: &ContextPack,
+    model: &str,
+) -> Result<Answer> {
+    let user_prompt = format!(
+        "Context:\n\n{}\n\nQuestion: {}",
+        pack.render(),
+        question
+    );
+
+    let text = chat.chat(ASK_SYSTEM_PROMPT, &user_prompt, model).await?;
+    Ok(check_citations(&text, pack))
+}
+
+/// Flag sentences without citations and citations of unknown sources
+pub fn check_citations(text: &str, pack: &ContextPack) -> Answer {
+    let known: HashSet<&str> = pack.sources.iter().map(|s| s.label.as_str()).collect();
+    let mut cited: Vec<String> = Vec::new();
+    let mut flagged = Vec::new();
+
+    for claim in split_claims(text) {
+        let labels = extract_citations(&claim);
+
+        if labels.is_empty() {
+            if is_assertion(&claim) {
+                flagged.push(FlaggedClaim {
+                    text: claim,
+                    reason: ClaimIssue::Uncited,
+                });
+            }
+            continue;
+        }
+
+        for label in labels {
+            if known.contains(label.as_str()) {
+                if !cited.contains(&label) {
+                    cited.push(label);
+                }
+            } else {
+                flagged.push(FlaggedClaim {
+                    text: claim.clone(),
+                    reason: ClaimIssue::UnknownSource(label),
+                });
+            }
+        }
+    }
+
+    Answer {
+        text: text.to_string(),
+        cited,
+        flagged,
+    }
+}
+
+/// Split an answer into sentence-sized claims
+fn split_claims(text: &str) -> Vec<String> {
+    let mut claims: Vec<&str> = Vec::new();
+
+    for line in text.lines() {
+        let mut start = 0;
+        let bytes = line.as_bytes();
+        for (i, &b) in bytes.iter().enumerate() {
+            // Sentence ends at ". " / "! " / "? " — citations sit before the terminator
+            let at_break = matches!(b, b'.' | b'!' | b'?')
+                && bytes.get(i + 1).map(|n| *n == b' ').unwrap_or(true);
+            if at_break {
+                claims.push(line[start..=i].trim());
+                start = i + 1;
+            }
+        }
+        if start < line.len() {
+            claims.push(line[start..].trim());
+        }
+    }
+
+    // Citations written after the full stop belong to the preceding sentence
+    let mut merged: Vec<String> = Vec::new();
+    for claim in claims.into_iter().filter(|c| !c.is_empty()) {
+        match merged.last_mut() {
+            Some(prev) if claim.starts_with('[') => {
+                prev.push(' ');
+                prev.push_str(claim);
+            }
+            _ => merged.push(claim.to_string()),
+        }
+    }
+
+    merged
+}
+
+/// Pull `[label]` citations out of a claim (supports `[a] [b]` and `[a; b]`)
+fn extract_citations(claim: &str) -> Vec<String> {
+    let mut labels = Vec::new();
+    let mut rest = claim;
+
+    while let Some(open) = rest.find('[') {
+        let Some(close) = rest[open..].find(']') else {
+            break;
+        };
+        let inner = &rest[open + 1..open + close];
+        for label in inner.split([';', ',']) {
+            let label = label.trim();
+            if label.contains(':') {
+                labels.push(label.to_string());
+            }
+        }
+        rest = &rest[open + close + 1..];
+    }
+
+    labels
+}
+
+/// Whether a claim is a statement that needs support (not a heading or hedge)
+fn is_assertion(claim: &str) -> bool {
+    let trimmed = claim.trim_start_matches(['-', '*', '#', ' ']);
+    let lower = trimmed.to_lowercase();
+
+    trimmed.split_whitespace().count() >= 4
+        && !trimmed.ends_with(':')
+        && !lower.contains("does not contain")
+        && !lower.contains("doesn't contain")
+        && !lower.starts_with("i don't know")
+}
+
+/// Rough token estimate (~4 characters per token)
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Print the raw context pack
+pub fn display_context(pack: &ContextPack) {
+    println!(
+        "{} Context: {} sources, ~{} tokens ({} dropped for budget)\n",
+        "→".blue(),
+        pack.sources.len().to_string().cyan(),
+        pack.estimated_tokens.to_string().cyan(),
+        pack.dropped.to_string().yellow()
+    );
+    println!("{}\n", pack.render().dimmed());
+}
+
+/// Print the answer, its sources, and any flagged claims
+pub fn display_answer(answer: &Answer, pack: &ContextPack) {
+    println!("{}\n", highlight_citations(&answer.text));
+
+    if !answer.cited.is_empty() {
+        println!("{}", "Sources".bold());
+        for label in &answer.cited {
+            if let Some(source) = pack.sources.iter().find(|s| &s.label == label) {
+                println!(
+                    "  [{}] {} {}",
+                    label.cyan(),
+                    source.date,
+                    source.session_id.dimmed()
+                );
+            }
+        }
+        println!();
+    }
+
+    if !answer.flagged.is_empty() {
+        println!("{} {} claims need checking:", "⚠".yellow(), answer.flagged.len());
+        for claim in &answer.flagged {
+            let reason = match &claim.reason {
+                ClaimIssue::Uncited => "uncited".to_string(),
+                ClaimIssue::UnknownSource(label) => format!("unknown source [{}]", label),
+            };
+            println!("  {} {} ({})", "•".yellow(), claim.text, reason.yellow());
+        }
+        println!();
+    }
+}
+
+fn highlight_citations(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(open) = rest.find('[') {
+        let Some(close) = rest[open..].find(']') else {
+            break;
+        };
+        out.push_str(&rest[..open]);
+        out.push_str(&rest[open..=open + close].cyan().to_string());
+        rest = &rest[open + close + 1..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embed::{self, EmbedConfig, HashEmbedder};
+    use crate::openrouter::CannedChat;
+    use std::path::Path;
+
+    const SESSION: &str = "c3d2e5f4-postgres-indexes";
+
+    /// An in-memory database with one fixture session indexed and harvested
+    async fn fixture_db(embedder: &HashEmbedder) -> Result<Database> {
+        let mut db = Database::open_in_memory()?;
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("fixtures/eval/sessions/{}.jsonl", SESSION));
+        embed::process_session(&mut db, embedder, &path, SESSION, 0, 0, &EmbedConfig::default()).await?;
+
+        let learning: Learning = serde_json::from_value(serde_json::json!({
+            "session_id": SESSION,
+            "date": "2026-01-12",
+            "topics": ["postgres"],
+            "decisions": ["Add a composite index on orders (customer_id, created_at)"],
+            "facts_learned": [],
+            "action_items": [],
+            "summary": "Fixed the slow orders query with a composite index",
+            "message_count": 8,
+            "cost": 0.0,
+            "harvested_at": "2026-01-12T11:00:00Z"
+        }))?;
+        db.upsert_learning(&learning)?;
+        Ok(db)
+    }
+
+    #[tokio::test]
+    async fn retrieves_and_checks_citations_offline() -> Result<()> {
+        let embedder = HashEmbedder::default();
+        let db = fixture_db(&embedder).await?;
+        let config = AskConfig::default();
+
+        let pack = retrieve_with(&db, &embedder, None::<&CannedChat>, "slow orders query", &config).await?;
+        let labels: Vec<&str> = pack.sources.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels.first(), Some(&"c3d2e5f4:learnings"));
+        let chunk = labels
+            .iter()
+            .find(|l| !l.ends_with(":learnings"))
+            .expect("a conversation chunk is retrieved");
+        assert!(chunk.starts_with("c3d2e5f4:"));
+
+        let chat = CannedChat::new(format!(
+            "The orders query got a composite index [c3d2e5f4:learnings]. \
+             EXPLAIN ANALYZE showed a sequential scan [{}]. \
+             It was deployed on Friday [deadbeef:1-2]. \
+             The index made the page ten times faster.",
+            chunk
+        ));
+        let answer = answer(&chat, "slow orders query", &pack, &config.model).await?;
+
+        assert_eq!(answer.cited, vec!["c3d2e5f4:learnings".to_string(), chunk.to_string()]);
+        let issues: Vec<&ClaimIssue> = answer.flagged.iter().map(|f| &f.reason).collect();
+        assert_eq!(
+            issues,
+            vec![&ClaimIssue::UnknownSource("deadbeef:1-2".to_string()), &ClaimIssue::Uncited]
+        );
+        assert_eq!(answer.flagged[1].text, "The index made the page ten times faster.");
+        Ok(())
+    }
+
+    #[test]
+    fn citations_after_the_full_stop_belong_to_the_sentence() {
+        let pack = ContextPack {
+            sources: vec![ContextSource {
+                label: "c3d2e5f4:0-5".to_string(),
+                session_id: SESSION.to_string(),
+                date: "2026-01-12".to_string(),
+                text: String::new(),
+            }],
+            ..Default::default()
+        };
+
+        let answer = check_citations("Billing moved to Postgres last month. [c3d2e5f4:0-5; c3d2e5f4:9-12]", &pack);
+        assert_eq!(answer.cited, vec!["c3d2e5f4:0-5".to_string()]);
+        assert_eq!(answer.flagged.len(), 1);
+        assert_eq!(answer.flagged[0].reason, ClaimIssue::UnknownSource("c3d2e5f4:9-12".to_string()));
+    }
+}
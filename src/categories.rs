@@ -0,0 +1,311 @@
+//! Categories - configurable learning categories and harvest prompt
+//!
+//! The built-in categories (topics, decisions, facts_learned, action_items)
+//! live in `Learning`'s own fields; user-defined ones are stored in
+//! `Learning::categories`. Both are configured in `harvest-categories.json`
+//! in the memory directory:
+//!
+//! ```json
+//! {
+//!   "prompt_template": "harvest-prompt.txt",
+//!   "categories": [
+//!     { "name": "bugs_found", "description": "Bugs found, with their symptom", "format": "- 🐛 {item}" },
+//!     { "name": "open_questions", "description": "Questions left unanswered" }
+//!   ]
+//! }
+//! ```
+//!
+//! The prompt always asks for entities and relations too (see `graph.rs`),
+//! and for the turns behind each decision, fact and action item (see `trace.rs`).
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::graph::ENTITY_KINDS;
+use crate::trace::TRACED;
+
+/// A learning category the harvest model fills in
+#[derive(Debug, Clone, Deserialize)]
+pub struct Category {
+    /// JSON key, lowercase snake_case
+    pub name: String,
+    /// What belongs in the category, shown to the model
+    pub description: String,
+    /// Markdown for one exported item; `{item}` is replaced with the text
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CategoriesFile {
+    #[serde(default)]
+    categories: Vec<Category>,
+    /// Prompt template path, relative to the categories file
+    #[serde(default)]
+    prompt_template: Option<PathBuf>,
+}
+
+/// Categories stored in `Learning`'s own fields, in prompt order
+const BUILTIN: &[(&str, &str, Option<&str>)] = &[
+    ("topics", "2-5 keywords describing what was discussed", None),
+    (
+        "decisions",
+        "Only explicit decisions made, not general discussion",
+        Some("- **Decision**: {item}"),
+    ),
+    (
+        "facts_learned",
+        "New information about the user (preferences, background, etc.)",
+        Some("- **Learned**: {item}"),
+    ),
+    ("action_items", "Tasks that were identified to do", Some("- [ ] {item}")),
+];
+
+/// Output keys that aren't list categories
+const RESERVED: &[&str] = &["summary", "entities", "relations", "completed_action_items"];
+
+const ENTITIES_RULE: &str = "Named people, projects, repositories, services and tools the conversation \
+is about (not generic concepts), with their type";
+
+const RELATIONS_RULE: &str = "How those entities relate, as short subject-predicate-object facts \
+(e.g. \"billing-api\" \"uses\" \"Postgres\"); subject and object are entity names";
+
+const TURNS_RULE: &str = "For decisions, facts_learned and action_items, give the first and last \
+[turn N] the item is based on, and a short verbatim quote (one sentence at most) from those turns";
+
+/// Default prompt template. `{{format}}` becomes the JSON shape and
+/// `{{rules}}` one line per category; the conversation is appended after.
+pub const DEFAULT_TEMPLATE: &str = r#"Analyze this conversation and extract structured learnings.
+
+Respond with ONLY valid JSON (no markdown, no explanation):
+{{format}}
+
+Rules:
+{{rules}}
+- Use empty arrays [] if nothing fits a category
+- Be concise and specific
+"#;
+
+/// The full set of categories to extract, plus the prompt template
+#[derive(Debug, Clone)]
+pub struct CategorySet {
+    pub categories: Vec<Category>,
+    template: String,
+}
+
+impl Default for CategorySet {
+    fn default() -> Self {
+        Self {
+            categories: BUILTIN
+                .iter()
+                .map(|(name, description, format)| Category {
+                    name: name.to_string(),
+                    description: description.to_string(),
+                    format: format.map(|f| f.to_string()),
+                })
+                .collect(),
+            template: DEFAULT_TEMPLATE.to_string(),
+        }
+    }
+}
+
+impl CategorySet {
+    /// Load categories from a JSON file, or the built-ins if it doesn't exist.
+    ///
+    /// Entries named like a built-in override its description and format;
+    /// any other entry adds a category.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut set = Self::default();
+        if !path.exists() {
+            return Ok(set);
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read categories file: {}", path.display()))?;
+        let file: CategoriesFile = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid categories file: {}", path.display()))?;
+
+        for category in file.categories {
+            validate_name(&category.name)?;
+            match set.categories.iter_mut().find(|c| c.name == category.name) {
+                Some(existing) if is_builtin(&category.name) => {
+                    existing.description = category.description;
+                    if category.format.is_some() {
+                        existing.format = category.format;
+                    }
+                }
+                Some(_) => return Err(anyhow!("Category '{}' is defined twice", category.name)),
+                None => set.categories.push(category),
+            }
+        }
+
+        if let Some(template) = file.prompt_template {
+            let template_path = path.parent().unwrap_or(Path::new(".")).join(template);
+            set.template = std::fs::read_to_string(&template_path).with_context(|| {
+                format!("Failed to read prompt template: {}", template_path.display())
+            })?;
+        }
+
+        Ok(set)
+    }
+
+    /// Categories beyond the built-ins
+    pub fn custom(&self) -> impl Iterator<Item = &Category> {
+        self.categories.iter().filter(|c| !is_builtin(&c.name))
+    }
+
+    /// Render the extraction prompt (without the conversation)
+    pub fn prompt(&self) -> String {
+        let mut format = String::from("{\n");
+        for category in &self.categories {
+            if TRACED.contains(&category.name.as_str()) {
+                format.push_str(&format!(
+                    "    \"{}\": [{{\"text\": \"...\", \"turns\": [first, last], \"quote\": \"...\"}}],\n",
+                    category.name
+                ));
+            } else {
+                format.push_str(&format!("    \"{}\": [\"...\"],\n", category.name));
+            }
+        }
+        format.push_str(&format!(
+            "    \"entities\": [{{\"name\": \"...\", \"type\": \"{}\"}}],\n",
+            ENTITY_KINDS.join("|")
+        ));
+        format.push_str("    \"relations\": [{\"subject\": \"...\", \"predicate\": \"...\", \"object\": \"...\"}],\n");
+        format.push_str("    \"summary\": \"One paragraph summary\"\n}");
+
+        let mut rules: Vec<String> = self
+            .categories
+            .iter()
+            .map(|c| format!("- {}: {}", c.name, c.description))
+            .collect();
+        rules.push(format!("- entities: {}", ENTITIES_RULE));
+        rules.push(format!("- relations: {}", RELATIONS_RULE));
+        rules.push(format!("- turns: {}", TURNS_RULE));
+        rules.push("- summary: Brief summary of the conversation's purpose and outcome".to_string());
+        let rules = rules.join("\n");
+
+        let mut prompt = self.template.replace("{{rules}}", &rules);
+        if prompt.contains("{{format}}") {
+            prompt = prompt.replace("{{format}}", &format);
+        } else {
+            // Custom templates must still ask for parseable output
+            prompt.push_str(&format!(
+                "\nRespond with ONLY valid JSON (no markdown, no explanation):\n{}\n",
+                format
+            ));
+        }
+        prompt
+    }
+
+    /// JSON Schema for the extraction output
+    pub fn json_schema(&self) -> serde_json::Value {
+        let list = serde_json::json!({ "type": "array", "items": { "type": "string" } });
+        let traced = serde_json::json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string" },
+                    "turns": { "type": "array", "items": { "type": "integer" } },
+                    "quote": { "type": "string" },
+                },
+                "required": ["text", "turns", "quote"],
+                "additionalProperties": false,
+            }
+        });
+        let mut properties = serde_json::Map::new();
+        for category in &self.categories {
+            let items = if TRACED.contains(&category.name.as_str()) { &traced } else { &list };
+            properties.insert(category.name.clone(), items.clone());
+        }
+        properties.insert(
+            "entities".to_string(),
+            serde_json::json!({
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "type": { "type": "string", "enum": ENTITY_KINDS },
+                    },
+                    "required": ["name", "type"],
+                    "additionalProperties": false,
+                }
+            }),
+        );
+        properties.insert(
+            "relations".to_string(),
+            serde_json::json!({
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "subject": { "type": "string" },
+                        "predicate": { "type": "string" },
+                        "object": { "type": "string" },
+                    },
+                    "required": ["subject", "predicate", "object"],
+                    "additionalProperties": false,
+                }
+            }),
+        );
+        properties.insert("summary".to_string(), serde_json::json!({ "type": "string" }));
+
+        let mut required: Vec<&str> = self.categories.iter().map(|c| c.name.as_str()).collect();
+        required.extend(["entities", "relations", "summary"]);
+
+        serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        })
+    }
+
+    /// Format one item for markdown export
+    pub fn format_item(&self, name: &str, item: &str) -> String {
+        match self.categories.iter().find(|c| c.name == name).and_then(|c| c.format.as_deref()) {
+            Some(format) => format.replace("{item}", item),
+            None => format!("- **{}**: {}", label(name), item),
+        }
+    }
+}
+
+/// Whether a category is stored in `Learning`'s own fields
+pub fn is_builtin(name: &str) -> bool {
+    BUILTIN.iter().any(|(builtin, _, _)| *builtin == name)
+}
+
+/// Human-readable label for a category name (`bugs_found` → `Bugs Found`)
+pub fn label(name: &str) -> String {
+    name.split('_')
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    if !valid {
+        return Err(anyhow!(
+            "Invalid category name '{}': use lowercase letters, digits and underscores",
+            name
+        ));
+    }
+    if RESERVED.contains(&name) {
+        return Err(anyhow!("'{}' is reserved and can't be a category", name));
+    }
+
+    Ok(())
+}
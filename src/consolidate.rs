@@ -0,0 +1,387 @@
+//! Consolidate - merge near-duplicate learnings across sessions
+//!
+//! Clusters facts and decisions from all learnings by embedding similarity,
+//! keeps one canonical entry per cluster with its supporting sessions, and
+//! marks entries that a later one reverses as superseded.
+
+use anyhow::{anyhow, bail, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::db::Database;
+use crate::harvest::Learning;
+use crate::openrouter::{ChatModel, Embedder};
+use crate::recall::cosine_similarity;
+
+/// Learning fields that are consolidated
+pub const KINDS: &[&str] = &["facts_learned", "decisions"];
+
+/// Configuration for consolidation
+#[derive(Clone, Debug)]
+pub struct ConsolidateConfig {
+    /// Minimum similarity for two items to be the same entry
+    pub threshold: f32,
+    /// Minimum similarity for two entries to be checked for a contradiction
+    pub contradiction_floor: f32,
+    /// Most contradiction checks to send to the chat model per run
+    pub max_checks: usize,
+    /// Chat model used to judge contradictions
+    pub model: String,
+}
+
+impl Default for ConsolidateConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 0.85,
+            contradiction_floor: 0.6,
+            max_checks: 200,
+            model: "deepseek/deepseek-v3.2".to_string(),
+        }
+    }
+}
+
+/// A session that stated a canonical entry, in its own words
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemSource {
+    pub session_id: String,
+    pub date: String,
+    pub text: String,
+}
+
+/// One consolidated fact or decision
+#[derive(Debug, Clone, Serialize)]
+pub struct CanonicalItem {
+    /// Database id (0 until stored)
+    pub id: i64,
+    /// Learning field it came from (`facts_learned` or `decisions`)
+    pub kind: String,
+    /// Canonical wording (the most recent source's)
+    pub text: String,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub sources: Vec<ItemSource>,
+    /// Id of the entry that reverses this one
+    pub superseded_by: Option<i64>,
+}
+
+/// The raw items that consolidated entries stand in for, as
+/// `(kind, session_id, text)`. Items harvested since the last consolidation,
+/// or that joined no entry, are not covered and are still shown per session.
+pub fn covered(items: &[CanonicalItem]) -> HashSet<(&str, &str, &str)> {
+    items
+        .iter()
+        .flat_map(|item| {
+            item.sources
+                .iter()
+                .map(move |s| (item.kind.as_str(), s.session_id.as_str(), s.text.as_str()))
+        })
+        .collect()
+}
+
+/// Summary of a consolidation run
+#[derive(Debug, Default)]
+pub struct ConsolidateStats {
+    pub items: usize,
+    pub canonical: usize,
+    pub merged: usize,
+    pub checked: usize,
+    pub superseded: usize,
+}
+
+const CONTRADICTION_PROMPT: &str = "You compare two statements extracted from a user's conversations. \
+Answer with ONLY \"yes\" or \"no\".";
+
+/// Working cluster while consolidating
+struct Cluster {
+    kind: String,
+    sources: Vec<ItemSource>,
+    centroid: Vec<f32>,
+    superseded_by: Option<usize>,
+}
+
+/// Consolidate all learnings and replace the stored canonical entries.
+///
+/// Contradictions are only checked when a chat model is given; verdicts are
+/// cached so reruns don't ask about the same pair twice.
+pub async fn run<E: Embedder, C: ChatModel>(
+    db: &mut Database,
+    embedder: &E,
+    chat: Option<&C>,
+    config: &ConsolidateConfig,
+) -> Result<ConsolidateStats> {
+    let learnings = db.load_learnings(None)?;
+    let items = collect_items(&learnings);
+    let embeddings = embed_items(db, embedder, &items).await?;
+    let item_count = items.len();
+
+    // Greedy clustering in date order: join the most similar cluster of the same kind
+    let mut clusters: Vec<Cluster> = Vec::new();
+    for ((kind, source), embedding) in items.into_iter().zip(embeddings) {
+        let embedding = normalize(embedding);
+        let best = clusters
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.kind == kind)
+            .map(|(i, c)| (i, cosine_similarity(&c.centroid, &embedding)))
+            .filter(|(_, score)| *score >= config.threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        match best {
+            Some((i, _)) => {
+                let cluster = &mut clusters[i];
+                let n = cluster.sources.len() as f32;
+                for (c, x) in cluster.centroid.iter_mut().zip(&embedding) {
+                    *c = (*c * n + x) / (n + 1.0);
+                }
+                cluster.sources.push(source);
+            }
+            None => clusters.push(Cluster {
+                kind: kind.to_string(),
+                sources: vec![source],
+                centroid: embedding,
+                superseded_by: None,
+            }),
+        }
+    }
+
+    let mut stats = ConsolidateStats {
+        items: item_count,
+        canonical: clusters.len(),
+        merged: item_count - clusters.len(),
+        ..Default::default()
+    };
+
+    if let Some(chat) = chat {
+        let (checked, superseded) = detect_contradictions(db, chat, &mut clusters, config).await?;
+        stats.checked = checked;
+        stats.superseded = superseded;
+    }
+
+    let canonical: Vec<CanonicalItem> = clusters
+        .iter()
+        .map(|c| CanonicalItem {
+            id: 0,
+            kind: c.kind.clone(),
+            text: c.sources.last().map(|s| s.text.clone()).unwrap_or_default(),
+            first_seen: c.sources.first().map(|s| s.date.clone()).unwrap_or_default(),
+            last_seen: c.sources.last().map(|s| s.date.clone()).unwrap_or_default(),
+            sources: c.sources.clone(),
+            // Index into this list; the database maps it to the stored id
+            superseded_by: c.superseded_by.map(|i| i as i64),
+        })
+        .collect();
+
+    db.replace_canonical_items(&canonical)?;
+
+    Ok(stats)
+}
+
+/// Embeddings of all items, in order. Only items not embedded by an earlier
+/// run (new, or reworded) are sent to the embedder.
+async fn embed_items<E: Embedder>(
+    db: &mut Database,
+    embedder: &E,
+    items: &[(&'static str, ItemSource)],
+) -> Result<Vec<Vec<f32>>> {
+    let model = embedder.model();
+    let mut cached = db.item_embeddings(&model)?;
+    let key = |source: &ItemSource| (source.session_id.clone(), text_hash(&source.text));
+
+    let missing: Vec<&ItemSource> = items
+        .iter()
+        .map(|(_, source)| source)
+        .filter(|source| !cached.contains_key(&key(source)))
+        .collect();
+    if !missing.is_empty() {
+        let texts: Vec<String> = missing.iter().map(|source| source.text.clone()).collect();
+        let embedded = embedder.embed_batch(&texts).await?;
+        if embedded.len() != texts.len() {
+            bail!("Embedder returned {} embeddings for {} items", embedded.len(), texts.len());
+        }
+
+        let fresh: Vec<(&str, String, Vec<f32>)> = missing
+            .iter()
+            .zip(embedded)
+            .map(|(source, embedding)| (source.session_id.as_str(), text_hash(&source.text), embedding))
+            .collect();
+        db.store_item_embeddings(&model, &fresh)?;
+        for (session_id, hash, embedding) in fresh {
+            cached.insert((session_id.to_string(), hash), embedding);
+        }
+    }
+
+    items
+        .iter()
+        .map(|(_, source)| {
+            cached
+                .get(&key(source))
+                .cloned()
+                .ok_or_else(|| anyhow!("No embedding for \"{}\"", source.text))
+        })
+        .collect()
+}
+
+fn text_hash(text: &str) -> String {
+    format!("{:016x}", crate::embed::fnv1a(text.as_bytes()))
+}
+
+/// All facts and decisions as (kind, source), oldest first
+fn collect_items(learnings: &[Learning]) -> Vec<(&'static str, ItemSource)> {
+    let mut items = Vec::new();
+
+    for learning in learnings {
+        for &kind in KINDS {
+            let texts = match kind {
+                "facts_learned" => &learning.facts_learned,
+                _ => &learning.decisions,
+            };
+            for text in texts.iter().filter(|t| !t.trim().is_empty()) {
+                items.push((
+                    kind,
+                    ItemSource {
+                        session_id: learning.session_id.clone(),
+                        date: learning.date.clone(),
+                        text: text.clone(),
+                    },
+                ));
+            }
+        }
+    }
+
+    items.sort_by(|a, b| a.1.date.cmp(&b.1.date));
+    items
+}
+
+/// Ask the chat model whether related-but-distinct entries reverse each other.
+///
+/// Returns (pairs checked, entries marked superseded).
+async fn detect_contradictions<C: ChatModel>(
+    db: &Database,
+    chat: &C,
+    clusters: &mut [Cluster],
+    config: &ConsolidateConfig,
+) -> Result<(usize, usize)> {
+    // Candidate pairs: same kind, similar topic, but not similar enough to merge
+    let mut pairs: Vec<(f32, usize, usize)> = Vec::new();
+    for i in 0..clusters.len() {
+        for j in (i + 1)..clusters.len() {
+            if clusters[i].kind != clusters[j].kind {
+                continue;
+            }
+            let score = cosine_similarity(&clusters[i].centroid, &clusters[j].centroid);
+            if score >= config.contradiction_floor && score < config.threshold {
+                pairs.push((score, i, j));
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut checked = 0;
+    let mut superseded = 0;
+
+    for (_, i, j) in pairs {
+        // The entry last stated earlier is the one that could have been reversed
+        let last = |c: &Cluster| c.sources.last().map(|s| s.date.clone()).unwrap_or_default();
+        let (older, newer) = if last(&clusters[i]) <= last(&clusters[j]) { (i, j) } else { (j, i) };
+        if last(&clusters[older]) == last(&clusters[newer]) || clusters[older].superseded_by.is_some() {
+            continue;
+        }
+
+        let older_text = clusters[older].sources.last().map(|s| s.text.clone()).unwrap_or_default();
+        let newer_text = clusters[newer].sources.last().map(|s| s.text.clone()).unwrap_or_default();
+
+        let verdict = match db.contradiction_verdict(&older_text, &newer_text)? {
+            Some(verdict) => verdict,
+            None => {
+                if checked >= config.max_checks {
+                    continue;
+                }
+                checked += 1;
+
+                let prompt = format!(
+                    "Earlier ({}): {}\nLater ({}): {}\n\n\
+                     Does the later statement reverse, replace or contradict the earlier one, \
+                     so that the earlier one is no longer true?",
+                    last(&clusters[older]),
+                    older_text,
+                    last(&clusters[newer]),
+                    newer_text
+                );
+                let answer = chat.chat(CONTRADICTION_PROMPT, &prompt, &config.model).await?;
+                let verdict = answer.trim().to_lowercase().starts_with("yes");
+                db.store_contradiction_verdict(&older_text, &newer_text, verdict)?;
+                verdict
+            }
+        };
+
+        if verdict {
+            clusters[older].superseded_by = Some(newer);
+            superseded += 1;
+        }
+    }
+
+    Ok((checked, superseded))
+}
+
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for x in vector.iter_mut() {
+            *x /= norm;
+        }
+    }
+    vector
+}
+
+/// Print a consolidation summary
+pub fn display_stats(stats: &ConsolidateStats) {
+    println!(
+        "{} {} facts and decisions → {} entries ({} merged)",
+        "✓".green(),
+        stats.items.to_string().cyan(),
+        stats.canonical.to_string().cyan(),
+        stats.merged.to_string().cyan()
+    );
+    if stats.checked > 0 || stats.superseded > 0 {
+        println!(
+            "  {} contradiction checks, {} entries superseded",
+            stats.checked.to_string().cyan(),
+            stats.superseded.to_string().yellow()
+        );
+    }
+}
+
+/// Print canonical entries, most supported first
+pub fn display_items(items: &[CanonicalItem]) {
+    for &kind in KINDS {
+        let mut entries: Vec<&CanonicalItem> = items.iter().filter(|i| i.kind == kind).collect();
+        if entries.is_empty() {
+            continue;
+        }
+        entries.sort_by(|a, b| {
+            b.sources
+                .len()
+                .cmp(&a.sources.len())
+                .then_with(|| b.last_seen.cmp(&a.last_seen))
+        });
+
+        let heading = if kind == "decisions" { "Decisions" } else { "Facts" };
+        println!("\n{}", heading.bold());
+
+        for item in entries {
+            let sessions = format!("{} sessions, {} → {}", item.sources.len(), item.first_seen, item.last_seen);
+            match item.superseded_by.and_then(|id| items.iter().find(|i| i.id == id)) {
+                Some(newer) => println!(
+                    "  {} {} {}\n      {} {}",
+                    "✗".red(),
+                    item.text.strikethrough().dimmed(),
+                    format!("({})", sessions).dimmed(),
+                    "superseded by:".yellow(),
+                    newer.text
+                ),
+                None => println!("  {} {} {}", "●".cyan(), item.text, format!("({})", sessions).dimmed()),
+            }
+        }
+    }
+}
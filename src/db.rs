@@ -1,15 +1,21 @@
 //! Database operations for Profundo
 //!
-//! SQLite storage for embeddings and processing state.
+//! SQLite storage for embeddings, harvested learnings, action items and processing state.
 
 use anyhow::{Context, Result};
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::{Path, PathBuf};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
 
+use crate::consolidate::{CanonicalItem, ItemSource};
+use crate::graph::{self, Graph, GraphEntity, GraphRelation, Mention};
 use crate::harvest::Learning;
+use crate::profile::ProfileLine;
 use crate::session::TextChunk;
+use crate::todo::{ActionItem, ActionStatus};
 
 /// Embedded chunk stored in the database
 #[derive(Debug, Clone)]
@@ -18,6 +24,7 @@ pub struct StoredChunk {
     pub rowid: i64,
     pub id: String,
     pub session_id: String,
+    pub agent: String,
     pub turn_start: i32,
     pub turn_end: i32,
     pub timestamp: Option<String>,
@@ -28,7 +35,6 @@ pub struct StoredChunk {
 /// Database handle for Profundo
 pub struct Database {
     conn: Connection,
-    learnings_path: Option<PathBuf>,
 }
 
 impl Database {
@@ -43,30 +49,60 @@ impl Database {
         let conn = Connection::open(path)
             .context("Failed to open database")?;
 
-        let db = Self { conn, learnings_path: None };
+        let db = Self { conn };
         db.init_schema()?;
 
         Ok(db)
     }
 
-    /// Open or create the database, and (re)build the learnings FTS index
-    pub fn open_with_learnings(path: &Path, learnings_path: &Path) -> Result<Self> {
-        // Ensure parent directory exists
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)
-                .context("Failed to create database directory")?;
-        }
+    /// Open a throwaway in-memory database (for evaluation runs)
+    pub fn open_in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory()
+            .context("Failed to open in-memory database")?;
 
-        let conn = Connection::open(path)
-            .context("Failed to open database")?;
-
-        let mut db = Self { conn, learnings_path: Some(learnings_path.to_path_buf()) };
+        let db = Self { conn };
         db.init_schema()?;
-        db.rebuild_learnings_fts(learnings_path)?;
 
         Ok(db)
     }
 
+    /// Open or create the database, importing `learnings_path` once if the
+    /// learnings haven't been migrated from JSONL yet
+    pub fn open_with_learnings(path: &Path, learnings_path: &Path) -> Result<Self> {
+        let mut db = Self::open(path)?;
+        db.migrate_learnings(learnings_path)?;
+        Ok(db)
+    }
+
+    /// Import `learnings_path` unless the learnings were already migrated from
+    /// JSONL. Returns what was imported, or None if there was nothing to do.
+    pub fn migrate_learnings(&mut self, learnings_path: &Path) -> Result<Option<ImportStats>> {
+        let imported: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM state WHERE key = 'learnings_imported'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if imported.is_some() {
+            return Ok(None);
+        }
+
+        if !learnings_path.exists() {
+            self.conn.execute(
+                "INSERT OR REPLACE INTO state(key, value) VALUES('learnings_imported', '1')",
+                [],
+            )?;
+            return Ok(None);
+        }
+
+        let stats = self
+            .import_jsonl(learnings_path, true)
+            .context("Failed to migrate learnings.jsonl into the database")?;
+        Ok(Some(stats))
+    }
+
     /// Initialize database schema
     fn init_schema(&self) -> Result<()> {
         self.conn.execute_batch(
@@ -106,16 +142,131 @@ impl Database {
                 INSERT INTO chunks_fts(rowid, text) VALUES (new.rowid, new.text);
             END;
 
-            -- Full-text search index for harvested learnings (FTS5)
-            CREATE VIRTUAL TABLE IF NOT EXISTS learnings_fts USING fts5(
-                session_id,
-                topics,
-                summary,
-                facts,
-                decisions,
-                action_items
+            -- Harvested learnings, one row per session (list fields as JSON)
+            CREATE TABLE IF NOT EXISTS learnings (
+                id INTEGER PRIMARY KEY,
+                session_id TEXT NOT NULL UNIQUE,
+                agent TEXT NOT NULL DEFAULT 'main',
+                date TEXT NOT NULL,
+                topics TEXT NOT NULL DEFAULT '[]',
+                decisions TEXT NOT NULL DEFAULT '[]',
+                facts_learned TEXT NOT NULL DEFAULT '[]',
+                action_items TEXT NOT NULL DEFAULT '[]',
+                categories TEXT NOT NULL DEFAULT '{}',
+                entities TEXT NOT NULL DEFAULT '[]',
+                relations TEXT NOT NULL DEFAULT '[]',
+                provenance TEXT NOT NULL DEFAULT '[]',
+                summary TEXT NOT NULL,
+                message_count INTEGER NOT NULL DEFAULT 0,
+                cost REAL NOT NULL DEFAULT 0,
+                harvested_at TEXT NOT NULL,
+                version INTEGER NOT NULL DEFAULT 1,
+                harvested_messages INTEGER NOT NULL DEFAULT 0,
+                harvested_bytes INTEGER NOT NULL DEFAULT 0
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_learnings_date ON learnings(date);
+            CREATE INDEX IF NOT EXISTS idx_learnings_agent ON learnings(agent);
+
+            -- Consolidated facts and decisions (rebuilt by `profundo consolidate`)
+            CREATE TABLE IF NOT EXISTS canonical_items (
+                id INTEGER PRIMARY KEY,
+                kind TEXT NOT NULL,
+                text TEXT NOT NULL,
+                first_seen TEXT NOT NULL,
+                last_seen TEXT NOT NULL,
+                superseded_by INTEGER REFERENCES canonical_items(id)
+            );
+
+            CREATE TABLE IF NOT EXISTS canonical_sources (
+                canonical_id INTEGER NOT NULL REFERENCES canonical_items(id) ON DELETE CASCADE,
+                session_id TEXT NOT NULL,
+                date TEXT NOT NULL,
+                text TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_canonical_sources_id ON canonical_sources(canonical_id);
+
+            -- Cached "does the later statement reverse the earlier one" answers
+            CREATE TABLE IF NOT EXISTS contradiction_verdicts (
+                older TEXT NOT NULL,
+                newer TEXT NOT NULL,
+                contradicts INTEGER NOT NULL,
+                PRIMARY KEY (older, newer)
             );
 
+            -- Embeddings of facts and decisions, so consolidate only embeds new ones
+            CREATE TABLE IF NOT EXISTS item_embeddings (
+                session_id TEXT NOT NULL,
+                text_hash TEXT NOT NULL,
+                model TEXT NOT NULL,
+                embedding BLOB NOT NULL,
+                PRIMARY KEY (session_id, text_hash, model)
+            );
+
+            -- Profile sections facts were sorted into, and the last text written
+            -- for each section with a hash of its facts (see profile.rs)
+            CREATE TABLE IF NOT EXISTS profile_facts (
+                text TEXT PRIMARY KEY,
+                section TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS profile_sections (
+                section TEXT PRIMARY KEY,
+                input_hash TEXT NOT NULL,
+                lines TEXT NOT NULL DEFAULT '[]'
+            );
+
+            -- Action item lifecycle, synced from learnings (see todo.rs)
+            CREATE TABLE IF NOT EXISTS action_items (
+                id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                agent TEXT NOT NULL,
+                text TEXT NOT NULL,
+                date TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'open',
+                created_at TEXT NOT NULL,
+                closed_at TEXT,
+                closed_by TEXT,
+                note TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_action_items_status ON action_items(status);
+            CREATE INDEX IF NOT EXISTS idx_action_items_session ON action_items(session_id);
+
+            -- Entity graph merged across sessions, synced from learnings (see graph.rs)
+            CREATE TABLE IF NOT EXISTS entities (
+                id INTEGER PRIMARY KEY,
+                key TEXT NOT NULL UNIQUE,
+                name TEXT NOT NULL,
+                kind TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS entity_mentions (
+                entity_id INTEGER NOT NULL REFERENCES entities(id),
+                session_id TEXT NOT NULL,
+                date TEXT NOT NULL,
+                PRIMARY KEY (entity_id, session_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS relations (
+                id INTEGER PRIMARY KEY,
+                subject_id INTEGER NOT NULL REFERENCES entities(id),
+                predicate TEXT NOT NULL,
+                object_id INTEGER NOT NULL REFERENCES entities(id),
+                UNIQUE (subject_id, predicate, object_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS relation_sources (
+                relation_id INTEGER NOT NULL REFERENCES relations(id),
+                session_id TEXT NOT NULL,
+                date TEXT NOT NULL,
+                PRIMARY KEY (relation_id, session_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_entity_mentions_session ON entity_mentions(session_id);
+            CREATE INDEX IF NOT EXISTS idx_relation_sources_session ON relation_sources(session_id);
+
             -- Processed sessions bookkeeping
             CREATE TABLE IF NOT EXISTS sessions_processed (
                 session_id TEXT PRIMARY KEY,
@@ -153,9 +304,143 @@ impl Database {
             )?;
         }
 
+        // Older databases had a standalone learnings_fts rebuilt from JSONL on
+        // every open; replace it with one backed by the learnings table
+        let learnings_fts_sql: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT sql FROM sqlite_master WHERE name = 'learnings_fts'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        if learnings_fts_sql.map(|sql| !sql.contains("content=learnings")).unwrap_or(false) {
+            self.conn
+                .execute("DROP TABLE learnings_fts", [])
+                .context("Failed to drop old learnings FTS index")?;
+        }
+
+        self.conn.execute_batch(
+            r#"
+            -- Full-text search index for harvested learnings (FTS5)
+            CREATE VIRTUAL TABLE IF NOT EXISTS learnings_fts USING fts5(
+                session_id,
+                topics,
+                summary,
+                facts_learned,
+                decisions,
+                action_items,
+                categories,
+                content=learnings,
+                content_rowid=id
+            );
+
+            -- Keep learnings FTS index in sync
+            CREATE TRIGGER IF NOT EXISTS learnings_ai AFTER INSERT ON learnings BEGIN
+                INSERT INTO learnings_fts(rowid, session_id, topics, summary, facts_learned, decisions, action_items, categories)
+                VALUES (new.id, new.session_id, new.topics, new.summary, new.facts_learned, new.decisions, new.action_items, new.categories);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS learnings_ad AFTER DELETE ON learnings BEGIN
+                INSERT INTO learnings_fts(learnings_fts, rowid, session_id, topics, summary, facts_learned, decisions, action_items, categories)
+                VALUES ('delete', old.id, old.session_id, old.topics, old.summary, old.facts_learned, old.decisions, old.action_items, old.categories);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS learnings_au AFTER UPDATE ON learnings BEGIN
+                INSERT INTO learnings_fts(learnings_fts, rowid, session_id, topics, summary, facts_learned, decisions, action_items, categories)
+                VALUES ('delete', old.id, old.session_id, old.topics, old.summary, old.facts_learned, old.decisions, old.action_items, old.categories);
+                INSERT INTO learnings_fts(rowid, session_id, topics, summary, facts_learned, decisions, action_items, categories)
+                VALUES (new.id, new.session_id, new.topics, new.summary, new.facts_learned, new.decisions, new.action_items, new.categories);
+            END;
+            "#,
+        ).context("Failed to initialize learnings FTS index")?;
+
+        // It counts transcript messages, not turns
+        if self.has_column("learnings", "harvested_turns")? {
+            self.conn
+                .execute("ALTER TABLE learnings RENAME COLUMN harvested_turns TO harvested_messages", [])
+                .context("Failed to rename learnings.harvested_turns")?;
+        }
+
+        // Entity columns were added after learnings moved into the database
+        self.ensure_column("learnings", "entities", "TEXT NOT NULL DEFAULT '[]'")?;
+        self.ensure_column("learnings", "relations", "TEXT NOT NULL DEFAULT '[]'")?;
+        self.ensure_column("learnings", "provenance", "TEXT NOT NULL DEFAULT '[]'")?;
+
+        // Agent columns were added after the first release
+        self.ensure_column("chunks", "agent", "TEXT NOT NULL DEFAULT 'main'")?;
+        self.ensure_column("sessions_processed", "agent", "TEXT NOT NULL DEFAULT 'main'")?;
+        self.conn
+            .execute_batch("CREATE INDEX IF NOT EXISTS idx_chunks_agent ON chunks(agent);")
+            .context("Failed to create agent index")?;
+
+        // One-time migration from random UUIDs to stable `<session>:<start>-<end>` ids
+        let stable_ids: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM state WHERE key = 'stable_chunk_ids'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        if stable_ids.is_none() {
+            self.conn
+                .execute(
+                    "UPDATE chunks SET id = session_id || ':' || turn_start || '-' || turn_end",
+                    [],
+                )
+                .context("Failed to migrate chunk ids")?;
+            self.conn.execute(
+                "INSERT OR REPLACE INTO state(key, value) VALUES('stable_chunk_ids', '1')",
+                [],
+            )?;
+        }
+
+        // One-time tracking of action items harvested before they had a status
+        let actions_synced: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM state WHERE key = 'action_items_synced'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        if actions_synced.is_none() {
+            for learning in self.load_learnings(None)? {
+                sync_action_items(&self.conn, &learning)?;
+            }
+            self.conn.execute(
+                "INSERT OR REPLACE INTO state(key, value) VALUES('action_items_synced', '1')",
+                [],
+            )?;
+        }
+
         Ok(())
     }
 
+    /// Add a column to an existing table if it isn't there yet
+    fn ensure_column(&self, table: &str, column: &str, decl: &str) -> Result<()> {
+        if !self.has_column(table, column)? {
+            self.conn
+                .execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl), [])
+                .with_context(|| format!("Failed to add {}.{}", table, column))?;
+        }
+
+        Ok(())
+    }
+
+    fn has_column(&self, table: &str, column: &str) -> Result<bool> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .any(|name| name == column);
+        Ok(exists)
+    }
+
     /// Check if a session has been processed (and file hasn't changed)
     pub fn is_session_processed(&self, session_id: &str, file_size: u64, file_mtime: i64) -> Result<bool> {
         let result: Option<(i64, i64)> = self.conn
@@ -179,6 +464,7 @@ impl Database {
     pub fn store_chunks(
         &mut self,
         session_id: &str,
+        agent: &str,
         file_path: &str,
         file_size: u64,
         file_mtime: i64,
@@ -195,17 +481,18 @@ impl Database {
         // Insert new chunks (scoped to drop stmt before commit)
         {
             let mut stmt = tx.prepare(
-                "INSERT INTO chunks (id, session_id, turn_start, turn_end, timestamp, text, embedding)
-                 VALUES (?, ?, ?, ?, ?, ?, ?)"
+                "INSERT INTO chunks (id, session_id, agent, turn_start, turn_end, timestamp, text, embedding)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
             )?;
 
             for (chunk, embedding) in chunks {
-                let id = uuid::Uuid::new_v4().to_string();
+                let id = chunk.id();
                 let embedding_bytes = embedding_to_bytes(embedding);
 
                 stmt.execute(params![
                     id,
                     chunk.session_id,
+                    chunk.agent,
                     chunk.turn_start as i32,
                     chunk.turn_end as i32,
                     chunk.timestamp,
@@ -217,9 +504,9 @@ impl Database {
 
         // Update processed status
         tx.execute(
-            "INSERT OR REPLACE INTO sessions_processed (session_id, file_path, file_size, file_mtime, chunks_count)
-             VALUES (?, ?, ?, ?, ?)",
-            params![session_id, file_path, file_size as i64, file_mtime, chunks.len() as i32],
+            "INSERT OR REPLACE INTO sessions_processed (session_id, agent, file_path, file_size, file_mtime, chunks_count)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![session_id, agent, file_path, file_size as i64, file_mtime, chunks.len() as i32],
         )?;
 
         tx.commit()?;
@@ -229,20 +516,21 @@ impl Database {
     /// Load all chunks for similarity search
     pub fn load_all_chunks(&self) -> Result<Vec<StoredChunk>> {
         let mut stmt = self.conn.prepare(
-            "SELECT rowid, id, session_id, turn_start, turn_end, timestamp, text, embedding FROM chunks"
+            "SELECT rowid, id, session_id, agent, turn_start, turn_end, timestamp, text, embedding FROM chunks"
         )?;
 
         let chunks = stmt
             .query_map([], |row| {
-                let embedding_bytes: Vec<u8> = row.get(7)?;
+                let embedding_bytes: Vec<u8> = row.get(8)?;
                 Ok(StoredChunk {
                     rowid: row.get(0)?,
                     id: row.get(1)?,
                     session_id: row.get(2)?,
-                    turn_start: row.get(3)?,
-                    turn_end: row.get(4)?,
-                    timestamp: row.get(5)?,
-                    text: row.get(6)?,
+                    agent: row.get(3)?,
+                    turn_start: row.get(4)?,
+                    turn_end: row.get(5)?,
+                    timestamp: row.get(6)?,
+                    text: row.get(7)?,
                     embedding: bytes_to_embedding(&embedding_bytes),
                 })
             })?
@@ -254,24 +542,26 @@ impl Database {
 
     /// BM25-ranked lexical search using FTS5.
     ///
-    /// Returns (rowid, rank) pairs ordered by ascending rank (lower is better).
+    /// Returns (rowid, rank) pairs ordered by ascending rank (lower is better),
+    /// optionally restricted to one agent's chunks.
     /// Sanitizes query to prevent FTS5 syntax errors from special characters.
-    pub fn bm25_search(&self, query: &str, limit: usize) -> Result<Vec<(i64, f32)>> {
+    pub fn bm25_search(&self, query: &str, limit: usize, agent: Option<&str>) -> Result<Vec<(i64, f32)>> {
         let safe_query = sanitize_fts_query(query);
         if safe_query.is_empty() {
             return Ok(Vec::new());
         }
 
         let mut stmt = self.conn.prepare(
-            "SELECT rowid, bm25(chunks_fts) as rank \
+            "SELECT chunks_fts.rowid, bm25(chunks_fts) as rank \
              FROM chunks_fts \
-             WHERE chunks_fts MATCH ? \
+             JOIN chunks ON chunks.rowid = chunks_fts.rowid \
+             WHERE chunks_fts MATCH ?1 AND (?2 IS NULL OR chunks.agent = ?2) \
              ORDER BY rank \
-             LIMIT ?"
+             LIMIT ?3"
         )?;
 
         let rows = stmt
-            .query_map(params![safe_query, limit as i64], |row| {
+            .query_map(params![safe_query, agent, limit as i64], |row| {
                 Ok((row.get::<_, i64>(0)?, row.get::<_, f32>(1)?))
             })?
             .collect::<Result<Vec<_>, _>>()
@@ -280,116 +570,488 @@ impl Database {
         Ok(rows)
     }
 
-    /// Rebuild the learnings FTS5 table from learnings.jsonl
-    pub fn rebuild_learnings_fts(&mut self, learnings_path: &Path) -> Result<()> {
-        let tx = self.conn.transaction()?;
+    /// FTS5 snippets around the matched query terms for the given chunks.
+    ///
+    /// Any query term counts as a match, so hits found via expanded query
+    /// variants still get a passage. Matches are wrapped in `start` and
+    /// `end`; chunks without a lexical match are absent from the returned map.
+    pub fn chunk_snippets(
+        &self,
+        query: &str,
+        rowids: &[i64],
+        max_tokens: usize,
+        (start, end): (&str, &str),
+    ) -> Result<HashMap<i64, String>> {
+        let safe_query = sanitize_fts_query_any(query);
+        if safe_query.is_empty() || rowids.is_empty() {
+            return Ok(HashMap::new());
+        }
 
-        tx.execute("DELETE FROM learnings_fts", [])?;
+        let placeholders: Vec<&str> = rowids.iter().map(|_| "?").collect();
+        let sql = format!(
+            "SELECT rowid, snippet(chunks_fts, 0, ?, ?, '…', ?) \
+             FROM chunks_fts \
+             WHERE chunks_fts MATCH ? AND rowid IN ({})",
+            placeholders.join(",")
+        );
 
-        if !learnings_path.exists() {
-            tx.commit()?;
-            return Ok(());
-        }
+        let mut values: Vec<Value> = vec![
+            Value::Text(start.to_string()),
+            Value::Text(end.to_string()),
+            Value::Integer(max_tokens.clamp(1, 64) as i64),
+            Value::Text(safe_query),
+        ];
+        values.extend(rowids.iter().map(|r| Value::Integer(*r)));
 
-        let file = File::open(learnings_path)
-            .with_context(|| format!("Failed to open learnings file: {}", learnings_path.display()))?;
-        let reader = BufReader::new(file);
+        let mut stmt = self.conn.prepare(&sql)?;
+        let snippets = stmt
+            .query_map(params_from_iter(values), |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<HashMap<_, _>, _>>()
+            .context("Failed to build chunk snippets")?;
+
+        Ok(snippets)
+    }
 
-        let mut stmt = tx.prepare(
-            "INSERT INTO learnings_fts (session_id, topics, summary, facts, decisions, action_items) VALUES (?, ?, ?, ?, ?, ?)"
+    /// Insert a learning, or replace the existing one for the same session,
+    /// along with its action items and graph entries
+    pub fn upsert_learning(&self, learning: &Learning) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        upsert_learning(&tx, learning)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record how much of a session's log has been read, when it grew
+    /// without anything new to harvest
+    pub fn set_harvested_bytes(&self, session_id: &str, size: u64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE learnings SET harvested_bytes = ? WHERE session_id = ?",
+            params![size as i64, session_id],
         )?;
+        Ok(())
+    }
+
+    /// Load all learnings in harvest order, optionally for one agent only
+    pub fn load_learnings(&self, agent: Option<&str>) -> Result<Vec<Learning>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM learnings WHERE (?1 IS NULL OR agent = ?1) ORDER BY id",
+            LEARNING_COLUMNS
+        ))?;
+
+        let learnings = stmt
+            .query_map(params![agent], learning_from_row)?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to load learnings")?;
+
+        Ok(learnings)
+    }
+
+    /// Number of stored learnings
+    pub fn learnings_count(&self) -> Result<usize> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM learnings", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Import learnings from a JSONL file.
+    ///
+    /// A line replaces the stored learning for its session unless the stored
+    /// one has a higher version. Lines that aren't a valid learning are
+    /// skipped with a warning.
+    pub fn import_learnings_jsonl(&mut self, path: &Path) -> Result<ImportStats> {
+        self.import_jsonl(path, false)
+    }
+
+    /// Import a JSONL file in one transaction, marking the one-time
+    /// migration done in the same transaction when `migration` is set
+    fn import_jsonl(&mut self, path: &Path, migration: bool) -> Result<ImportStats> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open learnings file: {}", path.display()))?;
+        let reader = BufReader::new(file);
+
+        let tx = self.conn.transaction()?;
+        let mut stats = ImportStats::default();
 
         for line in reader.lines() {
-            let line = line?;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                    stats.skipped += 1;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
             let line = line.trim();
             if line.is_empty() {
                 continue;
             }
 
-            let learning: Learning = serde_json::from_str(line)
-                .context("Failed to parse learning JSON")?;
-
-            let topics = learning.topics.join(" ");
-            let facts = learning.facts_learned.join(" ");
-            let decisions = learning.decisions.join(" ");
-            let action_items = learning.action_items.join(" ");
+            let learning: Learning = match serde_json::from_str(line) {
+                Ok(learning) => learning,
+                Err(_) => {
+                    stats.skipped += 1;
+                    continue;
+                }
+            };
+
+            let stored_version: Option<u32> = tx
+                .query_row(
+                    "SELECT version FROM learnings WHERE session_id = ?",
+                    params![learning.session_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            if stored_version.map(|v| v <= learning.version).unwrap_or(true) {
+                upsert_learning(&tx, &learning)?;
+                stats.imported += 1;
+            }
+        }
 
-            stmt.execute(params![
-                learning.session_id,
-                topics,
-                learning.summary,
-                facts,
-                decisions,
-                action_items,
-            ])?;
+        if migration {
+            tx.execute(
+                "INSERT OR REPLACE INTO state(key, value) VALUES('learnings_imported', '1')",
+                [],
+            )?;
         }
+        tx.commit()?;
+        Ok(stats)
+    }
 
-        drop(stmt);
+    /// Write all learnings to a JSONL file. Returns the number written.
+    pub fn export_learnings_jsonl(&self, path: &Path) -> Result<usize> {
+        let learnings = self.load_learnings(None)?;
 
-        tx.commit()?;
-        Ok(())
+        let mut file = File::create(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        for learning in &learnings {
+            writeln!(file, "{}", serde_json::to_string(learning)?)?;
+        }
+
+        Ok(learnings.len())
     }
 
-    /// BM25-ranked lexical search over harvested learnings.
+    /// Replace all consolidated entries.
     ///
-    /// Returns (Learning, rank) ordered by ascending rank (lower is better).
-    pub fn search_learnings(&self, query: &str, limit: usize) -> Result<Vec<(Learning, f32)>> {
-        let Some(learnings_path) = self.learnings_path.as_deref() else {
-            return Ok(Vec::new());
-        };
+    /// `superseded_by` on the input is an index into `items`; it is stored as
+    /// the id of that entry.
+    pub fn replace_canonical_items(&mut self, items: &[CanonicalItem]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM canonical_sources", [])?;
+        tx.execute("DELETE FROM canonical_items", [])?;
 
-        let safe_query = sanitize_fts_query(query);
-        if safe_query.is_empty() {
-            return Ok(Vec::new());
+        let mut ids = Vec::with_capacity(items.len());
+        {
+            let mut insert_item = tx.prepare(
+                "INSERT INTO canonical_items (kind, text, first_seen, last_seen) VALUES (?, ?, ?, ?)",
+            )?;
+            let mut insert_source = tx.prepare(
+                "INSERT INTO canonical_sources (canonical_id, session_id, date, text) VALUES (?, ?, ?, ?)",
+            )?;
+
+            for item in items {
+                insert_item.execute(params![item.kind, item.text, item.first_seen, item.last_seen])?;
+                let id = tx.last_insert_rowid();
+                for source in &item.sources {
+                    insert_source.execute(params![id, source.session_id, source.date, source.text])?;
+                }
+                ids.push(id);
+            }
+        }
+
+        for (item, id) in items.iter().zip(&ids) {
+            if let Some(index) = item.superseded_by {
+                tx.execute(
+                    "UPDATE canonical_items SET superseded_by = ? WHERE id = ?",
+                    params![ids[index as usize], id],
+                )?;
+            }
         }
 
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Load consolidated entries with their sources
+    pub fn load_canonical_items(&self) -> Result<Vec<CanonicalItem>> {
         let mut stmt = self.conn.prepare(
-            "SELECT session_id, bm25(learnings_fts) as rank \
-             FROM learnings_fts \
-             WHERE learnings_fts MATCH ? \
-             ORDER BY rank \
-             LIMIT ?"
+            "SELECT id, kind, text, first_seen, last_seen, superseded_by FROM canonical_items ORDER BY id",
         )?;
+        let mut items = stmt
+            .query_map([], |row| {
+                Ok(CanonicalItem {
+                    id: row.get(0)?,
+                    kind: row.get(1)?,
+                    text: row.get(2)?,
+                    first_seen: row.get(3)?,
+                    last_seen: row.get(4)?,
+                    sources: Vec::new(),
+                    superseded_by: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to load consolidated entries")?;
 
-        let rows = stmt
-            .query_map(params![safe_query, limit as i64], |row| {
-                Ok((row.get::<_, String>(0)?, row.get::<_, f32>(1)?))
+        let mut stmt = self.conn.prepare(
+            "SELECT canonical_id, session_id, date, text FROM canonical_sources ORDER BY date, rowid",
+        )?;
+        let sources = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    ItemSource {
+                        session_id: row.get(1)?,
+                        date: row.get(2)?,
+                        text: row.get(3)?,
+                    },
+                ))
             })?
             .collect::<Result<Vec<_>, _>>()
-            .context("Failed to run learnings BM25 search")?;
+            .context("Failed to load consolidated sources")?;
 
-        if rows.is_empty() {
-            return Ok(Vec::new());
+        let index: HashMap<i64, usize> = items.iter().enumerate().map(|(i, item)| (item.id, i)).collect();
+        for (id, source) in sources {
+            if let Some(&i) = index.get(&id) {
+                items[i].sources.push(source);
+            }
         }
 
-        if !learnings_path.exists() {
-            return Ok(Vec::new());
+        Ok(items)
+    }
+
+    /// Cached contradiction verdict for an (older, newer) statement pair
+    pub fn contradiction_verdict(&self, older: &str, newer: &str) -> Result<Option<bool>> {
+        let verdict = self
+            .conn
+            .query_row(
+                "SELECT contradicts FROM contradiction_verdicts WHERE older = ? AND newer = ?",
+                params![older, newer],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(verdict)
+    }
+
+    /// Cache a contradiction verdict
+    pub fn store_contradiction_verdict(&self, older: &str, newer: &str, contradicts: bool) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO contradiction_verdicts (older, newer, contradicts) VALUES (?, ?, ?)",
+            params![older, newer, contradicts],
+        )?;
+        Ok(())
+    }
+
+    /// Cached embeddings of facts and decisions from one embedding model,
+    /// by `(session_id, text_hash)`
+    pub fn item_embeddings(&self, model: &str) -> Result<HashMap<(String, String), Vec<f32>>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT session_id, text_hash, embedding FROM item_embeddings WHERE model = ?")?;
+        let embeddings = stmt
+            .query_map(params![model], |row| {
+                let bytes: Vec<u8> = row.get(2)?;
+                Ok(((row.get(0)?, row.get(1)?), bytes_to_embedding(&bytes)))
+            })?
+            .collect::<Result<HashMap<_, _>, _>>()
+            .context("Failed to load item embeddings")?;
+        Ok(embeddings)
+    }
+
+    /// Cache embeddings of facts and decisions, given as `(session_id, text_hash, embedding)`
+    pub fn store_item_embeddings(&mut self, model: &str, embeddings: &[(&str, String, Vec<f32>)]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO item_embeddings (session_id, text_hash, model, embedding) VALUES (?, ?, ?, ?)",
+            )?;
+            for (session_id, text_hash, embedding) in embeddings {
+                stmt.execute(params![session_id, text_hash, model, embedding_to_bytes(embedding)])?;
+            }
         }
+        tx.commit()?;
+        Ok(())
+    }
 
-        let file = File::open(learnings_path)
-            .with_context(|| format!("Failed to open learnings file: {}", learnings_path.display()))?;
-        let reader = BufReader::new(file);
+    /// Profile section a fact was sorted into
+    pub fn profile_fact_section(&self, text: &str) -> Result<Option<String>> {
+        let section = self
+            .conn
+            .query_row("SELECT section FROM profile_facts WHERE text = ?", params![text], |row| row.get(0))
+            .optional()?;
+        Ok(section)
+    }
 
-        let mut by_session_id: std::collections::HashMap<String, Learning> = std::collections::HashMap::new();
-        for line in reader.lines() {
-            let line = line?;
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
+    /// Remember which profile section a fact belongs to
+    pub fn store_profile_fact_section(&self, text: &str, section: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO profile_facts (text, section) VALUES (?, ?)",
+            params![text, section],
+        )?;
+        Ok(())
+    }
+
+    /// Last written lines of a profile section, with the hash of the facts they were written from
+    pub fn profile_section(&self, section: &str) -> Result<Option<(String, Vec<ProfileLine>)>> {
+        let row: Option<(String, String)> = self
+            .conn
+            .query_row(
+                "SELECT input_hash, lines FROM profile_sections WHERE section = ?",
+                params![section],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(row.map(|(hash, lines)| (hash, serde_json::from_str(&lines).unwrap_or_default())))
+    }
+
+    /// Store a written profile section
+    pub fn store_profile_section(&self, section: &str, input_hash: &str, lines: &[ProfileLine]) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO profile_sections (section, input_hash, lines) VALUES (?, ?, ?)",
+            params![section, input_hash, serde_json::to_string(lines)?],
+        )?;
+        Ok(())
+    }
+
+    /// Load the entity graph with the sessions behind each entity and relation
+    pub fn load_graph(&self) -> Result<Graph> {
+        let mut stmt = self.conn.prepare("SELECT id, name, kind FROM entities ORDER BY name COLLATE NOCASE")?;
+        let mut entities = stmt
+            .query_map([], |row| {
+                Ok(GraphEntity {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    kind: row.get(2)?,
+                    sessions: Vec::new(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to load entities")?;
+
+        let index: HashMap<i64, usize> = entities.iter().enumerate().map(|(i, e)| (e.id, i)).collect();
+        let mut stmt = self
+            .conn
+            .prepare("SELECT entity_id, session_id, date FROM entity_mentions ORDER BY date, session_id")?;
+        let mentions = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (id, session_id, date) in mentions {
+            if let Some(&i) = index.get(&id) {
+                entities[i].sessions.push(Mention { session_id, date });
             }
-            let learning: Learning = serde_json::from_str(line)
-                .context("Failed to parse learning JSON")?;
-            by_session_id.insert(learning.session_id.clone(), learning);
         }
 
-        let mut out: Vec<(Learning, f32)> = Vec::new();
-        for (session_id, rank) in rows {
-            if let Some(learning) = by_session_id.remove(&session_id) {
-                out.push((learning, rank));
+        let mut stmt = self.conn.prepare(
+            "SELECT r.id, s.name, r.predicate, o.name FROM relations r \
+             JOIN entities s ON s.id = r.subject_id \
+             JOIN entities o ON o.id = r.object_id \
+             ORDER BY s.name COLLATE NOCASE, r.predicate, o.name COLLATE NOCASE",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    GraphRelation {
+                        subject: row.get(1)?,
+                        predicate: row.get(2)?,
+                        object: row.get(3)?,
+                        sessions: Vec::new(),
+                    },
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to load relations")?;
+
+        let index: HashMap<i64, usize> = rows.iter().enumerate().map(|(i, (id, _))| (*id, i)).collect();
+        let mut relations: Vec<GraphRelation> = rows.into_iter().map(|(_, r)| r).collect();
+        let mut stmt = self
+            .conn
+            .prepare("SELECT relation_id, session_id, date FROM relation_sources ORDER BY date, session_id")?;
+        let sources = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (id, session_id, date) in sources {
+            if let Some(&i) = index.get(&id) {
+                relations[i].sessions.push(Mention { session_id, date });
             }
         }
 
-        Ok(out)
+        Ok(Graph { entities, relations })
+    }
+
+    /// Load tracked action items, oldest first, optionally with one status only
+    pub fn load_action_items(&self, status: Option<ActionStatus>) -> Result<Vec<ActionItem>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, session_id, agent, text, date, status, created_at, closed_at, closed_by, note \
+             FROM action_items WHERE (?1 IS NULL OR status = ?1) ORDER BY date, created_at, rowid",
+        )?;
+
+        let items = stmt
+            .query_map(params![status.map(|s| s.as_str())], action_item_from_row)?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to load action items")?;
+
+        Ok(items)
+    }
+
+    /// Change an action item's status.
+    ///
+    /// Closing records the time, and the finishing session when harvest
+    /// detected it; reopening clears both.
+    pub fn set_action_status(
+        &self,
+        id: &str,
+        status: ActionStatus,
+        closed_by: Option<&str>,
+        note: Option<&str>,
+    ) -> Result<()> {
+        let closed_at = (status != ActionStatus::Open).then(|| chrono::Utc::now().to_rfc3339());
+        let updated = self.conn.execute(
+            "UPDATE action_items SET status = ?, closed_at = ?, closed_by = ?, note = ? WHERE id = ?",
+            params![status.as_str(), closed_at, closed_by, note, id],
+        )?;
+
+        if updated == 0 {
+            anyhow::bail!("No action item with id '{}'", id);
+        }
+        Ok(())
+    }
+
+    /// BM25-ranked lexical search over harvested learnings.
+    ///
+    /// Returns (Learning, rank) ordered by ascending rank (lower is better),
+    /// optionally restricted to one agent's learnings.
+    pub fn search_learnings(&self, query: &str, limit: usize, agent: Option<&str>) -> Result<Vec<(Learning, f32)>> {
+        let safe_query = sanitize_fts_query(query);
+        if safe_query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let columns = LEARNING_COLUMNS
+            .split(", ")
+            .map(|c| format!("learnings.{}", c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}, bm25(learnings_fts) as rank \
+             FROM learnings_fts \
+             JOIN learnings ON learnings.id = learnings_fts.rowid \
+             WHERE learnings_fts MATCH ?1 AND (?2 IS NULL OR learnings.agent = ?2) \
+             ORDER BY rank \
+             LIMIT ?3",
+            columns
+        ))?;
+
+        let results = stmt
+            .query_map(params![safe_query, agent, limit as i64], |row| {
+                Ok((learning_from_row(row)?, row.get::<_, f32>(LEARNING_COLUMN_COUNT)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to run learnings BM25 search")?;
+
+        Ok(results)
     }
 
     /// Load chunks by rowids (for pre-filtered semantic search).
@@ -403,7 +1065,7 @@ impl Database {
         // Build parameterized IN clause
         let placeholders: Vec<String> = rowids.iter().map(|_| "?".to_string()).collect();
         let sql = format!(
-            "SELECT rowid, id, session_id, turn_start, turn_end, timestamp, text, embedding \
+            "SELECT rowid, id, session_id, agent, turn_start, turn_end, timestamp, text, embedding \
              FROM chunks WHERE rowid IN ({})",
             placeholders.join(",")
         );
@@ -416,15 +1078,16 @@ impl Database {
 
         let chunks = stmt
             .query_map(params.as_slice(), |row| {
-                let embedding_bytes: Vec<u8> = row.get(7)?;
+                let embedding_bytes: Vec<u8> = row.get(8)?;
                 Ok(StoredChunk {
                     rowid: row.get(0)?,
                     id: row.get(1)?,
                     session_id: row.get(2)?,
-                    turn_start: row.get(3)?,
-                    turn_end: row.get(4)?,
-                    timestamp: row.get(5)?,
-                    text: row.get(6)?,
+                    agent: row.get(3)?,
+                    turn_start: row.get(4)?,
+                    turn_end: row.get(5)?,
+                    timestamp: row.get(6)?,
+                    text: row.get(7)?,
                     embedding: bytes_to_embedding(&embedding_bytes),
                 })
             })?
@@ -459,6 +1122,206 @@ impl Database {
     }
 }
 
+/// Insert or replace a learning on a connection (or open transaction)
+fn upsert_learning(conn: &Connection, learning: &Learning) -> Result<()> {
+    conn.execute(
+        "INSERT INTO learnings (session_id, agent, date, topics, decisions, facts_learned, \
+         action_items, categories, summary, message_count, cost, harvested_at, version, \
+         harvested_messages, harvested_bytes, entities, relations, provenance) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18) \
+         ON CONFLICT(session_id) DO UPDATE SET \
+         agent = excluded.agent, date = excluded.date, topics = excluded.topics, \
+         decisions = excluded.decisions, facts_learned = excluded.facts_learned, \
+         action_items = excluded.action_items, categories = excluded.categories, \
+         entities = excluded.entities, relations = excluded.relations, \
+         provenance = excluded.provenance, summary = excluded.summary, message_count = excluded.message_count, \
+         cost = excluded.cost, harvested_at = excluded.harvested_at, \
+         version = excluded.version, harvested_messages = excluded.harvested_messages, \
+         harvested_bytes = excluded.harvested_bytes",
+        params![
+            learning.session_id,
+            learning.agent,
+            learning.date,
+            serde_json::to_string(&learning.topics)?,
+            serde_json::to_string(&learning.decisions)?,
+            serde_json::to_string(&learning.facts_learned)?,
+            serde_json::to_string(&learning.action_items)?,
+            serde_json::to_string(&learning.categories)?,
+            learning.summary,
+            learning.message_count as i64,
+            learning.cost,
+            learning.harvested_at,
+            learning.version,
+            learning.harvested_messages as i64,
+            learning.harvested_bytes as i64,
+            serde_json::to_string(&learning.entities)?,
+            serde_json::to_string(&learning.relations)?,
+            serde_json::to_string(&learning.provenance)?,
+        ],
+    )
+    .with_context(|| format!("Failed to store learning for {}", learning.session_id))?;
+
+    sync_action_items(conn, learning)?;
+    sync_graph(conn, learning)
+}
+
+/// Replace a session's contribution to the entity graph, then drop entities
+/// and relations no session mentions anymore
+fn sync_graph(conn: &Connection, learning: &Learning) -> Result<()> {
+    conn.execute("DELETE FROM entity_mentions WHERE session_id = ?", params![learning.session_id])?;
+    conn.execute("DELETE FROM relation_sources WHERE session_id = ?", params![learning.session_id])?;
+
+    let mention = |name: &str, kind: &str| -> Result<Option<i64>> {
+        let key = graph::entity_key(name);
+        if key.is_empty() {
+            return Ok(None);
+        }
+        // The first spelling seen is kept; a specific type wins over "other"
+        // (entities only named in a relation)
+        conn.execute(
+            "INSERT INTO entities (key, name, kind) VALUES (?1, ?2, ?3) \
+             ON CONFLICT(key) DO UPDATE SET \
+             kind = CASE WHEN excluded.kind = 'other' THEN kind ELSE excluded.kind END",
+            params![key, name.trim(), kind],
+        )?;
+        let id: i64 = conn.query_row("SELECT id FROM entities WHERE key = ?", params![key], |row| row.get(0))?;
+        conn.execute(
+            "INSERT OR IGNORE INTO entity_mentions (entity_id, session_id, date) VALUES (?, ?, ?)",
+            params![id, learning.session_id, learning.date],
+        )?;
+        Ok(Some(id))
+    };
+
+    for entity in &learning.entities {
+        mention(&entity.name, &entity.kind)?;
+    }
+
+    for relation in &learning.relations {
+        let predicate = relation.predicate.trim().to_lowercase();
+        let (Some(subject), Some(object)) = (mention(&relation.subject, "other")?, mention(&relation.object, "other")?)
+        else {
+            continue;
+        };
+        if predicate.is_empty() {
+            continue;
+        }
+
+        conn.execute(
+            "INSERT OR IGNORE INTO relations (subject_id, predicate, object_id) VALUES (?, ?, ?)",
+            params![subject, predicate, object],
+        )?;
+        let id: i64 = conn.query_row(
+            "SELECT id FROM relations WHERE subject_id = ? AND predicate = ? AND object_id = ?",
+            params![subject, predicate, object],
+            |row| row.get(0),
+        )?;
+        conn.execute(
+            "INSERT OR IGNORE INTO relation_sources (relation_id, session_id, date) VALUES (?, ?, ?)",
+            params![id, learning.session_id, learning.date],
+        )?;
+    }
+
+    conn.execute_batch(
+        "DELETE FROM relations WHERE id NOT IN (SELECT relation_id FROM relation_sources);
+         DELETE FROM entities WHERE id NOT IN (SELECT entity_id FROM entity_mentions);",
+    )
+    .with_context(|| format!("Failed to update entity graph for {}", learning.session_id))?;
+
+    Ok(())
+}
+
+/// Track a learning's action items, keeping the status of ones already known.
+///
+/// Open items that a re-harvest no longer lists are removed; closed ones stay
+/// as history.
+fn sync_action_items(conn: &Connection, learning: &Learning) -> Result<()> {
+    let ids: Vec<String> = learning
+        .action_items
+        .iter()
+        .map(|text| crate::todo::item_id(&learning.session_id, text))
+        .collect();
+
+    for (id, text) in ids.iter().zip(&learning.action_items) {
+        conn.execute(
+            "INSERT INTO action_items (id, session_id, agent, text, date, created_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6) \
+             ON CONFLICT(id) DO UPDATE SET agent = excluded.agent, text = excluded.text, date = excluded.date",
+            params![id, learning.session_id, learning.agent, text, learning.date, learning.harvested_at],
+        )
+        .with_context(|| format!("Failed to track action items for {}", learning.session_id))?;
+    }
+
+    let stale: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT id FROM action_items WHERE session_id = ? AND status = 'open'")?;
+        let rows = stmt.query_map(params![learning.session_id], |row| row.get::<_, String>(0))?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+    for id in stale.iter().filter(|id| !ids.contains(id)) {
+        conn.execute("DELETE FROM action_items WHERE id = ?", params![id])?;
+    }
+
+    Ok(())
+}
+
+fn action_item_from_row(row: &rusqlite::Row) -> rusqlite::Result<ActionItem> {
+    let status: String = row.get(5)?;
+    Ok(ActionItem {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        agent: row.get(2)?,
+        text: row.get(3)?,
+        date: row.get(4)?,
+        status: ActionStatus::parse(&status).unwrap_or(ActionStatus::Open),
+        created_at: row.get(6)?,
+        closed_at: row.get(7)?,
+        closed_by: row.get(8)?,
+        note: row.get(9)?,
+    })
+}
+
+/// Columns read by `learning_from_row`, in order
+const LEARNING_COLUMNS: &str = "session_id, agent, date, topics, decisions, facts_learned, action_items, \
+categories, summary, message_count, cost, harvested_at, version, harvested_messages, harvested_bytes, \
+entities, relations, provenance";
+
+const LEARNING_COLUMN_COUNT: usize = 18;
+
+fn learning_from_row(row: &rusqlite::Row) -> rusqlite::Result<Learning> {
+    fn json<T: serde::de::DeserializeOwned + Default>(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<T> {
+        let text: String = row.get(idx)?;
+        Ok(serde_json::from_str(&text).unwrap_or_default())
+    }
+
+    Ok(Learning {
+        session_id: row.get(0)?,
+        agent: row.get(1)?,
+        date: row.get(2)?,
+        topics: json(row, 3)?,
+        decisions: json(row, 4)?,
+        facts_learned: json(row, 5)?,
+        action_items: json(row, 6)?,
+        categories: json(row, 7)?,
+        summary: row.get(8)?,
+        message_count: row.get::<_, i64>(9)? as usize,
+        cost: row.get(10)?,
+        harvested_at: row.get(11)?,
+        version: row.get(12)?,
+        harvested_messages: row.get::<_, i64>(13)? as usize,
+        harvested_bytes: row.get::<_, i64>(14)? as u64,
+        entities: json(row, 15)?,
+        relations: json(row, 16)?,
+        provenance: json(row, 17)?,
+    })
+}
+
+/// Result of importing learnings from JSONL
+#[derive(Debug, Default)]
+pub struct ImportStats {
+    pub imported: usize,
+    /// Lines that weren't a valid learning
+    pub skipped: usize,
+}
+
 #[derive(Debug)]
 pub struct DbStats {
     pub chunks_count: usize,
@@ -487,6 +1350,16 @@ fn sanitize_fts_query(query: &str) -> String {
         .join(" ")
 }
 
+/// Sanitize a user query for FTS5 MATCH, matching rows with *any* term.
+///
+/// Same quoting as `sanitize_fts_query`, but terms are OR-ed together.
+fn sanitize_fts_query_any(query: &str) -> String {
+    sanitize_fts_query(query)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
 /// Convert embedding vector to bytes for storage
 fn embedding_to_bytes(embedding: &[f32]) -> Vec<u8> {
     embedding
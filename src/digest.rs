@@ -0,0 +1,388 @@
+//! Digest - weekly and monthly rollups
+//!
+//! Where `export::write_rollup` covers one day, a digest covers an ISO week or
+//! a calendar month: that period's learnings grouped by theme, its decisions in
+//! order, the state of the action items it raised, and usage compared with the
+//! period before. Digests go to `memory/weekly/` and `memory/monthly/` inside a
+//! generated block, so rerunning one keeps any notes written around it.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::db::Database;
+use crate::export::{self, format_stats_summary};
+use crate::harvest::Learning;
+use crate::session::{short_id, TokenStats};
+use crate::stats::{self, AggregatedStats, StatsConfig};
+use crate::todo::{ActionItem, ActionStatus};
+use crate::Paths;
+
+/// Most theme sections in a digest; smaller themes go under "Other"
+const MAX_THEMES: usize = 8;
+
+/// Whether a digest covers a week or a month
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeriodKind {
+    Week,
+    Month,
+}
+
+/// The dates a digest covers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Period {
+    pub kind: PeriodKind,
+    pub start: NaiveDate,
+    /// Last day, inclusive
+    pub end: NaiveDate,
+}
+
+impl Period {
+    /// Parse an ISO week like `2026-W41`
+    pub fn week(spec: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid week '{}' (expected YYYY-Www, e.g. 2026-W41)", spec);
+        let (year, week) = spec.split_once(['-', ' ']).ok_or_else(invalid)?;
+        let week = week.trim_start_matches(['W', 'w']);
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        let week: u32 = week.parse().map_err(|_| invalid())?;
+        let start = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).ok_or_else(invalid)?;
+        Ok(Self::week_of(start))
+    }
+
+    /// Parse a month like `2026-10`
+    pub fn month(spec: &str) -> Result<Self> {
+        let start = NaiveDate::parse_from_str(&format!("{}-01", spec), "%Y-%m-%d")
+            .map_err(|_| anyhow!("Invalid month '{}' (expected YYYY-MM, e.g. 2026-10)", spec))?;
+        Ok(Self::month_of(start))
+    }
+
+    /// The ISO week containing `date`
+    pub fn week_of(date: NaiveDate) -> Self {
+        let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+        Self {
+            kind: PeriodKind::Week,
+            start,
+            end: start + Duration::days(6),
+        }
+    }
+
+    /// The calendar month containing `date`
+    pub fn month_of(date: NaiveDate) -> Self {
+        let start = date.with_day(1).unwrap_or(date);
+        let next = if start.month() == 12 {
+            NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+        };
+        Self {
+            kind: PeriodKind::Month,
+            start,
+            end: next.map(|n| n - Duration::days(1)).unwrap_or(start),
+        }
+    }
+
+    /// The period just before this one
+    pub fn previous(&self) -> Self {
+        let day_before = self.start - Duration::days(1);
+        match self.kind {
+            PeriodKind::Week => Self::week_of(day_before),
+            PeriodKind::Month => Self::month_of(day_before),
+        }
+    }
+
+    /// `2026-W41` or `2026-10`
+    pub fn label(&self) -> String {
+        match self.kind {
+            PeriodKind::Week => {
+                let week = self.start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            PeriodKind::Month => self.start.format("%Y-%m").to_string(),
+        }
+    }
+
+    /// Heading for the digest file
+    pub fn title(&self) -> String {
+        match self.kind {
+            PeriodKind::Week => format!(
+                "Week {} ({} – {})",
+                self.label(),
+                self.start.format("%b %-d"),
+                self.end.format("%b %-d, %Y")
+            ),
+            PeriodKind::Month => self.start.format("%B %Y").to_string(),
+        }
+    }
+
+    fn noun(&self) -> &'static str {
+        match self.kind {
+            PeriodKind::Week => "week",
+            PeriodKind::Month => "month",
+        }
+    }
+
+    fn contains(&self, date: &str) -> bool {
+        NaiveDate::parse_from_str(date.get(..10).unwrap_or(date), "%Y-%m-%d")
+            .map(|d| d >= self.start && d <= self.end)
+            .unwrap_or(false)
+    }
+
+    /// Where this period's digest is written
+    pub fn path(&self, paths: &Paths) -> PathBuf {
+        let dir = match self.kind {
+            PeriodKind::Week => "weekly",
+            PeriodKind::Month => "monthly",
+        };
+        paths.memory_dir.join(dir).join(format!("{}.md", self.label()))
+    }
+}
+
+/// Summary of a written digest
+pub struct DigestStats {
+    pub sessions: usize,
+    pub decisions: usize,
+    pub open: usize,
+    pub completed: usize,
+    pub path: PathBuf,
+}
+
+/// Write the digest for `period`, optionally for one agent only
+pub fn write_digest(paths: &Paths, period: &Period, agent: Option<&str>) -> Result<DigestStats> {
+    let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+
+    let mut learnings: Vec<Learning> = db
+        .load_learnings(agent)?
+        .into_iter()
+        .filter(|l| period.contains(&l.date))
+        .collect();
+    learnings.sort_by(|a, b| a.date.cmp(&b.date));
+
+    let mut items = db.load_action_items(None)?;
+    if let Some(agent) = agent {
+        items.retain(|i| i.agent == agent);
+    }
+
+    let stats_for = |period: &Period| {
+        stats::collect(
+            paths,
+            StatsConfig {
+                since: Some(period.start),
+                until: Some(period.end),
+                agent: agent.map(|a| a.to_string()),
+            },
+        )
+    };
+    let current = stats_for(period)?;
+    let previous = stats_for(&period.previous())?;
+
+    // Items raised this period, plus earlier ones finished during it
+    let raised: Vec<&ActionItem> = items.iter().filter(|i| period.contains(&i.date)).collect();
+    let open: Vec<&ActionItem> = raised.iter().copied().filter(|i| i.status == ActionStatus::Open).collect();
+    let completed: Vec<&ActionItem> = items
+        .iter()
+        .filter(|i| i.status == ActionStatus::Done)
+        .filter(|i| period.contains(&i.date) || i.closed_at.as_deref().map(|t| period.contains(t)).unwrap_or(false))
+        .collect();
+    let dropped: Vec<&ActionItem> = raised.iter().copied().filter(|i| i.status == ActionStatus::Dropped).collect();
+
+    let decisions: Vec<(&Learning, &String)> = learnings
+        .iter()
+        .flat_map(|l| l.decisions.iter().map(move |d| (l, d)))
+        .collect();
+
+    let mut content = String::from("## Profundo Digest\n\n");
+    content.push_str(&format!(
+        "**Sessions**: {} with learnings | **Decisions**: {} | **Action items**: {} open, {} completed\n\n",
+        learnings.len(),
+        decisions.len(),
+        open.len(),
+        completed.len()
+    ));
+
+    if learnings.is_empty() {
+        content.push_str(&format!("_No learnings harvested this {}._\n\n", period.noun()));
+    } else {
+        content.push_str("### Themes\n\n");
+        for (theme, members) in group_by_theme(&learnings) {
+            let sessions = if members.len() == 1 { "session" } else { "sessions" };
+            content.push_str(&format!("#### {} ({} {})\n\n", theme, members.len(), sessions));
+            for learning in members {
+                content.push_str(&format!("- {} _({} `{}`)_\n", learning.summary, learning.date, short_id(&learning.session_id)));
+            }
+            content.push('\n');
+        }
+    }
+
+    if !decisions.is_empty() {
+        content.push_str("### Decisions\n\n");
+        for (i, (learning, decision)) in decisions.iter().enumerate() {
+            content.push_str(&format!("{}. {} _({})_\n", i + 1, decision, learning.date));
+        }
+        content.push('\n');
+    }
+
+    if !open.is_empty() || !completed.is_empty() || !dropped.is_empty() {
+        content.push_str("### Action Items\n\n");
+        if !open.is_empty() {
+            content.push_str("**Open**\n\n");
+            for item in &open {
+                content.push_str(&format!("- [ ] {} _(raised {})_\n", item.text, item.date));
+            }
+            content.push('\n');
+        }
+        if !completed.is_empty() {
+            content.push_str("**Completed**\n\n");
+            for item in &completed {
+                content.push_str(&format!("- [x] {} _(raised {}{})_\n", item.text, item.date, closed_on(item)));
+            }
+            content.push('\n');
+        }
+        if !dropped.is_empty() {
+            content.push_str("**Dropped**\n\n");
+            for item in &dropped {
+                content.push_str(&format!("- ~~{}~~ _(raised {}{})_\n", item.text, item.date, closed_on(item)));
+            }
+            content.push('\n');
+        }
+    }
+
+    content.push_str("### Usage\n\n");
+    content.push_str(&format_trends(&current, &previous, period));
+
+    let path = period.path(paths);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let existing = if path.exists() {
+        fs::read_to_string(&path)?
+    } else {
+        format!("# {}\n", period.title())
+    };
+    export::write_atomic(&path, &export::splice_generated(&existing, &export::generated_block(&content)))?;
+
+    Ok(DigestStats {
+        sessions: learnings.len(),
+        decisions: decisions.len(),
+        open: open.len(),
+        completed: completed.len(),
+        path,
+    })
+}
+
+/// Group learnings under their most common topic in the period. Topics shared
+/// by fewer than two sessions, and themes past `MAX_THEMES`, go under "Other".
+fn group_by_theme(learnings: &[Learning]) -> Vec<(String, Vec<&Learning>)> {
+    let key = |topic: &str| topic.trim().to_lowercase();
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut spelling: HashMap<String, &str> = HashMap::new();
+    for learning in learnings {
+        let mut seen = std::collections::HashSet::new();
+        for topic in learning.topics.iter().filter(|t| !t.trim().is_empty()) {
+            if seen.insert(key(topic)) {
+                *counts.entry(key(topic)).or_default() += 1;
+                spelling.entry(key(topic)).or_insert(topic.trim());
+            }
+        }
+    }
+
+    let mut groups: Vec<(String, Vec<&Learning>)> = Vec::new();
+    let mut other = Vec::new();
+    for learning in learnings {
+        let best = learning
+            .topics
+            .iter()
+            .map(|t| key(t))
+            .filter(|k| counts.get(k).copied().unwrap_or(0) >= 2)
+            .max_by(|a, b| counts[a].cmp(&counts[b]).then_with(|| b.cmp(a)));
+        match best {
+            Some(theme) => match groups.iter_mut().find(|(k, _)| *k == theme) {
+                Some((_, members)) => members.push(learning),
+                None => groups.push((theme, vec![learning])),
+            },
+            None => other.push(learning),
+        }
+    }
+
+    groups.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+    let keep = MAX_THEMES.min(groups.len());
+    for (_, members) in groups.drain(keep..) {
+        other.extend(members);
+    }
+    other.sort_by(|a, b| a.date.cmp(&b.date));
+
+    let mut themes: Vec<(String, Vec<&Learning>)> = groups
+        .into_iter()
+        .map(|(k, members)| (spelling.get(&k).map(|s| s.to_string()).unwrap_or(k), members))
+        .collect();
+    if !other.is_empty() {
+        themes.push(("Other".to_string(), other));
+    }
+    themes
+}
+
+/// Usage this period against the one before, as a markdown table
+fn format_trends(current: &AggregatedStats, previous: &AggregatedStats, period: &Period) -> String {
+    if current.session_count == 0 && previous.session_count == 0 {
+        return "_No sessions in this or the previous period._\n".to_string();
+    }
+
+    let row = |name: &str, now: f64, before: f64, fmt: &dyn Fn(f64) -> String| {
+        format!("| {} | {} | {} | {} |\n", name, fmt(now), fmt(before), change(now, before))
+    };
+    let tokens = |n: f64| format!("{:.1}K", n / 1000.0);
+    let total = |s: &TokenStats| (s.input_tokens + s.output_tokens) as f64;
+
+    let mut table = format!(
+        "{}\n\n| | {} | {} | Change |\n|---|---|---|---|\n",
+        format_stats_summary(&current.total),
+        period.label(),
+        period.previous().label()
+    );
+    table.push_str(&row(
+        "Sessions",
+        current.session_count as f64,
+        previous.session_count as f64,
+        &|n| format!("{}", n),
+    ));
+    table.push_str(&row("Tokens", total(&current.total), total(&previous.total), &tokens));
+    table.push_str(&row(
+        "Input tokens",
+        current.total.input_tokens as f64,
+        previous.total.input_tokens as f64,
+        &tokens,
+    ));
+    table.push_str(&row(
+        "Output tokens",
+        current.total.output_tokens as f64,
+        previous.total.output_tokens as f64,
+        &tokens,
+    ));
+    table.push_str(&row(
+        "Cache hit rate",
+        current.total.cache_hit_rate() * 100.0,
+        previous.total.cache_hit_rate() * 100.0,
+        &|n| format!("{:.0}%", n),
+    ));
+    table.push_str(&row(
+        "Cost",
+        current.total.total_cost,
+        previous.total.total_cost,
+        &|n| format!("${:.2}", n),
+    ));
+    table
+}
+
+/// Relative change, e.g. `+12%`
+fn change(now: f64, before: f64) -> String {
+    if before == 0.0 {
+        return if now == 0.0 { "–".to_string() } else { "new".to_string() };
+    }
+    format!("{:+.0}%", (now - before) / before * 100.0)
+}
+
+fn closed_on(item: &ActionItem) -> String {
+    item.closed_on().map(|d| format!(", closed {}", d)).unwrap_or_default()
+}
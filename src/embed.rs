@@ -9,11 +9,12 @@ use std::path::Path;
 use walkdir::WalkDir;
 
 use crate::db::Database;
-use crate::openrouter::OpenRouterClient;
+use crate::openrouter::{Embedder, OpenRouterClient};
 use crate::session::Session;
 use crate::Paths;
 
 /// Configuration for the embedding pipeline
+#[derive(Clone, Debug)]
 pub struct EmbedConfig {
     /// Number of conversation turns per chunk
     pub chunk_size: usize,
@@ -21,6 +22,8 @@ pub struct EmbedConfig {
     pub overlap: usize,
     /// Process all sessions, even if already processed
     pub force_reprocess: bool,
+    /// Only embed this agent's sessions (all agents if None)
+    pub agent: Option<String>,
 }
 
 impl Default for EmbedConfig {
@@ -29,6 +32,7 @@ impl Default for EmbedConfig {
             chunk_size: 5,
             overlap: 2,
             force_reprocess: false,
+            agent: None,
         }
     }
 }
@@ -38,7 +42,12 @@ pub async fn run(paths: &Paths, config: EmbedConfig) -> Result<EmbedStats> {
     let client = OpenRouterClient::from_env()?;
     let mut db = Database::open(&paths.db_path)?;
 
-    let sessions = discover_sessions(&paths.sessions_dir)?;
+    let mut sessions = Vec::new();
+    for agent in paths.session_dirs(config.agent.as_deref()) {
+        sessions.extend(discover_sessions(&agent.sessions_dir)?);
+    }
+    sessions.sort_by_key(|(_, _, _, mtime)| *mtime);
+
     println!(
         "{} Found {} session files",
         "→".blue(),
@@ -78,7 +87,7 @@ pub async fn run(paths: &Paths, config: EmbedConfig) -> Result<EmbedStats> {
     );
 
     for (session_id, path, size, mtime) in to_process {
-        pb.set_message(format!("{}", session_id));
+        pb.set_message(session_id.to_string());
 
         match process_session(&mut db, &client, &path, &session_id, size, mtime, &config).await {
             Ok(chunks_count) => {
@@ -113,9 +122,9 @@ pub async fn run(paths: &Paths, config: EmbedConfig) -> Result<EmbedStats> {
 }
 
 /// Process a single session file
-async fn process_session(
+pub(crate) async fn process_session<E: Embedder>(
     db: &mut Database,
-    client: &OpenRouterClient,
+    client: &E,
     path: &Path,
     session_id: &str,
     size: u64,
@@ -124,6 +133,7 @@ async fn process_session(
 ) -> Result<usize> {
     // Parse session
     let session = Session::from_file(path)?;
+    let agent = session.agent.clone();
 
     // Extract chunks
     let chunks = session.extract_text_chunks(config.chunk_size, config.overlap);
@@ -132,6 +142,7 @@ async fn process_session(
         // Still mark as processed to avoid re-checking
         db.store_chunks(
             session_id,
+            &agent,
             path.to_str().unwrap_or(""),
             size,
             mtime,
@@ -147,12 +158,13 @@ async fn process_session(
     // Pair chunks with embeddings
     let chunk_embeddings: Vec<_> = chunks
         .into_iter()
-        .zip(embeddings.into_iter())
+        .zip(embeddings)
         .collect();
 
     // Store in database
     db.store_chunks(
         session_id,
+        &agent,
         path.to_str().unwrap_or(""),
         size,
         mtime,
@@ -163,7 +175,7 @@ async fn process_session(
 }
 
 /// Discover all session files
-fn discover_sessions(sessions_dir: &Path) -> Result<Vec<(String, std::path::PathBuf, u64, i64)>> {
+pub(crate) fn discover_sessions(sessions_dir: &Path) -> Result<Vec<(String, std::path::PathBuf, u64, i64)>> {
     let mut sessions = Vec::new();
 
     for entry in WalkDir::new(sessions_dir)
@@ -213,3 +225,83 @@ pub struct EmbedStats {
     pub chunks_created: usize,
     pub errors: usize,
 }
+
+/// Deterministic, offline embedder based on feature hashing.
+///
+/// Maps lowercase word tokens into a fixed number of buckets with a stable
+/// hash. Only lexical overlap is captured, so scores are not comparable with
+/// real embedding models — it exists so `profundo eval` can run without network.
+pub struct HashEmbedder {
+    dim: usize,
+}
+
+impl HashEmbedder {
+    pub fn new(dim: usize) -> Self {
+        Self { dim: dim.max(1) }
+    }
+
+    /// Embed a single text
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        const STOPWORDS: &[&str] = &[
+            "the", "and", "for", "you", "that", "this", "with", "are", "was", "but", "not",
+            "have", "has", "can", "will", "what", "about", "from", "user", "assistant",
+        ];
+
+        let mut counts: std::collections::HashMap<&str, f32> = std::collections::HashMap::new();
+        let lower = text.to_lowercase();
+        for token in lower.split(|c: char| !c.is_alphanumeric()) {
+            if token.len() < 2 || STOPWORDS.contains(&token) {
+                continue;
+            }
+            *counts.entry(token).or_insert(0.0) += 1.0;
+        }
+
+        let mut vector = vec![0.0f32; self.dim];
+        for (token, count) in counts {
+            let hash = fnv1a(token.as_bytes());
+            let bucket = (hash % self.dim as u64) as usize;
+            let sign = if (hash >> 63) == 0 { 1.0 } else { -1.0 };
+            // Sublinear term frequency
+            vector[bucket] += sign * (1.0 + count.ln());
+        }
+
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            for x in &mut vector {
+                *x /= norm;
+            }
+        }
+
+        vector
+    }
+}
+
+impl Default for HashEmbedder {
+    fn default() -> Self {
+        Self::new(512)
+    }
+}
+
+impl Embedder for HashEmbedder {
+    fn embed_batch(
+        &self,
+        texts: &[String],
+    ) -> impl std::future::Future<Output = Result<Vec<Vec<f32>>>> + Send {
+        let embeddings = texts.iter().map(|t| self.embed_text(t)).collect();
+        std::future::ready(Ok(embeddings))
+    }
+
+    fn model(&self) -> String {
+        format!("hash-{}", self.dim)
+    }
+}
+
+/// 64-bit FNV-1a (stable across platforms and Rust versions)
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
@@ -0,0 +1,438 @@
+//! Eval - retrieval quality evaluation
+//!
+//! Runs labeled queries against a session corpus under several retrieval
+//! configurations and reports recall@k, MRR and nDCG@k side by side.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::db::Database;
+use crate::embed::{self, EmbedConfig};
+use crate::openrouter::{ChatModel, Embedder};
+use crate::recall::{self, RecallConfig, SearchResult};
+
+/// A labeled query
+#[derive(Debug, Deserialize)]
+pub struct EvalCase {
+    pub query: String,
+    pub relevant: Vec<RelevantRange>,
+}
+
+/// A relevant passage: a session, optionally narrowed to an inclusive turn range
+#[derive(Debug, Deserialize)]
+pub struct RelevantRange {
+    pub session_id: String,
+    #[serde(default)]
+    pub turns: Option<(usize, usize)>,
+}
+
+impl RelevantRange {
+    /// Whether a retrieved chunk covering turns `[start, end)` overlaps this range
+    fn matches(&self, result: &SearchResult) -> bool {
+        if result.chunk.session_id != self.session_id {
+            return false;
+        }
+        match self.turns {
+            None => true,
+            Some((first, last)) => {
+                let start = result.chunk.turn_start.max(0) as usize;
+                let end = result.chunk.turn_end.max(0) as usize;
+                start <= last && end > first
+            }
+        }
+    }
+}
+
+/// A retrieval configuration to evaluate
+#[derive(Clone, Debug)]
+pub struct Variant {
+    pub name: String,
+    pub chunking: EmbedConfig,
+    pub recall: RecallConfig,
+}
+
+/// Metrics for one variant, averaged over all cases
+#[derive(Debug, Default, Serialize)]
+pub struct VariantReport {
+    pub name: String,
+    pub recall_at_k: f64,
+    pub mrr: f64,
+    pub ndcg_at_k: f64,
+    pub queries: usize,
+    /// Why the variant was not run, if it was skipped
+    pub skipped: Option<String>,
+}
+
+/// The built-in comparison grid: chunking, RRF constant, semantic-only and expansion
+pub fn default_variants(k: usize, threshold: f32) -> Vec<Variant> {
+    let base = RecallConfig {
+        top_k: k,
+        threshold,
+        ..RecallConfig::default()
+    };
+    let chunking = |chunk_size, overlap| EmbedConfig {
+        chunk_size,
+        overlap,
+        force_reprocess: true,
+        agent: None,
+    };
+
+    vec![
+        Variant {
+            name: "baseline (5/2, rrf 60)".to_string(),
+            chunking: chunking(5, 2),
+            recall: base.clone(),
+        },
+        Variant {
+            name: "semantic only".to_string(),
+            chunking: chunking(5, 2),
+            recall: RecallConfig {
+                semantic_only: true,
+                ..base.clone()
+            },
+        },
+        Variant {
+            name: "rrf 20".to_string(),
+            chunking: chunking(5, 2),
+            recall: RecallConfig {
+                rrf_k: 20.0,
+                ..base.clone()
+            },
+        },
+        Variant {
+            name: "rrf 120".to_string(),
+            chunking: chunking(5, 2),
+            recall: RecallConfig {
+                rrf_k: 120.0,
+                ..base.clone()
+            },
+        },
+        Variant {
+            name: "chunks 3/1".to_string(),
+            chunking: chunking(3, 1),
+            recall: base.clone(),
+        },
+        Variant {
+            name: "chunks 8/3".to_string(),
+            chunking: chunking(8, 3),
+            recall: base.clone(),
+        },
+        Variant {
+            name: "expand".to_string(),
+            chunking: chunking(5, 2),
+            recall: RecallConfig {
+                expand: true,
+                ..base
+            },
+        },
+    ]
+}
+
+/// Load labeled queries from a JSONL file
+pub fn load_cases(path: &Path) -> Result<Vec<EvalCase>> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open labels file: {}", path.display()))?;
+    let reader = BufReader::new(file);
+
+    let mut cases = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let case: EvalCase = serde_json::from_str(&line)
+            .with_context(|| format!("Invalid label on line {}", i + 1))?;
+        cases.push(case);
+    }
+
+    Ok(cases)
+}
+
+/// Evaluate every variant against the corpus.
+///
+/// Each distinct chunking is indexed once into an in-memory database. Variants
+/// that need query expansion are skipped when no chat backend is given.
+pub async fn run<E: Embedder, C: ChatModel>(
+    corpus_dir: &Path,
+    cases: &[EvalCase],
+    variants: &[Variant],
+    embedder: &E,
+    chat: Option<&C>,
+) -> Result<Vec<VariantReport>> {
+    let sessions = embed::discover_sessions(corpus_dir)?;
+    let mut reports = Vec::new();
+    let mut indexed: Option<((usize, usize), Database)> = None;
+
+    for variant in variants {
+        if variant.recall.expand && chat.is_none() {
+            reports.push(VariantReport {
+                name: variant.name.clone(),
+                skipped: Some("needs a chat model (--online)".to_string()),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let key = (variant.chunking.chunk_size, variant.chunking.overlap);
+        if indexed.as_ref().map(|(k, _)| *k != key).unwrap_or(true) {
+            let mut db = Database::open_in_memory()?;
+            for (session_id, path, size, mtime) in &sessions {
+                embed::process_session(
+                    &mut db,
+                    embedder,
+                    path,
+                    session_id,
+                    *size,
+                    *mtime,
+                    &variant.chunking,
+                )
+                .await
+                .with_context(|| format!("Failed to index {}", session_id))?;
+            }
+            indexed = Some((key, db));
+        }
+        let (_, db) = indexed.as_ref().expect("indexed above");
+
+        let mut report = VariantReport {
+            name: variant.name.clone(),
+            queries: cases.len(),
+            ..Default::default()
+        };
+
+        for case in cases {
+            let results =
+                recall::search_with(db, embedder, chat, &case.query, variant.recall.clone())
+                    .await?;
+            let (recall_at_k, rr, ndcg) = score_case(&results, &case.relevant, variant.recall.top_k);
+            report.recall_at_k += recall_at_k;
+            report.mrr += rr;
+            report.ndcg_at_k += ndcg;
+        }
+
+        if !cases.is_empty() {
+            let n = cases.len() as f64;
+            report.recall_at_k /= n;
+            report.mrr /= n;
+            report.ndcg_at_k /= n;
+        }
+
+        reports.push(report);
+    }
+
+    Ok(reports)
+}
+
+/// Score one query: (recall@k, reciprocal rank, nDCG@k) with binary relevance.
+///
+/// A result is a hit if it overlaps a relevant range not already matched by a
+/// higher-ranked result, so duplicate hits on the same passage don't inflate scores.
+fn score_case(results: &[SearchResult], relevant: &[RelevantRange], k: usize) -> (f64, f64, f64) {
+    if relevant.is_empty() {
+        return (0.0, 0.0, 0.0);
+    }
+
+    let mut matched = vec![false; relevant.len()];
+    let mut reciprocal_rank = 0.0;
+    let mut dcg = 0.0;
+
+    for (i, result) in results.iter().take(k).enumerate() {
+        let overlapping: Vec<usize> = relevant
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.matches(result))
+            .map(|(j, _)| j)
+            .collect();
+
+        if overlapping.is_empty() {
+            continue;
+        }
+
+        if reciprocal_rank == 0.0 {
+            reciprocal_rank = 1.0 / (i + 1) as f64;
+        }
+
+        if let Some(&j) = overlapping.iter().find(|&&j| !matched[j]) {
+            matched[j] = true;
+            dcg += 1.0 / ((i + 2) as f64).log2();
+        }
+    }
+
+    let ideal_hits = relevant.len().min(k);
+    let idcg: f64 = (0..ideal_hits).map(|i| 1.0 / ((i + 2) as f64).log2()).sum();
+
+    let recall_at_k = matched.iter().filter(|m| **m).count() as f64 / relevant.len() as f64;
+    let ndcg = if idcg > 0.0 { dcg / idcg } else { 0.0 };
+
+    (recall_at_k, reciprocal_rank, ndcg)
+}
+
+/// Display a comparison table, marking the best score in each column
+pub fn display(reports: &[VariantReport], k: usize) {
+    let ran: Vec<&VariantReport> = reports.iter().filter(|r| r.skipped.is_none()).collect();
+    let best = |f: fn(&VariantReport) -> f64| ran.iter().map(|r| f(r)).fold(0.0, f64::max);
+    let best_recall = best(|r| r.recall_at_k);
+    let best_mrr = best(|r| r.mrr);
+    let best_ndcg = best(|r| r.ndcg_at_k);
+
+    let cell = |value: f64, best: f64| {
+        let text = format!("{:>9.3}", value);
+        if value > 0.0 && (value - best).abs() < 1e-9 {
+            text.green().bold().to_string()
+        } else {
+            text
+        }
+    };
+
+    println!(
+        "\n{}",
+        format!(
+            "{:<26} {:>9} {:>9} {:>9}",
+            "Configuration",
+            format!("Recall@{}", k),
+            "MRR",
+            format!("nDCG@{}", k)
+        )
+        .bold()
+    );
+    println!("{}", "─".repeat(56));
+
+    for report in reports {
+        match &report.skipped {
+            Some(reason) => println!(
+                "{:<26} {}",
+                report.name,
+                format!("skipped: {}", reason).dimmed()
+            ),
+            None => println!(
+                "{:<26} {} {} {}",
+                report.name,
+                cell(report.recall_at_k, best_recall),
+                cell(report.mrr, best_mrr),
+                cell(report.ndcg_at_k, best_ndcg)
+            ),
+        }
+    }
+
+    if let Some(report) = ran.first() {
+        println!("\n{} {} queries", "→".blue(), report.queries.to_string().cyan());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StoredChunk;
+    use crate::embed::HashEmbedder;
+    use crate::openrouter::CannedChat;
+
+    fn result(session_id: &str, turn_start: i32, turn_end: i32) -> SearchResult {
+        SearchResult {
+            chunk: StoredChunk {
+                rowid: 0,
+                id: crate::session::chunk_id(session_id, turn_start as usize, turn_end as usize),
+                session_id: session_id.to_string(),
+                agent: crate::DEFAULT_AGENT.to_string(),
+                turn_start,
+                turn_end,
+                timestamp: None,
+                text: String::new(),
+                embedding: Vec::new(),
+            },
+            similarity: 0.0,
+        }
+    }
+
+    fn relevant(session_id: &str, turns: Option<(usize, usize)>) -> RelevantRange {
+        RelevantRange {
+            session_id: session_id.to_string(),
+            turns,
+        }
+    }
+
+    fn assert_scores(actual: (f64, f64, f64), expected: (f64, f64, f64)) {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(
+            close(actual.0, expected.0) && close(actual.1, expected.1) && close(actual.2, expected.2),
+            "got {:?}, expected {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn scores_a_known_ranking() {
+        let relevant = [relevant("a", Some((0, 3))), relevant("b", None)];
+        // A miss, a hit on a, a second hit on the same passage, then b
+        let results = [result("x", 0, 5), result("a", 0, 5), result("a", 2, 6), result("b", 0, 3)];
+        let discount = |rank: usize| 1.0 / ((rank + 1) as f64).log2();
+        let ideal = discount(1) + discount(2);
+
+        // Only a is found in the top 3; the duplicate hit on it doesn't count twice
+        assert_scores(score_case(&results, &relevant, 3), (0.5, 0.5, discount(2) / ideal));
+        assert_scores(
+            score_case(&results, &relevant, 4),
+            (1.0, 0.5, (discount(2) + discount(4)) / ideal),
+        );
+    }
+
+    #[test]
+    fn turn_ranges_must_overlap() {
+        let relevant = [relevant("a", Some((4, 7)))];
+        // Chunk turns are end-exclusive: 0-4 stops short of turn 4
+        let results = [result("a", 0, 4), result("a", 3, 5)];
+        assert_scores(score_case(&results, &relevant, 5), (1.0, 0.5, 1.0 / 3f64.log2()));
+    }
+
+    #[test]
+    fn scores_zero_without_relevant_hits() {
+        let results = [result("x", 0, 5), result("y", 0, 5)];
+        assert_scores(score_case(&results, &[relevant("a", None)], 5), (0.0, 0.0, 0.0));
+        assert_scores(score_case(&[], &[relevant("a", None)], 5), (0.0, 0.0, 0.0));
+        assert_scores(score_case(&results, &[], 5), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn k_larger_than_the_results() {
+        let relevant = [relevant("a", None), relevant("b", None), relevant("c", None)];
+        let results = [result("b", 0, 5)];
+        // The ideal ranking still has all three relevant passages in the top 10
+        let discount = |rank: usize| 1.0 / ((rank + 1) as f64).log2();
+        let ideal = discount(1) + discount(2) + discount(3);
+        assert_scores(score_case(&results, &relevant, 10), (1.0 / 3.0, 1.0, 1.0 / ideal));
+    }
+
+    #[tokio::test]
+    async fn fixture_corpus_meets_the_offline_baseline() -> Result<()> {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/eval");
+        let cases = load_cases(&fixtures.join("labels.jsonl"))?;
+        let variants = default_variants(5, 0.0);
+
+        let reports = run(
+            &fixtures.join("sessions"),
+            &cases,
+            &variants,
+            &HashEmbedder::default(),
+            None::<&CannedChat>,
+        )
+        .await?;
+
+        assert_eq!(reports.len(), variants.len());
+        for report in &reports {
+            if report.name == "expand" {
+                assert!(report.skipped.is_some(), "expansion needs a chat model");
+                continue;
+            }
+            assert_eq!(report.skipped, None, "{}", report.name);
+            assert_eq!(report.queries, cases.len());
+            // Every query's best match is relevant, and nearly all relevant passages are found
+            assert_eq!(report.mrr, 1.0, "{} MRR", report.name);
+            assert!(report.recall_at_k >= 0.95, "{} recall@5 {}", report.name, report.recall_at_k);
+            assert!(report.ndcg_at_k >= 0.95, "{} nDCG@5 {}", report.name, report.ndcg_at_k);
+        }
+        Ok(())
+    }
+}
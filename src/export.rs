@@ -3,74 +3,568 @@
 //! Formats harvested learnings as markdown that Clawdbot can index via its
 //! native memory search.
 
-use anyhow::{Context, Result};
-use chrono::{Datelike, NaiveDate, Weekday};
-use std::fs::{self, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
-use std::path::Path;
-
+use anyhow::{anyhow, Context, Result};
+use chrono::{Datelike, NaiveDate, Utc, Weekday};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::categories::CategorySet;
+use crate::consolidate::CanonicalItem;
+use crate::db::Database;
+use crate::digest::Period;
 use crate::harvest::Learning;
 use crate::session::TokenStats;
-use crate::stats::{self, StatsConfig};
+use crate::stats::{self, AggregatedStats, SessionUsage, StatsConfig};
+use crate::template::{CanonicalView, DayView, ExportContext, LearningView, RollupContext, StatsView, Templates};
+use crate::todo::{self, ActionItem, ActionStatus};
 use crate::Paths;
 
-/// Load all learnings from the JSONL file
-pub fn load_learnings(path: &Path) -> Result<Vec<Learning>> {
-    if !path.exists() {
-        return Ok(Vec::new());
+/// Output format for `profundo export`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Learnings by date, for OpenClaw indexing
+    #[default]
+    Markdown,
+    /// Everything as structured JSON, with usage stats
+    Json,
+    /// One row per learning item, for spreadsheets
+    Csv,
+    /// A single self-contained page with a date index and filtering (see `html.rs`)
+    Html,
+    /// An Obsidian vault: session, topic and daily notes with wikilinks (see `obsidian.rs`)
+    Obsidian,
+    /// One architecture decision record per decision (see `adr.rs`)
+    Adr,
+    /// All decision records in a single decision log
+    AdrLog,
+    /// Action items as todo.txt tasks, merged into an existing file (see `tasks.rs`)
+    Todotxt,
+    /// Action items as iCalendar VTODOs
+    Ics,
+}
+
+impl ExportFormat {
+    /// The exporter that writes this format
+    pub fn exporter(self) -> Box<dyn Exporter> {
+        match self {
+            ExportFormat::Markdown => Box::new(MarkdownExporter),
+            ExportFormat::Json => Box::new(JsonExporter),
+            ExportFormat::Csv => Box::new(CsvExporter),
+            ExportFormat::Html => Box::new(crate::html::HtmlExporter),
+            ExportFormat::Obsidian => Box::new(crate::obsidian::ObsidianExporter),
+            ExportFormat::Adr => Box::new(crate::adr::AdrExporter { log: false }),
+            ExportFormat::AdrLog => Box::new(crate::adr::AdrExporter { log: true }),
+            ExportFormat::Todotxt => Box::new(crate::tasks::TodoTxtExporter),
+            ExportFormat::Ics => Box::new(crate::tasks::IcsExporter),
+        }
     }
+}
 
-    let file = fs::File::open(path)?;
-    let reader = BufReader::new(file);
+/// An export format.
+///
+/// To add a format, implement this and add an `ExportFormat` variant that
+/// returns it from `ExportFormat::exporter`.
+pub trait Exporter {
+    /// Where the export goes when no output path is given
+    fn default_path(&self, paths: &Paths) -> PathBuf;
 
-    let learnings: Vec<Learning> = reader
-        .lines()
-        .filter_map(|l| l.ok())
-        .filter_map(|l| serde_json::from_str(&l).ok())
-        .collect();
+    /// Write the learnings in `scope` to `output`
+    fn export(&self, paths: &Paths, scope: &ExportScope, output: &Path) -> Result<ExportStats>;
+}
 
-    Ok(learnings)
+/// The learnings one export covers, after filtering and splitting
+pub struct ExportScope<'a> {
+    pub learnings: &'a [Learning],
+    /// Whether learnings or items were left out. Consolidated entries and
+    /// tracked action items are then limited to the ones kept.
+    pub partial: bool,
+    /// The agent and dates the export covers, for its usage stats
+    pub usage: StatsConfig,
 }
 
-/// Filter learnings by date
-pub fn filter_by_date(learnings: &[Learning], date: NaiveDate) -> Vec<&Learning> {
-    let date_str = date.format("%Y-%m-%d").to_string();
-    learnings
-        .iter()
-        .filter(|l| l.date == date_str)
-        .collect()
+impl ExportScope<'_> {
+    /// Consolidated facts and decisions; when partial, those stated by a kept item
+    pub fn canonical_items(&self, paths: &Paths) -> Result<Vec<CanonicalItem>> {
+        let mut items = Database::open(&paths.db_path)?.load_canonical_items()?;
+        if self.partial {
+            let kept: HashSet<(&str, &str)> = self
+                .learnings
+                .iter()
+                .flat_map(|l| {
+                    l.decisions
+                        .iter()
+                        .chain(&l.facts_learned)
+                        .map(move |text| (l.session_id.as_str(), text.as_str()))
+                })
+                .collect();
+            items.retain(|item| {
+                item.sources
+                    .iter()
+                    .any(|s| kept.contains(&(s.session_id.as_str(), s.text.as_str())))
+            });
+        }
+        Ok(items)
+    }
+
+    /// Tracked action items; when partial, those of kept items
+    pub fn action_items(&self, paths: &Paths) -> Result<Vec<ActionItem>> {
+        let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+        let mut items = db.load_action_items(None)?;
+        if self.partial {
+            let kept: HashSet<String> = self
+                .learnings
+                .iter()
+                .flat_map(|l| l.action_items.iter().map(|text| todo::item_id(&l.session_id, text)))
+                .collect();
+            items.retain(|item| kept.contains(&item.id));
+        }
+        Ok(items)
+    }
 }
 
-/// Format a single learning as markdown bullets
-pub fn format_learning_bullets(learning: &Learning) -> String {
-    let mut lines = Vec::new();
+/// `learnings.md`, see `export_to_markdown`
+pub struct MarkdownExporter;
+
+impl Exporter for MarkdownExporter {
+    fn default_path(&self, paths: &Paths) -> PathBuf {
+        paths.memory_dir.join("learnings.md")
+    }
 
-    // Topics as tags
-    if !learning.topics.is_empty() {
-        lines.push(format!("- **Topics**: {}", learning.topics.join(", ")));
+    fn export(&self, paths: &Paths, scope: &ExportScope, output: &Path) -> Result<ExportStats> {
+        export_to_markdown(paths, scope, output)
     }
+}
 
-    // Summary
-    if !learning.summary.is_empty() {
-        lines.push(format!("- {}", learning.summary));
+/// Learnings, action items, consolidated entries and usage stats as one JSON document
+pub struct JsonExporter;
+
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    generated_at: String,
+    stats: &'a ExportStats,
+    usage: &'a AggregatedStats,
+    learnings: &'a [Learning],
+    action_items: &'a [ActionItem],
+    canonical_items: &'a [CanonicalItem],
+}
+
+impl Exporter for JsonExporter {
+    fn default_path(&self, paths: &Paths) -> PathBuf {
+        paths.memory_dir.join("learnings.json")
     }
 
-    // Decisions
-    for decision in &learning.decisions {
-        lines.push(format!("- **Decision**: {}", decision));
+    fn export(&self, paths: &Paths, scope: &ExportScope, output: &Path) -> Result<ExportStats> {
+        let learnings = scope.learnings;
+        if learnings.is_empty() {
+            return Ok(ExportStats::default());
+        }
+
+        let stats = ExportStats::count(learnings);
+        let usage = stats::collect(paths, scope.usage.clone())?;
+        let document = JsonExport {
+            generated_at: Utc::now().to_rfc3339(),
+            stats: &stats,
+            usage: &usage,
+            learnings,
+            action_items: &scope.action_items(paths)?,
+            canonical_items: &scope.canonical_items(paths)?,
+        };
+
+        fs::write(output, serde_json::to_string_pretty(&document)?).context("Failed to write export file")?;
+        Ok(stats)
     }
+}
+
+/// One row per learning item: `date,session_id,agent,kind,text,status,item_id`
+pub struct CsvExporter;
 
-    // Facts learned
-    for fact in &learning.facts_learned {
-        lines.push(format!("- **Learned**: {}", fact));
+impl Exporter for CsvExporter {
+    fn default_path(&self, paths: &Paths) -> PathBuf {
+        paths.memory_dir.join("learnings.csv")
     }
 
-    // Action items
-    for action in &learning.action_items {
-        lines.push(format!("- [ ] {}", action));
+    fn export(&self, paths: &Paths, scope: &ExportScope, output: &Path) -> Result<ExportStats> {
+        let mut learnings = scope.learnings.to_vec();
+        if learnings.is_empty() {
+            return Ok(ExportStats::default());
+        }
+        learnings.sort_by(|a, b| a.date.cmp(&b.date));
+        let action_items = load_action_items(paths)?;
+
+        let mut content = String::from("date,session_id,agent,kind,text,status,item_id\r\n");
+        for learning in &learnings {
+            let mut row = |kind: &str, text: &str, status: &str, item_id: &str| {
+                let fields = [learning.date.as_str(), &learning.session_id, &learning.agent, kind, text, status, item_id];
+                content.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+                content.push_str("\r\n");
+            };
+
+            if !learning.summary.is_empty() {
+                row("summary", &learning.summary, "", "");
+            }
+            for topic in &learning.topics {
+                row("topic", topic, "", "");
+            }
+            for decision in &learning.decisions {
+                row("decision", decision, "", "");
+            }
+            for fact in &learning.facts_learned {
+                row("fact", fact, "", "");
+            }
+            for action in &learning.action_items {
+                let id = todo::item_id(&learning.session_id, action);
+                let status = action_items.get(&id).map(|i| i.status).unwrap_or(ActionStatus::Open);
+                row("action_item", action, status.as_str(), &id);
+            }
+            for (name, items) in &learning.categories {
+                for item in items {
+                    row(name, item, "", "");
+                }
+            }
+        }
+
+        fs::write(output, content).context("Failed to write export file")?;
+        Ok(ExportStats::count(&learnings))
     }
+}
 
-    lines.join("\n")
+/// Items each export filter excluded, by filter name (`topic`, `category`, ...)
+pub type Exclusions = Vec<(String, usize)>;
+
+/// Which learnings and items an export includes. Unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// Sessions on or after this date
+    pub since: Option<NaiveDate>,
+    /// Sessions on or before this date
+    pub until: Option<NaiveDate>,
+    /// Sessions of this agent
+    pub agent: Option<String>,
+    /// Sessions whose id starts with any of these
+    pub sessions: Vec<String>,
+    /// Sessions with any of these topics (case-insensitive)
+    pub topics: Vec<String>,
+    /// Item kinds to keep: `decisions`, `facts`, `actions` or a custom category
+    pub categories: Vec<String>,
+}
+
+impl ExportFilter {
+    /// Usage stats for the agent and dates the filter selects
+    pub fn usage(&self) -> StatsConfig {
+        StatsConfig {
+            since: self.since,
+            until: self.until,
+            agent: self.agent.clone(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.since.is_none()
+            && self.until.is_none()
+            && self.agent.is_none()
+            && self.sessions.is_empty()
+            && self.topics.is_empty()
+            && self.categories.is_empty()
+    }
+
+    /// Keep the learnings and items that match. Also returns, for each filter
+    /// that is set, how many items it excluded.
+    ///
+    /// A session failing the date, agent, session or topic filter is dropped
+    /// with all its items. The category filter drops items of other kinds,
+    /// and sessions left without any.
+    pub fn apply(&self, learnings: Vec<Learning>, categories: &CategorySet) -> Result<(Vec<Learning>, Exclusions)> {
+        let kinds = self
+            .categories
+            .iter()
+            .map(|name| category_kind(name, categories, &learnings))
+            .collect::<Result<HashSet<String>>>()?;
+        let topics: Vec<String> = self.topics.iter().map(|t| t.trim().to_lowercase()).collect();
+
+        let active = [
+            self.since.is_some() || self.until.is_some(),
+            self.agent.is_some(),
+            !self.sessions.is_empty(),
+            !topics.is_empty(),
+            !kinds.is_empty(),
+        ];
+        let mut excluded = [0usize; 5];
+        let mut kept = Vec::new();
+
+        for mut learning in learnings {
+            let date = NaiveDate::parse_from_str(&learning.date, "%Y-%m-%d").ok();
+            let matches = [
+                self.since.map(|since| date.is_some_and(|d| d >= since)).unwrap_or(true)
+                    && self.until.map(|until| date.is_some_and(|d| d <= until)).unwrap_or(true),
+                self.agent.as_ref().map(|agent| learning.agent == *agent).unwrap_or(true),
+                self.sessions.is_empty() || self.sessions.iter().any(|id| learning.session_id.starts_with(id.as_str())),
+                topics.is_empty() || learning.topics.iter().any(|t| topics.contains(&t.trim().to_lowercase())),
+            ];
+            if let Some(failed) = matches.iter().position(|m| !m) {
+                excluded[failed] += item_count(&learning);
+                continue;
+            }
+
+            if !kinds.is_empty() {
+                let before = item_count(&learning);
+                if !kinds.contains("decisions") {
+                    learning.decisions.clear();
+                }
+                if !kinds.contains("facts_learned") {
+                    learning.facts_learned.clear();
+                }
+                if !kinds.contains("action_items") {
+                    learning.action_items.clear();
+                }
+                learning.categories.retain(|name, _| kinds.contains(name));
+                let after = item_count(&learning);
+                excluded[4] += before - after;
+                if after == 0 {
+                    continue;
+                }
+            }
+            kept.push(learning);
+        }
+
+        let names = ["since/until", "agent", "session", "topic", "category"];
+        let report = (0..names.len())
+            .filter(|&i| active[i])
+            .map(|i| (names[i].to_string(), excluded[i]))
+            .collect();
+        Ok((kept, report))
+    }
+}
+
+/// Stored name of a category given to `--category`
+fn category_kind(name: &str, categories: &CategorySet, learnings: &[Learning]) -> Result<String> {
+    let kind = match name {
+        "facts" => "facts_learned",
+        "actions" => "action_items",
+        other => other,
+    };
+    let known = categories.categories.iter().any(|c| c.name == kind)
+        || learnings.iter().any(|l| l.categories.contains_key(kind));
+    if !known {
+        let mut names = vec!["decisions".to_string(), "facts".to_string(), "actions".to_string()];
+        names.extend(categories.custom().map(|c| c.name.clone()));
+        return Err(anyhow!("Unknown category '{}' (expected one of: {})", name, names.join(", ")));
+    }
+    Ok(kind.to_string())
+}
+
+/// Decisions, facts, action items and custom category items in a learning
+fn item_count(learning: &Learning) -> usize {
+    learning.decisions.len()
+        + learning.facts_learned.len()
+        + learning.action_items.len()
+        + learning.categories.values().map(|items| items.len()).sum::<usize>()
+}
+
+/// How `--split-by` groups learnings into separate exports
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SplitBy {
+    Day,
+    /// ISO week (`2026-W41`)
+    Week,
+    Month,
+    /// A session with several topics is in each of their exports
+    Topic,
+}
+
+impl SplitBy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SplitBy::Day => "day",
+            SplitBy::Week => "week",
+            SplitBy::Month => "month",
+            SplitBy::Topic => "topic",
+        }
+    }
+
+    /// First and last day of a day, week or month group
+    fn dates(self, key: &str) -> Option<(NaiveDate, NaiveDate)> {
+        let period = match self {
+            SplitBy::Day => {
+                let day = NaiveDate::parse_from_str(key, "%Y-%m-%d").ok()?;
+                return Some((day, day));
+            }
+            SplitBy::Week => Period::week(key).ok()?,
+            SplitBy::Month => Period::month(key).ok()?,
+            SplitBy::Topic => return None,
+        };
+        Some((period.start, period.end))
+    }
+
+    /// Names of the groups a learning belongs to, usable as file names
+    fn keys(self, learning: &Learning) -> Vec<String> {
+        let date = NaiveDate::parse_from_str(&learning.date, "%Y-%m-%d").ok();
+        match (self, date) {
+            (SplitBy::Day, _) => vec![learning.date.clone()],
+            (SplitBy::Week, Some(date)) => vec![Period::week_of(date).label()],
+            (SplitBy::Month, Some(date)) => vec![Period::month_of(date).label()],
+            (SplitBy::Week | SplitBy::Month, None) => vec!["undated".to_string()],
+            (SplitBy::Topic, _) => {
+                let mut keys: Vec<String> = learning.topics.iter().map(|t| file_key(t)).filter(|k| !k.is_empty()).collect();
+                keys.sort();
+                keys.dedup();
+                if keys.is_empty() {
+                    keys.push("untagged".to_string());
+                }
+                keys
+            }
+        }
+    }
+}
+
+/// Lowercase letters and digits, other runs of characters as single dashes
+fn file_key(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Export the learnings that match `filter` in `format`.
+///
+/// Without `split`, writes to `output` (or the format's default path).
+/// With it, `output` is a directory holding one export per group, named
+/// after the group: `2026-10.md` for a file format, `2026-10/` for a
+/// directory format. It defaults to the format's default path with
+/// `-by-<split>` in place of the extension (`learnings-by-month/`).
+/// Returns where the export went.
+pub fn run(
+    paths: &Paths,
+    format: ExportFormat,
+    output: Option<PathBuf>,
+    filter: &ExportFilter,
+    split: Option<SplitBy>,
+) -> Result<(PathBuf, ExportStats)> {
+    let exporter = format.exporter();
+    let default_path = exporter.default_path(paths);
+
+    let categories = CategorySet::load(&paths.categories_path)?;
+    let (learnings, excluded) = filter.apply(load_learnings(paths)?, &categories)?;
+
+    let Some(split) = split else {
+        let output = output.unwrap_or(default_path);
+        let scope = ExportScope {
+            learnings: &learnings,
+            partial: !filter.is_empty(),
+            usage: filter.usage(),
+        };
+        let mut stats = if learnings.is_empty() {
+            ExportStats::default()
+        } else {
+            exporter.export(paths, &scope, &output)?
+        };
+        stats.excluded = excluded;
+        return Ok((output, stats));
+    };
+
+    let dir = output.unwrap_or_else(|| {
+        let stem = default_path.file_stem().unwrap_or_default().to_string_lossy();
+        default_path.with_file_name(format!("{}-by-{}", stem, split.as_str()))
+    });
+    let extension = default_path.extension().map(|e| e.to_string_lossy().to_string());
+
+    let mut groups: BTreeMap<String, Vec<Learning>> = BTreeMap::new();
+    for learning in &learnings {
+        for key in split.keys(learning) {
+            groups.entry(key).or_default().push(learning.clone());
+        }
+    }
+
+    let mut stats = ExportStats {
+        excluded,
+        ..ExportStats::count(&learnings)
+    };
+    if !groups.is_empty() {
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    for (key, group) in &groups {
+        let path = match &extension {
+            Some(extension) => dir.join(format!("{}.{}", key, extension)),
+            None => dir.join(key),
+        };
+        // A day, week or month reports that period's usage, within the filter's dates
+        let mut usage = filter.usage();
+        if let Some((start, end)) = split.dates(key) {
+            usage.since = usage.since.max(Some(start));
+            usage.until = Some(usage.until.map_or(end, |until| until.min(end)));
+        }
+        let scope = ExportScope {
+            learnings: group,
+            partial: true,
+            usage,
+        };
+        let written = exporter.export(paths, &scope, &path)?;
+        stats.superseded += written.superseded;
+        stats.unchanged += written.unchanged;
+        stats.files += 1;
+    }
+
+    Ok((dir, stats))
+}
+
+/// Quote a CSV field when needed, and defuse text a spreadsheet would run as a formula
+fn csv_field(text: &str) -> String {
+    let text = if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", text)
+    } else {
+        text.to_string()
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// Load all learnings from the database
+pub fn load_learnings(paths: &Paths) -> Result<Vec<Learning>> {
+    Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?.load_learnings(None)
+}
+
+/// Filter learnings by date
+pub fn filter_by_date(learnings: &[Learning], date: NaiveDate) -> Vec<&Learning> {
+    let date_str = date.format("%Y-%m-%d").to_string();
+    learnings
+        .iter()
+        .filter(|l| l.date == date_str)
+        .collect()
+}
+
+/// Tracked action items by id, for showing their status in exports
+pub fn load_action_items(paths: &Paths) -> Result<HashMap<String, ActionItem>> {
+    let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+    Ok(db
+        .load_action_items(None)?
+        .into_iter()
+        .map(|item| (item.id.clone(), item))
+        .collect())
+}
+
+/// Format one action item with its status: a ticked box when done, struck
+/// through when dropped (untracked items are open)
+pub(crate) fn format_action_item(text: &str, tracked: Option<&ActionItem>, categories: &CategorySet) -> String {
+    let closed_on = |item: &ActionItem| item.closed_on().map(|d| format!(" {}", d)).unwrap_or_default();
+
+    match tracked {
+        Some(item) if item.status == ActionStatus::Done => format!(
+            "{} _(done{})_",
+            categories.format_item("action_items", text).replacen("[ ]", "[x]", 1),
+            closed_on(item)
+        ),
+        Some(item) if item.status == ActionStatus::Dropped => format!(
+            "{} _(dropped{})_",
+            categories.format_item("action_items", &format!("~~{}~~", text)),
+            closed_on(item)
+        ),
+        _ => categories.format_item("action_items", text),
+    }
 }
 
 /// Format stats as a compact markdown summary
@@ -85,172 +579,302 @@ pub fn format_stats_summary(stats: &TokenStats) -> String {
     )
 }
 
-/// Export all learnings to a markdown file
-pub fn export_to_markdown(paths: &Paths, output_path: &Path) -> Result<ExportStats> {
-    let learnings = load_learnings(&paths.learnings_path)?;
+/// Export learnings to a markdown file, laid out by the `export.md` template
+pub fn export_to_markdown(paths: &Paths, scope: &ExportScope, output_path: &Path) -> Result<ExportStats> {
+    let learnings = scope.learnings;
 
     if learnings.is_empty() {
         return Ok(ExportStats::default());
     }
 
+    let templates = Templates::load(paths)?;
+    let categories = CategorySet::load(&paths.categories_path)?;
+    let canonical = scope.canonical_items(paths)?;
+    let covered = crate::consolidate::covered(&canonical);
+    let action_items = load_action_items(paths)?;
+    let stats = ExportStats::count(learnings);
+
     // Group by date
-    let mut by_date: std::collections::BTreeMap<String, Vec<&Learning>> =
+    let mut by_date: std::collections::BTreeMap<&str, Vec<&Learning>> =
         std::collections::BTreeMap::new();
 
-    for learning in &learnings {
+    for learning in learnings {
         by_date
-            .entry(learning.date.clone())
+            .entry(learning.date.as_str())
             .or_default()
             .push(learning);
     }
 
-    let mut content = String::new();
-    content.push_str("# Profundo Learnings\n\n");
-    content.push_str("Extracted insights from conversation sessions.\n\n");
-
-    let mut total_decisions = 0;
-    let mut total_facts = 0;
-    let mut total_actions = 0;
-
-    // Write in reverse chronological order
-    for (date, day_learnings) in by_date.iter().rev() {
-        content.push_str(&format!("## {}\n\n", date));
-
-        for learning in day_learnings {
-            content.push_str(&format_learning_bullets(learning));
-            content.push_str("\n\n");
+    // Reverse chronological order
+    let days = by_date
+        .iter()
+        .rev()
+        .map(|(date, day_learnings)| DayView {
+            date,
+            learnings: day_learnings
+                .iter()
+                .map(|learning| {
+                    let mut view = LearningView::new(learning, &categories, &action_items, None);
+                    // Consolidated facts and decisions are listed once each instead of per session
+                    let id = learning.session_id.as_str();
+                    view.decisions.retain(|text| !covered.contains(&("decisions", id, *text)));
+                    view.facts.retain(|text| !covered.contains(&("facts_learned", id, *text)));
+                    view
+                })
+                .collect(),
+        })
+        .collect();
 
-            total_decisions += learning.decisions.len();
-            total_facts += learning.facts_learned.len();
-            total_actions += learning.action_items.len();
-        }
-    }
+    let content = templates.export(&ExportContext {
+        stats: &stats,
+        consolidated: !canonical.is_empty(),
+        canonical: CanonicalView::new(&canonical),
+        days,
+        default_agent: crate::DEFAULT_AGENT,
+    })?;
 
     fs::write(output_path, content).context("Failed to write export file")?;
 
-    Ok(ExportStats {
-        sessions: learnings.len(),
-        decisions: total_decisions,
-        facts: total_facts,
-        actions: total_actions,
-    })
+    Ok(stats)
 }
 
-/// Write a Profundo rollup section to a daily log file
-pub fn write_rollup(paths: &Paths, date: NaiveDate) -> Result<RollupStats> {
-    let learnings = load_learnings(&paths.learnings_path)?;
-    let day_learnings = filter_by_date(&learnings, date);
+/// How `write_rollups` runs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RollupOptions<'a> {
+    /// Only this agent's sessions (all agents if None)
+    pub agent: Option<&'a str>,
+    /// Compute diffs instead of writing
+    pub check: bool,
+    /// Also write days without sessions or learnings
+    pub include_empty: bool,
+}
 
-    // Get stats for this specific date
-    let stats_config = StatsConfig {
-        since: Some(date),
-        until: Some(date),
+/// Write a Profundo rollup section to one day's log, even if the day had no
+/// activity (see `write_rollups`)
+pub fn write_rollup(paths: &Paths, date: NaiveDate, agent: Option<&str>, check: bool) -> Result<RollupStats> {
+    let options = RollupOptions {
+        agent,
+        check,
+        include_empty: true,
     };
-    let day_stats = stats::collect(paths, stats_config)?;
+    write_rollups(paths, date, date, options)?
+        .pop()
+        .context("No rollup written")
+}
 
-    // Build the Profundo section
-    let mut section = String::new();
-    section.push_str("\n## Profundo\n\n");
+/// Write a Profundo rollup section to the daily log of every day from `since`
+/// to `until`, skipping days without sessions or learnings unless
+/// `include_empty` is set. Session logs are parsed once for the whole range.
+///
+/// The section body comes from the `rollup.md` template. It sits between
+/// `GENERATED_BEGIN` and `GENERATED_END`, and only that block is replaced on
+/// a rerun. A section from before the markers (a `## Profundo` heading up to
+/// the next `## ` heading) is converted to a block. With `check`, nothing is
+/// written and the stats carry a diff of what would change.
+pub fn write_rollups(paths: &Paths, since: NaiveDate, until: NaiveDate, options: RollupOptions) -> Result<Vec<RollupStats>> {
+    let learnings = load_learnings(paths)?;
+    let inputs = RollupInputs {
+        templates: Templates::load(paths)?,
+        categories: CategorySet::load(&paths.categories_path)?,
+        action_items: load_action_items(paths)?,
+        options,
+    };
 
-    // Stats line
-    if day_stats.session_count > 0 {
-        section.push_str(&format!(
-            "{} ({} sessions)\n\n",
-            format_stats_summary(&day_stats.total),
-            day_stats.session_count
-        ));
+    let usage = stats::collect(
+        paths,
+        StatsConfig {
+            since: Some(since),
+            until: Some(until),
+            agent: options.agent.map(|a| a.to_string()),
+        },
+    )?;
+
+    // Bucket learnings and sessions by day
+    let mut learnings_by_date: HashMap<&str, Vec<&Learning>> = HashMap::new();
+    for learning in learnings
+        .iter()
+        .filter(|l| options.agent.map(|agent| l.agent == agent).unwrap_or(true))
+    {
+        learnings_by_date.entry(learning.date.as_str()).or_default().push(learning);
+    }
+    let mut sessions_by_date: HashMap<NaiveDate, Vec<&SessionUsage>> = HashMap::new();
+    for session in &usage.sessions {
+        sessions_by_date.entry(session.date).or_default().push(session);
     }
 
-    // Learnings
-    if day_learnings.is_empty() {
-        section.push_str("_No learnings harvested for this date._\n");
-    } else {
-        for learning in &day_learnings {
-            // Session reference
-            section.push_str(&format!(
-                "### Session `{}`\n\n",
-                if learning.session_id.len() >= 8 { &learning.session_id[..8] } else { &learning.session_id }
-            ));
-            section.push_str(&format_learning_bullets(learning));
-            section.push_str("\n\n");
+    let mut written = Vec::new();
+    for date in since.iter_days().take_while(|d| *d <= until) {
+        let day_learnings = learnings_by_date.remove(date.to_string().as_str()).unwrap_or_default();
+        let day_sessions = sessions_by_date.remove(&date).unwrap_or_default();
+        if day_learnings.is_empty() && day_sessions.is_empty() && !options.include_empty {
+            continue;
         }
+        written.push(inputs.write_day(paths, date, &day_learnings, &day_sessions, usage.by_date.get(&date))?);
     }
 
-    // Determine the daily log path
-    let daily_log_path = paths.memory_dir.join(format!("{}.md", date));
+    Ok(written)
+}
+
+/// What every day of a rollup needs, loaded once
+struct RollupInputs<'a> {
+    templates: Templates,
+    categories: CategorySet,
+    action_items: HashMap<String, ActionItem>,
+    options: RollupOptions<'a>,
+}
+
+impl RollupInputs<'_> {
+    fn write_day(
+        &self,
+        paths: &Paths,
+        date: NaiveDate,
+        day_learnings: &[&Learning],
+        day_sessions: &[&SessionUsage],
+        day_stats: Option<&TokenStats>,
+    ) -> Result<RollupStats> {
+        let body = self.templates.rollup(&RollupContext {
+            date,
+            weekday: weekday_name(date.weekday()).to_string(),
+            agent: self.options.agent,
+            stats: day_stats
+                .filter(|_| !day_sessions.is_empty())
+                .map(|stats| StatsView::new(stats, day_sessions.len())),
+            sessions: day_sessions,
+            learnings: day_learnings
+                .iter()
+                .map(|learning| {
+                    let usage = day_sessions.iter().find(|s| s.id == learning.session_id).copied();
+                    LearningView::new(learning, &self.categories, &self.action_items, usage)
+                })
+                .collect(),
+            default_agent: crate::DEFAULT_AGENT,
+        })?;
+        let section = generated_block(&format!("## Profundo\n\n{}", body));
+
+        let daily_log_path = paths.memory_dir.join(format!("{}.md", date));
+        let existing = if daily_log_path.exists() {
+            Some(fs::read_to_string(&daily_log_path).with_context(|| format!("Failed to read {}", daily_log_path.display()))?)
+        } else {
+            None
+        };
+
+        let content = match existing.as_deref() {
+            Some(existing) if existing.contains(GENERATED_BEGIN) => splice_generated(existing, &section),
+            Some(existing) => {
+                migrate_profundo_section(existing, &section).unwrap_or_else(|| splice_generated(existing, &section))
+            }
+            None => {
+                // New daily log with a date header
+                let header = format!(
+                    "# {} {} ({})\n",
+                    month_name(date.month()),
+                    date.day(),
+                    weekday_name(date.weekday())
+                );
+                format!("{}\n{}", header, section)
+            }
+        };
 
-    // Check if file exists and already has a Profundo section
-    if daily_log_path.exists() {
-        let existing = fs::read_to_string(&daily_log_path)?;
-        if existing.contains("## Profundo") {
-            // Replace existing section
-            let updated = replace_profundo_section(&existing, &section);
-            fs::write(&daily_log_path, updated)?;
+        let changed = existing.as_deref() != Some(content.as_str());
+        let diff = if self.options.check && changed {
+            Some(unified_diff(existing.as_deref().unwrap_or(""), &content, &daily_log_path))
         } else {
-            // Append to existing file
-            let mut file = OpenOptions::new()
-                .append(true)
-                .open(&daily_log_path)?;
-            file.write_all(section.as_bytes())?;
+            None
+        };
+        if changed && !self.options.check {
+            write_atomic(&daily_log_path, &content)?;
         }
-    } else {
-        // Create new file with header and Profundo section
-        let weekday = weekday_name(date.weekday());
-        let header = format!(
-            "# {} {} ({})\n",
-            month_name(date.month()),
-            date.day(),
-            weekday
-        );
-        let content = format!("{}{}", header, section);
-        fs::write(&daily_log_path, content)?;
-    }
-
-    Ok(RollupStats {
-        sessions: day_learnings.len(),
-        stats_sessions: day_stats.session_count,
-        path: daily_log_path,
-    })
+
+        Ok(RollupStats {
+            sessions: day_learnings.len(),
+            stats_sessions: day_sessions.len(),
+            path: daily_log_path,
+            changed,
+            diff,
+        })
+    }
 }
 
-/// Replace an existing ## Profundo section with a new one
-fn replace_profundo_section(content: &str, new_section: &str) -> String {
-    let mut result = String::new();
-    let mut in_profundo = false;
-    let mut found_next_section = false;
+/// Start of generated content in files that may also have hand edits
+pub const GENERATED_BEGIN: &str = "<!-- profundo:begin -->";
+/// End of generated content
+pub const GENERATED_END: &str = "<!-- profundo:end -->";
 
-    for line in content.lines() {
-        if line.starts_with("## Profundo") {
-            in_profundo = true;
-            continue;
-        }
+/// Wrap generated markdown in the begin/end markers
+pub fn generated_block(content: &str) -> String {
+    format!("{}\n{}\n{}\n", GENERATED_BEGIN, content.trim_end(), GENERATED_END)
+}
 
-        if in_profundo {
-            // Check if we've hit another ## section
-            if line.starts_with("## ") {
-                in_profundo = false;
-                found_next_section = true;
-                // Insert new section before this line
-                result.push_str(new_section);
-                result.push_str(line);
-                result.push('\n');
-            }
-            // Skip old Profundo content
-            continue;
+/// Replace the generated block in `existing` with `block`, keeping everything
+/// outside the markers; the block is appended when there is none yet
+pub fn splice_generated(existing: &str, block: &str) -> String {
+    let begin = existing.find(GENERATED_BEGIN);
+    let end = begin.and_then(|b| existing[b..].find(GENERATED_END).map(|e| b + e + GENERATED_END.len()));
+
+    match (begin, end) {
+        (Some(begin), Some(end)) => {
+            let after = existing[end..].strip_prefix('\n').unwrap_or(&existing[end..]);
+            format!("{}{}{}", &existing[..begin], block, after)
         }
+        _ if existing.trim().is_empty() => block.to_string(),
+        _ => format!("{}\n\n{}", existing.trim_end(), block),
+    }
+}
 
-        result.push_str(line);
-        result.push('\n');
+/// Replace a `## Profundo` section written before the generated-block
+/// markers, from its heading to the next `## ` heading, with `block`
+fn migrate_profundo_section(content: &str, block: &str) -> Option<String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let start = lines.iter().position(|l| l.trim_end() == "## Profundo")?;
+    let end = lines[start + 1..]
+        .iter()
+        .position(|l| l.starts_with("## "))
+        .map(|i| start + 1 + i)
+        .unwrap_or(lines.len());
+
+    let before = lines[..start].concat();
+    let after = lines[end..].concat();
+    let mut migrated = before.trim_end().to_string();
+    if !migrated.is_empty() {
+        migrated.push_str("\n\n");
+    }
+    migrated.push_str(block);
+    if !after.is_empty() {
+        migrated.push('\n');
+        migrated.push_str(&after);
     }
+    Some(migrated)
+}
 
-    // If Profundo was at the end, append new section
-    if in_profundo && !found_next_section {
-        // Remove trailing newline before adding section
-        result = result.trim_end().to_string();
-        result.push_str(new_section);
+/// Write a file through a temporary sibling renamed over it, so an
+/// interrupted write never leaves a truncated file behind
+pub fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = path
+        .file_name()
+        .with_context(|| format!("Not a file path: {}", path.display()))?
+        .to_string_lossy();
+    let tmp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+
+    fs::write(&tmp, content).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    // Keep the permissions of the file being replaced
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(&tmp, metadata.permissions()).ok();
     }
+    fs::rename(&tmp, path).map_err(|e| {
+        fs::remove_file(&tmp).ok();
+        anyhow::Error::new(e).context(format!("Failed to write {}", path.display()))
+    })
+}
 
-    result
+/// Unified diff from `old` to `new`, labelled with `path`
+pub fn unified_diff(old: &str, new: &str, path: &Path) -> String {
+    let label = path.display().to_string();
+    similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(&label, &label)
+        .to_string()
 }
 
 fn weekday_name(wd: Weekday) -> &'static str {
@@ -283,16 +907,49 @@ fn month_name(month: u32) -> &'static str {
     }
 }
 
-#[derive(Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct ExportStats {
     pub sessions: usize,
     pub decisions: usize,
     pub facts: usize,
     pub actions: usize,
+    /// Decisions a later one reversed (decision record exports)
+    #[serde(skip_serializing_if = "is_zero")]
+    pub superseded: usize,
+    /// Files that already had the exported content and were left alone
+    #[serde(skip_serializing_if = "is_zero")]
+    pub unchanged: usize,
+    /// Exports written by a split export
+    #[serde(skip_serializing_if = "is_zero")]
+    pub files: usize,
+    /// Items each filter left out
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded: Exclusions,
+}
+
+impl ExportStats {
+    /// Counts over a set of learnings
+    pub fn count(learnings: &[Learning]) -> Self {
+        Self {
+            sessions: learnings.len(),
+            decisions: learnings.iter().map(|l| l.decisions.len()).sum(),
+            facts: learnings.iter().map(|l| l.facts_learned.len()).sum(),
+            actions: learnings.iter().map(|l| l.action_items.len()).sum(),
+            ..Default::default()
+        }
+    }
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
 }
 
 pub struct RollupStats {
     pub sessions: usize,
     pub stats_sessions: usize,
     pub path: std::path::PathBuf,
+    /// Whether the daily log differs (or would differ, when checking) from before
+    pub changed: bool,
+    /// What would change, when checking
+    pub diff: Option<String>,
 }
@@ -0,0 +1,268 @@
+//! Graph - entities and relations extracted across sessions
+//!
+//! Harvest extracts the people, projects, repositories, services and tools a
+//! conversation is about, and how they relate ("billing-api uses Postgres").
+//! The database merges them across sessions into one graph, keeping the
+//! sessions each entity and relation came from.
+
+use anyhow::Result;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::harvest::Learning;
+use crate::session::short_id;
+
+/// Entity types the harvest model may assign
+pub const ENTITY_KINDS: &[&str] = &["person", "project", "repository", "service", "tool", "other"];
+
+/// Something a conversation is about, as extracted from one session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    pub name: String,
+    /// One of `ENTITY_KINDS`
+    #[serde(rename = "type", default = "default_kind")]
+    pub kind: String,
+}
+
+fn default_kind() -> String {
+    "other".to_string()
+}
+
+/// A directed relation between two entities, as extracted from one session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Relation {
+    pub subject: String,
+    /// Short verb phrase, lowercase ("uses", "owns", "deployed on")
+    pub predicate: String,
+    pub object: String,
+}
+
+/// A session an entity or relation came from
+#[derive(Debug, Clone, Serialize)]
+pub struct Mention {
+    pub session_id: String,
+    pub date: String,
+}
+
+/// An entity merged across sessions
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEntity {
+    /// Database id
+    pub id: i64,
+    pub name: String,
+    pub kind: String,
+    /// Sessions that mention it, oldest first
+    pub sessions: Vec<Mention>,
+}
+
+/// A relation merged across sessions
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphRelation {
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+    /// Sessions that state it, oldest first
+    pub sessions: Vec<Mention>,
+}
+
+/// The whole entity graph
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Graph {
+    pub entities: Vec<GraphEntity>,
+    pub relations: Vec<GraphRelation>,
+}
+
+/// Graph export format
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    #[default]
+    Dot,
+    /// Entities and relations with their sources
+    Json,
+}
+
+/// Key entities are merged on: case and whitespace don't matter
+pub fn entity_key(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Normalize an extracted entity type to one of `ENTITY_KINDS`
+pub fn normalize_kind(kind: &str) -> String {
+    let kind = kind.trim().to_lowercase();
+    let kind = match kind.as_str() {
+        "repo" => "repository",
+        "people" | "user" | "team member" => "person",
+        "library" | "language" | "framework" => "tool",
+        other => other,
+    };
+    if ENTITY_KINDS.contains(&kind) {
+        kind.to_string()
+    } else {
+        default_kind()
+    }
+}
+
+impl Graph {
+    /// Entities whose name matches `name`: the exact match if there is one,
+    /// otherwise every entity whose name contains it
+    pub fn find(&self, name: &str) -> Vec<&GraphEntity> {
+        let key = entity_key(name);
+        if let Some(entity) = self.entities.iter().find(|e| entity_key(&e.name) == key) {
+            return vec![entity];
+        }
+        self.entities
+            .iter()
+            .filter(|e| entity_key(&e.name).contains(&key))
+            .collect()
+    }
+
+    /// Render as Graphviz DOT, one node per entity shaped by its type
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph profundo {\n    rankdir=LR;\n    node [shape=box, style=rounded];\n\n");
+
+        for entity in &self.entities {
+            let shape = match entity.kind.as_str() {
+                "person" => "ellipse",
+                "repository" => "folder",
+                "service" => "component",
+                "tool" => "hexagon",
+                _ => "box",
+            };
+            out.push_str(&format!(
+                "    \"{}\" [label=\"{}\", shape={}, tooltip=\"{} ({} sessions)\"];\n",
+                dot_escape(&entity.name),
+                dot_escape(&entity.name),
+                shape,
+                entity.kind,
+                entity.sessions.len()
+            ));
+        }
+
+        if !self.relations.is_empty() {
+            out.push('\n');
+        }
+        for relation in &self.relations {
+            out.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                dot_escape(&relation.subject),
+                dot_escape(&relation.object),
+                dot_escape(&relation.predicate)
+            ));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Whether `text` names `name` as a whole word, ignoring case
+fn mentions(text: &str, name: &str) -> bool {
+    let text = text.to_lowercase();
+    let name = name.to_lowercase();
+    if name.is_empty() {
+        return false;
+    }
+
+    text.match_indices(&name).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + name.len()..].chars().next();
+        !before.map(char::is_alphanumeric).unwrap_or(false) && !after.map(char::is_alphanumeric).unwrap_or(false)
+    })
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Print everything known about an entity: its relations, the sessions that
+/// mention it, and facts and decisions that name it
+pub fn display_entity(graph: &Graph, entity: &GraphEntity, learnings: &[Learning]) {
+    let first = entity.sessions.first().map(|m| m.date.as_str()).unwrap_or("?");
+    let last = entity.sessions.last().map(|m| m.date.as_str()).unwrap_or("?");
+    println!(
+        "\n{} {} {}",
+        entity.name.bold(),
+        format!("({})", entity.kind).dimmed(),
+        format!("{} sessions, {} → {}", entity.sessions.len(), first, last).dimmed()
+    );
+
+    let key = entity_key(&entity.name);
+    let outgoing: Vec<&GraphRelation> = graph
+        .relations
+        .iter()
+        .filter(|r| entity_key(&r.subject) == key)
+        .collect();
+    let incoming: Vec<&GraphRelation> = graph
+        .relations
+        .iter()
+        .filter(|r| entity_key(&r.object) == key && entity_key(&r.subject) != key)
+        .collect();
+
+    if !outgoing.is_empty() || !incoming.is_empty() {
+        println!("\n{}", "Relations".bold());
+        for relation in outgoing {
+            println!(
+                "  {} {} {} {}",
+                "→".cyan(),
+                relation.predicate.italic(),
+                relation.object,
+                format!("({} sessions)", relation.sessions.len()).dimmed()
+            );
+        }
+        for relation in incoming {
+            println!(
+                "  {} {} {} {}",
+                "←".cyan(),
+                relation.subject,
+                relation.predicate.italic(),
+                format!("({} sessions)", relation.sessions.len()).dimmed()
+            );
+        }
+    }
+
+    let mentioned: Vec<&Learning> = learnings
+        .iter()
+        .filter(|l| entity.sessions.iter().any(|m| m.session_id == l.session_id))
+        .collect();
+
+    let statements: Vec<&String> = learnings
+        .iter()
+        .flat_map(|l| l.facts_learned.iter().chain(&l.decisions))
+        .filter(|s| mentions(s, &entity.name))
+        .collect();
+    if !statements.is_empty() {
+        println!("\n{}", "Facts and decisions".bold());
+        for statement in statements {
+            println!("  • {}", statement);
+        }
+    }
+
+    if !mentioned.is_empty() {
+        println!("\n{}", "Sessions".bold());
+        for learning in mentioned.iter().rev() {
+            println!("  {} {} {}", learning.date.cyan(), short_id(&learning.session_id).dimmed(), learning.summary);
+        }
+    }
+    println!();
+}
+
+/// Print all entities, most mentioned first
+pub fn display_entities(graph: &Graph) {
+    let mut entities: Vec<&GraphEntity> = graph.entities.iter().collect();
+    entities.sort_by(|a, b| b.sessions.len().cmp(&a.sessions.len()).then_with(|| a.name.cmp(&b.name)));
+
+    for entity in entities {
+        println!(
+            "  {} {} {}",
+            entity.name,
+            format!("({})", entity.kind).dimmed(),
+            format!("{} sessions", entity.sessions.len()).dimmed()
+        );
+    }
+}
@@ -6,28 +6,81 @@ use anyhow::{Context, Result};
 use chrono::{DateTime, NaiveDate, Utc};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
-use std::fs::{File, OpenOptions};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
 use walkdir::WalkDir;
 
-use crate::openrouter::OpenRouterClient;
-use crate::session::Session;
+use crate::categories::CategorySet;
+use crate::db::Database;
+use crate::graph::{self, Entity, Relation};
+use crate::openrouter::{self, ChatApiError, OpenRouterClient};
+use crate::session::{short_id, Session, SessionMessage};
+use crate::todo::{ActionItem, ActionStatus};
+use crate::trace::{self, Provenance};
 use crate::Paths;
 
 /// Extracted learnings from a session
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Learning {
     pub session_id: String,
+    /// Agent that owns the session
+    #[serde(default = "default_agent")]
+    pub agent: String,
     pub date: String,
     pub topics: Vec<String>,
     pub decisions: Vec<String>,
     pub facts_learned: Vec<String>,
     pub action_items: Vec<String>,
+    /// User-defined categories (see `categories.rs`), by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, Vec<String>>,
+    /// People, projects, repositories, services and tools the session is about
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entities: Vec<Entity>,
+    /// Relations between those entities
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relations: Vec<Relation>,
+    /// Supporting turns for decisions, facts and action items
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provenance: Vec<Provenance>,
     pub summary: String,
     pub message_count: usize,
     pub cost: f64,
     pub harvested_at: String,
+    /// How many times the session has been harvested. Only a counter: each
+    /// re-harvest replaces the stored learning, and earlier versions aren't kept.
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// Transcript messages covered so far (0 for learnings from before incremental harvesting)
+    #[serde(default, alias = "harvested_turns")]
+    pub harvested_messages: usize,
+    /// Session file size when last harvested (0 if unknown: harvested before
+    /// sizes were recorded)
+    #[serde(default)]
+    pub harvested_bytes: u64,
+}
+
+fn default_agent() -> String {
+    crate::DEFAULT_AGENT.to_string()
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+/// A session whose harvest failed, kept so a later run retries it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub session_id: String,
+    #[serde(default = "default_agent")]
+    pub agent: String,
+    pub path: String,
+    pub error: String,
+    pub attempts: u32,
+    pub first_failed_at: String,
+    pub last_failed_at: String,
 }
 
 /// Configuration for harvest
@@ -38,6 +91,14 @@ pub struct HarvestConfig {
     pub model: String,
     /// Minimum messages to process a session
     pub min_messages: usize,
+    /// Only harvest this agent's sessions (all agents if None)
+    pub agent: Option<String>,
+    /// Override the model's context window (tokens) used to size segments
+    pub context_window: Option<usize>,
+    /// Categories to extract and the prompt template
+    pub categories: CategorySet,
+    /// Defer sessions modified within this many minutes (likely still being written)
+    pub settle_minutes: i64,
 }
 
 impl Default for HarvestConfig {
@@ -46,47 +107,153 @@ impl Default for HarvestConfig {
             since: None,
             model: "deepseek/deepseek-v3.2".to_string(),
             min_messages: 4,
+            agent: None,
+            context_window: None,
+            categories: CategorySet::default(),
+            settle_minutes: 30,
         }
     }
 }
 
-const HARVEST_PROMPT: &str = r#"Analyze this conversation and extract structured learnings.
+const MERGE_PROMPT: &str = r#"These learnings were extracted separately from consecutive parts of one long conversation, in order.
 
-Respond with ONLY valid JSON (no markdown, no explanation):
-{
-    "topics": ["topic1", "topic2"],
-    "decisions": ["decision made"],
-    "facts_learned": ["new fact about user"],
-    "action_items": ["task to do"],
-    "summary": "One paragraph summary"
-}
+Merge them into a single result with exactly the same JSON shape:
+- Combine duplicate and near-duplicate items into one
+- If a later part reverses or replaces an earlier decision, keep only the later one
+- topics: the 2-5 most important topics overall
+- summary: one paragraph covering the whole conversation's purpose and outcome
 
-Rules:
-- topics: 2-5 keywords describing what was discussed
-- decisions: Only explicit decisions made, not general discussion
-- facts_learned: New information about the user (preferences, background, etc.)
-- action_items: Tasks that were identified to do
-- summary: Brief summary of the conversation's purpose and outcome
-- Use empty arrays [] if nothing fits a category
-- Be concise and specific
+Respond with ONLY valid JSON (no markdown, no explanation).
 
-Conversation:
+Partial results:
 "#;
 
+const CONTINUATION_PROMPT: &str = r#"
+This conversation was harvested before. These are the learnings from the earlier part, for context only:
+{previous}
+
+The text below is only what was said since. Extract what is new in it; don't repeat earlier learnings unless they changed.
+"#;
+
+const COMPLETION_PROMPT: &str = r#"
+Open action items from earlier sessions, as [id] text:
+{items}
+
+Also return "completed_action_items": the ids of those items this conversation clearly shows were finished. Only include an item if it was actually done, not merely discussed or planned. Use [] if none.
+"#;
+
+const REPAIR_PROMPT: &str = r#"Your previous response could not be used because it did not match the required JSON format.
+
+Return ONLY the corrected JSON object (no markdown, no explanation). Keep the content, fix the format.
+
+Problem:
+"#;
+
+const EXTRACT_SYSTEM_PROMPT: &str = "You are a helpful assistant that extracts structured information.";
+
+/// Most open action items offered to the model for completion checks (newest first)
+const MAX_OPEN_ITEMS: usize = 40;
+
+/// Repair round-trips allowed after the first response fails to parse
+const MAX_REPAIR_ATTEMPTS: usize = 2;
+
+/// Approximate characters per token for budget estimates
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Tokens kept free for the prompt and the model's JSON answer
+const RESERVED_TOKENS: usize = 4_000;
+
+/// Context window (tokens) assumed for models not in `context_window`
+const DEFAULT_CONTEXT_WINDOW: usize = 32_000;
+
+/// Known context windows (tokens) by model id prefix
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("deepseek/", 128_000),
+    ("anthropic/", 200_000),
+    ("google/gemini", 1_000_000),
+    ("openai/gpt-4.1", 1_000_000),
+    ("openai/gpt-4o", 128_000),
+    ("openai/gpt-5", 400_000),
+    ("meta-llama/", 128_000),
+    ("mistralai/", 32_000),
+    ("qwen/", 128_000),
+];
+
+/// Context window for a harvest model, in tokens
+pub fn context_window(model: &str) -> usize {
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, tokens)| *tokens)
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+}
+
 /// Run the harvest pipeline
 pub async fn run(paths: &Paths, config: HarvestConfig) -> Result<HarvestStats> {
     let client = OpenRouterClient::from_env()?;
 
-    // Load already harvested session IDs
-    let harvested = load_harvested_ids(&paths.learnings_path)?;
+    let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+
+    // Load already harvested sessions (with how far each was harvested)
+    let harvested: HashMap<String, Learning> = db
+        .load_learnings(None)?
+        .into_iter()
+        .map(|l| (l.session_id.clone(), l))
+        .collect();
     println!(
         "{} {} sessions already harvested",
         "→".blue(),
         harvested.len().to_string().cyan()
     );
 
-    // Discover sessions to process
-    let sessions = discover_sessions_for_harvest(&paths.sessions_dir, &config, &harvested)?;
+    // Discover new sessions and sessions that grew since they were harvested
+    let mut sessions = Vec::new();
+    for agent in paths.session_dirs(config.agent.as_deref()) {
+        sessions.extend(discover_sessions_for_harvest(&agent.sessions_dir, &config, &harvested)?);
+    }
+
+    // Previously failed sessions are retried regardless of --since,
+    // unless a later run already harvested them
+    let mut dead_letters = load_dead_letters(&paths.dead_letter_path)?;
+    dead_letters.retain(|d| {
+        harvested
+            .get(&d.session_id)
+            .map(|l| l.harvested_at < d.last_failed_at)
+            .unwrap_or(true)
+    });
+    for dead in &dead_letters {
+        let agent_matches = config.agent.as_deref().map(|a| a == dead.agent).unwrap_or(true);
+        let path = std::path::PathBuf::from(&dead.path);
+        if agent_matches && path.exists() && !sessions.iter().any(|(id, _)| id == &dead.session_id) {
+            sessions.push((dead.session_id.clone(), path));
+        }
+    }
+    let retrying = dead_letters
+        .iter()
+        .filter(|d| sessions.iter().any(|(id, _)| id == &d.session_id))
+        .count();
+    if retrying > 0 {
+        println!(
+            "{} Retrying {} previously failed sessions",
+            "→".blue(),
+            retrying.to_string().cyan()
+        );
+    }
+
+    // Sessions written to recently are probably still in progress
+    let before = sessions.len();
+    sessions.retain(|(_, path)| !is_still_being_written(path, config.settle_minutes));
+    let deferred = before - sessions.len();
+    if deferred > 0 {
+        println!(
+            "{} Deferring {} sessions modified in the last {} minutes",
+            "→".blue(),
+            deferred.to_string().yellow(),
+            config.settle_minutes
+        );
+    }
+
+    sessions.sort_by(|a, b| a.0.cmp(&b.0));
     println!(
         "{} Found {} sessions to harvest",
         "→".blue(),
@@ -95,57 +262,221 @@ pub async fn run(paths: &Paths, config: HarvestConfig) -> Result<HarvestStats> {
 
     if sessions.is_empty() {
         println!("{} Nothing new to harvest", "✓".green());
-        return Ok(HarvestStats::default());
+        return Ok(HarvestStats {
+            deferred,
+            ..Default::default()
+        });
     }
 
-    let mut stats = HarvestStats::default();
-    let mut learnings_file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&paths.learnings_path)
-        .context("Failed to open learnings file")?;
+    let mut stats = HarvestStats {
+        deferred,
+        ..Default::default()
+    };
 
     for (session_id, path) in sessions {
-        let id_display = if session_id.len() >= 8 { &session_id[..8] } else { &session_id };
-        print!("{} Harvesting {}... ", "→".blue(), id_display);
+        let id_display = short_id(&session_id);
+        let previous = harvested.get(&session_id);
+        if previous.is_some() {
+            print!("{} Updating {}... ", "→".blue(), id_display);
+        } else {
+            print!("{} Harvesting {}... ", "→".blue(), id_display);
+        }
         std::io::stdout().flush().ok();
 
-        match harvest_session(&client, &path, &session_id, &config).await {
-            Ok(Some(learning)) => {
-                // Write to JSONL
-                let json = serde_json::to_string(&learning)?;
-                writeln!(learnings_file, "{}", json)?;
+        // Earlier open action items of the same agent, which this session may finish
+        let agent = crate::agent_from_path(&path);
+        let open_items: Vec<ActionItem> = db
+            .load_action_items(Some(ActionStatus::Open))?
+            .into_iter()
+            .filter(|i| i.agent == agent && i.session_id != session_id)
+            .collect();
+
+        // Taken before harvesting, so anything appended meanwhile is picked up next run
+        let size = std::fs::metadata(&path).map(|m| m.len()).ok();
+        let result = harvest_session(&client, &path, &session_id, &config, previous, &open_items).await;
+
+        // Any outcome other than an error clears a previous failure
+        let before = dead_letters.len();
+        if result.is_ok() {
+            dead_letters.retain(|d| d.session_id != session_id);
+        }
+        if dead_letters.len() != before {
+            save_dead_letters(&paths.dead_letter_path, &dead_letters)?;
+        }
+
+        match result {
+            Ok(Some(Harvested {
+                learning,
+                completed,
+                merge_error,
+            })) => {
+                db.upsert_learning(&learning)?;
+                for id in &completed {
+                    db.set_action_status(id, ActionStatus::Done, Some(&session_id), None)?;
+                }
+                stats.closed += completed.len();
+                if previous.is_some() {
+                    stats.updated += 1;
+                } else {
+                    stats.harvested += 1;
+                }
 
                 println!(
-                    "{} topics, {} decisions, {} facts",
+                    "{} topics, {} decisions, {} facts{}{}",
                     learning.topics.len().to_string().cyan(),
                     learning.decisions.len().to_string().cyan(),
-                    learning.facts_learned.len().to_string().cyan()
+                    learning.facts_learned.len().to_string().cyan(),
+                    if completed.is_empty() {
+                        String::new()
+                    } else {
+                        format!(", {} action items done", completed.len().to_string().green())
+                    },
+                    if learning.version > 1 {
+                        format!(" (v{})", learning.version).dimmed().to_string()
+                    } else {
+                        String::new()
+                    }
                 );
-                stats.harvested += 1;
+                if let Some(e) = merge_error {
+                    println!(
+                        "  {} merging its parts failed, so they were kept side by side \
+                         (later decisions don't replace earlier ones): {:#}",
+                        "warning:".yellow(),
+                        e
+                    );
+                    stats.unmerged += 1;
+                }
             }
             Ok(None) => {
-                println!("{}", "skipped (too short)".dimmed());
+                if previous.is_some() {
+                    // The log grew without new text (tool calls, usage records): don't re-read it every run
+                    if let Some(size) = size {
+                        db.set_harvested_bytes(&session_id, size)?;
+                    }
+                    println!("{}", "skipped (no new messages)".dimmed());
+                } else {
+                    println!("{}", "skipped (too short)".dimmed());
+                }
                 stats.skipped += 1;
             }
             Err(e) => {
-                println!("{} {}", "error:".red(), e);
+                println!("{} {:#}", "error:".red(), e);
+                record_failure(&mut dead_letters, &session_id, &path, &e);
+                save_dead_letters(&paths.dead_letter_path, &dead_letters)?;
                 stats.errors += 1;
             }
         }
     }
 
     println!(
-        "\n{} Harvested {} sessions ({} skipped, {} errors)",
+        "\n{} Harvested {} sessions, updated {} ({} skipped, {} deferred, {} errors)",
         "✓".green(),
         stats.harvested.to_string().cyan(),
+        stats.updated.to_string().cyan(),
         stats.skipped.to_string().yellow(),
+        stats.deferred.to_string().yellow(),
         stats.errors.to_string().red()
     );
+    if stats.unmerged > 0 {
+        println!(
+            "{} {} long sessions' parts could not be merged and were kept side by side",
+            "→".yellow(),
+            stats.unmerged.to_string().yellow()
+        );
+    }
+    if stats.closed > 0 {
+        println!(
+            "{} Marked {} earlier action items done",
+            "✓".green(),
+            stats.closed.to_string().cyan()
+        );
+    }
+
+    if !dead_letters.is_empty() {
+        println!(
+            "{} {} sessions in {} will be retried on the next run",
+            "→".yellow(),
+            dead_letters.len().to_string().yellow(),
+            paths.dead_letter_path.display().to_string().dimmed()
+        );
+    }
 
     Ok(stats)
 }
 
+/// Whether a session file was modified within the settle window
+fn is_still_being_written(path: &Path, settle_minutes: i64) -> bool {
+    if settle_minutes <= 0 {
+        return false;
+    }
+
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(|modified| {
+            let modified: DateTime<Utc> = modified.into();
+            Utc::now() - modified < chrono::Duration::minutes(settle_minutes)
+        })
+        .unwrap_or(false)
+}
+
+/// Add or update the dead-letter entry for a failed session
+fn record_failure(dead_letters: &mut Vec<DeadLetter>, session_id: &str, path: &Path, error: &anyhow::Error) {
+    let now = Utc::now().to_rfc3339();
+    let error = format!("{:#}", error);
+
+    if let Some(dead) = dead_letters.iter_mut().find(|d| d.session_id == session_id) {
+        dead.attempts += 1;
+        dead.error = error;
+        dead.last_failed_at = now;
+        return;
+    }
+
+    dead_letters.push(DeadLetter {
+        session_id: session_id.to_string(),
+        agent: crate::agent_from_path(path),
+        path: path.display().to_string(),
+        error,
+        attempts: 1,
+        first_failed_at: now.clone(),
+        last_failed_at: now,
+    });
+}
+
+/// Load sessions whose harvest failed on an earlier run
+pub fn load_dead_letters(path: &Path) -> Result<Vec<DeadLetter>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open dead-letter file: {}", path.display()))?;
+    let reader = BufReader::new(file);
+
+    Ok(reader
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect())
+}
+
+/// Rewrite the dead-letter file, removing it once nothing is left to retry
+fn save_dead_letters(path: &Path, dead_letters: &[DeadLetter]) -> Result<()> {
+    if dead_letters.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+
+    let mut out = String::new();
+    for dead in dead_letters {
+        out.push_str(&serde_json::to_string(dead)?);
+        out.push('\n');
+    }
+    std::fs::write(path, out)
+        .with_context(|| format!("Failed to write dead-letter file: {}", path.display()))
+}
+
 /// Strip markdown code fences from LLM response
 /// Handles ```json ... ``` and ``` ... ``` patterns
 fn strip_markdown_json(response: &str) -> String {
@@ -170,76 +501,644 @@ fn strip_markdown_json(response: &str) -> String {
     without_suffix.trim().to_string()
 }
 
-/// Harvest a single session
+/// What harvesting one session produced
+struct Harvested {
+    learning: Learning,
+    /// Ids of earlier open action items the session finished
+    completed: Vec<String>,
+    /// Why merging the session's parts failed, when they were concatenated instead
+    merge_error: Option<anyhow::Error>,
+}
+
+/// Harvest a single session, or only its new messages when `previous` is
+/// the learning from an earlier run.
 async fn harvest_session(
     client: &OpenRouterClient,
     path: &Path,
     session_id: &str,
     config: &HarvestConfig,
-) -> Result<Option<Learning>> {
+    previous: Option<&Learning>,
+    open_items: &[ActionItem],
+) -> Result<Option<Harvested>> {
+    // Size first, so anything appended while we work is picked up next run
+    let size = std::fs::metadata(path)?.len();
     let session = Session::from_file(path)?;
+    let messages = transcript_messages(&session.messages);
+
+    let start = match previous {
+        // Harvested before sizes were recorded: only the message count is known
+        Some(p) if p.harvested_bytes == 0 => {
+            let end = session
+                .messages
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| m.msg_type == "message")
+                .nth(p.message_count)
+                .map(|(i, _)| i)
+                .unwrap_or(session.messages.len());
+            transcript_messages(&session.messages[..end]).len()
+        }
+        Some(p) => p.harvested_messages,
+        None => 0,
+    };
+    if previous.is_some() && messages.len() <= start {
+        return Ok(None);
+    }
 
     // Skip short sessions
-    if session.message_count < config.min_messages {
+    if previous.is_none() && session.message_count < config.min_messages {
         return Ok(None);
     }
 
-    // Format conversation for AI
-    let transcript = format_transcript(&session);
+    // Split long conversations into segments that fit the model's context
+    let window = config.context_window.unwrap_or_else(|| context_window(&config.model));
+    let segments = split_segments(&messages[start..], segment_chars(window));
 
-    // Truncate if too long (keep within context limits)
-    let max_chars = 50000;
-    let truncated = if transcript.len() > max_chars {
-        format!("{}...\n[truncated]", &transcript[..max_chars])
+    let date = previous.map(|p| p.date.clone()).unwrap_or_else(|| {
+        session
+            .first_timestamp
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    });
+
+    // Only items raised before this session can be finished by it; offer the newest
+    let mut candidates: Vec<&ActionItem> = open_items.iter().filter(|i| i.date <= date).collect();
+    candidates.reverse();
+    candidates.truncate(MAX_OPEN_ITEMS);
+
+    let mut harvest_prompt = config.categories.prompt();
+    if let Some(previous) = previous {
+        let earlier = serde_json::to_string_pretty(&ExtractedLearning::from(previous))?;
+        harvest_prompt.push_str(&CONTINUATION_PROMPT.replace("{previous}", &earlier));
+    }
+
+    let mut schema = config.categories.json_schema();
+    if !candidates.is_empty() {
+        let items = candidates
+            .iter()
+            .map(|i| format!("[{}] {}", i.short_id(), i.text))
+            .collect::<Vec<_>>()
+            .join("\n");
+        harvest_prompt.push_str(&COMPLETION_PROMPT.replace("{items}", &items));
+        add_completion_field(&mut schema);
+    }
+
+    // Map: extract from each segment. Reduce: merge partial results,
+    // starting from the earlier learning when this is a continuation.
+    let mut partials: Vec<ExtractedLearning> = previous.map(ExtractedLearning::from).into_iter().collect();
+    if segments.len() == 1 {
+        let prompt = format!("{}\nConversation:\n{}", harvest_prompt, segments[0]);
+        partials.push(extract(client, &prompt, &config.model, &config.categories, &schema).await?);
     } else {
-        transcript
-    };
+        for (i, segment) in segments.iter().enumerate() {
+            let prompt = format!(
+                "{}- This is part {} of {} of a long conversation; extract only what appears in this part\n\nConversation:\n{}",
+                harvest_prompt,
+                i + 1,
+                segments.len(),
+                segment
+            );
+            partials.push(extract(client, &prompt, &config.model, &config.categories, &schema).await?);
+        }
+    }
 
-    // Call AI for extraction
-    let response = client
-        .chat("You are a helpful assistant that extracts structured information.",
-              &format!("{}{}", HARVEST_PROMPT, truncated),
-              &config.model)
-        .await?;
+    let (extracted, merge_error) = if partials.len() == 1 {
+        (partials.remove(0), None)
+    } else {
+        merge_partials(client, partials, &config.model, &config.categories, &schema).await?
+    };
 
-    // Parse response as JSON (strip markdown code fences if present)
-    let json_str = strip_markdown_json(&response);
-    let extracted: ExtractedLearning = serde_json::from_str(&json_str)
-        .context("Failed to parse AI response as JSON")?;
+    // Drop citations of turns the session doesn't have
+    let turn_count = session.get_turns().len();
+    let provenance: Vec<Provenance> = extracted
+        .provenance
+        .iter()
+        .filter(|p| p.turn_start < turn_count)
+        .map(|p| Provenance {
+            turn_end: p.turn_end.min(turn_count),
+            ..p.clone()
+        })
+        .collect();
 
-    let date = session
-        .first_timestamp
-        .map(|t| t.format("%Y-%m-%d").to_string())
-        .unwrap_or_else(|| "unknown".to_string());
+    // Map the model's short ids back to the items it was offered
+    let completed: Vec<String> = candidates
+        .iter()
+        .filter(|i| {
+            extracted
+                .completed_action_items
+                .iter()
+                .any(|id| id.trim().trim_matches(|c| c == '[' || c == ']') == i.short_id())
+        })
+        .map(|i| i.id.clone())
+        .collect();
 
-    Ok(Some(Learning {
+    let learning = Learning {
         session_id: session_id.to_string(),
+        agent: session.agent.clone(),
         date,
         topics: extracted.topics,
         decisions: extracted.decisions,
         facts_learned: extracted.facts_learned,
         action_items: extracted.action_items,
+        categories: extracted.categories,
+        entities: extracted.entities,
+        relations: extracted.relations,
+        provenance,
         summary: extracted.summary,
         message_count: session.message_count,
         cost: session.total_cost,
         harvested_at: Utc::now().to_rfc3339(),
+        version: previous.map(|p| p.version + 1).unwrap_or(1),
+        harvested_messages: messages.len(),
+        harvested_bytes: size,
+    };
+
+    Ok(Some(Harvested {
+        learning,
+        completed,
+        merge_error,
     }))
 }
 
-#[derive(Deserialize)]
+/// Add the `completed_action_items` list to an extraction schema
+fn add_completion_field(schema: &mut serde_json::Value) {
+    schema["properties"]["completed_action_items"] =
+        serde_json::json!({ "type": "array", "items": { "type": "string" } });
+    if let Some(required) = schema["required"].as_array_mut() {
+        required.push(serde_json::json!("completed_action_items"));
+    }
+}
+
+/// Call AI for extraction and parse its JSON answer.
+///
+/// Uses structured output where the model supports it. If the answer still
+/// doesn't validate, the error is sent back for up to `MAX_REPAIR_ATTEMPTS`
+/// repair rounds before giving up.
+async fn extract(
+    client: &OpenRouterClient,
+    prompt: &str,
+    model: &str,
+    categories: &CategorySet,
+    schema: &serde_json::Value,
+) -> Result<ExtractedLearning> {
+    let mut response = if openrouter::supports_structured_output(model) {
+        match client
+            .chat_structured(EXTRACT_SYSTEM_PROMPT, prompt, model, "learnings", schema)
+            .await
+        {
+            Ok(response) => response,
+            // The provider may still refuse response_format; ask without it
+            Err(e)
+                if e.downcast_ref::<ChatApiError>()
+                    .is_some_and(ChatApiError::rejects_response_format) =>
+            {
+                client.chat(EXTRACT_SYSTEM_PROMPT, prompt, model).await?
+            }
+            Err(e) => return Err(e),
+        }
+    } else {
+        client.chat(EXTRACT_SYSTEM_PROMPT, prompt, model).await?
+    };
+
+    let mut attempt = 0;
+    loop {
+        let error = match parse_extracted(&response, categories) {
+            Ok(extracted) => return Ok(extracted),
+            Err(e) => e,
+        };
+
+        if attempt == MAX_REPAIR_ATTEMPTS {
+            return Err(error.context(format!(
+                "AI response still invalid after {} repair attempts",
+                MAX_REPAIR_ATTEMPTS
+            )));
+        }
+        attempt += 1;
+
+        let repair = format!(
+            "{}{:#}\n\nRequired JSON Schema:\n{}\n\nYour previous response:\n{}",
+            REPAIR_PROMPT,
+            error,
+            serde_json::to_string_pretty(schema)?,
+            response
+        );
+        response = client.chat(EXTRACT_SYSTEM_PROMPT, &repair, model).await?;
+    }
+}
+
+/// Leniently parse an extraction response.
+///
+/// Tolerates markdown fences, prose around the JSON object, trailing commas,
+/// missing or null list fields, and a bare string where a list is expected.
+/// Anything else is a validation error, worded to be sent back for repair.
+fn parse_extracted(response: &str, categories: &CategorySet) -> Result<ExtractedLearning> {
+    let stripped = strip_markdown_json(response);
+
+    let value: serde_json::Value = match serde_json::from_str(&stripped) {
+        Ok(value) => value,
+        Err(first_error) => {
+            let invalid = || anyhow::anyhow!("Invalid JSON: {}", first_error);
+            let object = find_json_object(&stripped).ok_or_else(invalid)?;
+            serde_json::from_str(&remove_trailing_commas(object)).map_err(|_| invalid())?
+        }
+    };
+
+    let serde_json::Value::Object(mut fields) = value else {
+        anyhow::bail!("Expected a JSON object at the top level");
+    };
+
+    let mut problems = Vec::new();
+    let mut provenance = Vec::new();
+    let mut list = |name: &str| -> Vec<String> {
+        match fields.remove(name) {
+            None | Some(serde_json::Value::Null) => Vec::new(),
+            Some(serde_json::Value::String(s)) if s.trim().is_empty() => Vec::new(),
+            Some(serde_json::Value::String(s)) => vec![s],
+            Some(serde_json::Value::Array(items)) => items
+                .into_iter()
+                .filter_map(|item| match item {
+                    serde_json::Value::String(s) => Some(s),
+                    serde_json::Value::Null => None,
+                    // Traced items come as {text, turns, quote}
+                    serde_json::Value::Object(item) if item.get("text").map(|t| t.is_string()).unwrap_or(false) => {
+                        let text = item["text"].as_str().unwrap_or_default().to_string();
+                        let quote = item.get("quote").and_then(|q| q.as_str());
+                        if let Some(turns) = item.get("turns") {
+                            provenance.extend(trace::from_turns(name, &text, turns, quote));
+                        }
+                        Some(text)
+                    }
+                    other => Some(other.to_string()),
+                })
+                .filter(|s| !s.trim().is_empty())
+                .collect(),
+            Some(_) => {
+                problems.push(format!("\"{}\" must be an array of strings", name));
+                Vec::new()
+            }
+        }
+    };
+
+    let mut extracted = ExtractedLearning::default();
+    for category in &categories.categories {
+        let items = list(&category.name);
+        match category.name.as_str() {
+            "topics" => extracted.topics = items,
+            "decisions" => extracted.decisions = items,
+            "facts_learned" => extracted.facts_learned = items,
+            "action_items" => extracted.action_items = items,
+            name => {
+                extracted.categories.insert(name.to_string(), items);
+            }
+        }
+    }
+    // Only asked for when open action items were offered, so never required
+    extracted.completed_action_items = list("completed_action_items");
+
+    match parse_entities(fields.remove("entities")) {
+        Ok(entities) => extracted.entities = entities,
+        Err(problem) => problems.push(problem),
+    }
+    match parse_relations(fields.remove("relations")) {
+        Ok(relations) => extracted.relations = relations,
+        Err(problem) => problems.push(problem),
+    }
+
+    let summary = match fields.remove("summary") {
+        Some(serde_json::Value::String(s)) if !s.trim().is_empty() => s,
+        Some(serde_json::Value::Array(parts)) if !parts.is_empty() => parts
+            .iter()
+            .filter_map(|p| p.as_str())
+            .collect::<Vec<_>>()
+            .join(" "),
+        None | Some(serde_json::Value::Null) => {
+            problems.push("missing required field \"summary\"".to_string());
+            String::new()
+        }
+        Some(_) => {
+            problems.push("\"summary\" must be a non-empty string".to_string());
+            String::new()
+        }
+    };
+
+    if !problems.is_empty() {
+        anyhow::bail!("Invalid learnings: {}", problems.join("; "));
+    }
+
+    extracted.summary = summary;
+    extracted.provenance = provenance;
+    Ok(extracted)
+}
+
+/// Leniently parse extracted entities: objects with a name and type, or bare
+/// names. Unknown types become "other" and unusable entries are skipped.
+fn parse_entities(value: Option<serde_json::Value>) -> std::result::Result<Vec<Entity>, String> {
+    let items = match value {
+        None | Some(serde_json::Value::Null) => return Ok(Vec::new()),
+        Some(serde_json::Value::Array(items)) => items,
+        Some(_) => return Err("\"entities\" must be an array of objects".to_string()),
+    };
+
+    Ok(items
+        .into_iter()
+        .filter_map(|item| match item {
+            serde_json::Value::String(name) => Some((name, String::new())),
+            serde_json::Value::Object(fields) => Some((
+                fields.get("name")?.as_str()?.to_string(),
+                fields.get("type").and_then(|t| t.as_str()).unwrap_or_default().to_string(),
+            )),
+            _ => None,
+        })
+        .filter(|(name, _)| !name.trim().is_empty())
+        .map(|(name, kind)| Entity {
+            name: name.trim().to_string(),
+            kind: graph::normalize_kind(&kind),
+        })
+        .collect())
+}
+
+/// Leniently parse extracted relations: subject/predicate/object objects, or
+/// `"subject | predicate | object"` strings. Incomplete entries are skipped.
+fn parse_relations(value: Option<serde_json::Value>) -> std::result::Result<Vec<Relation>, String> {
+    let items = match value {
+        None | Some(serde_json::Value::Null) => return Ok(Vec::new()),
+        Some(serde_json::Value::Array(items)) => items,
+        Some(_) => return Err("\"relations\" must be an array of objects".to_string()),
+    };
+
+    Ok(items
+        .into_iter()
+        .filter_map(|item| {
+            let parts: Vec<String> = match item {
+                serde_json::Value::String(text) => text.split('|').map(|p| p.trim().to_string()).collect(),
+                serde_json::Value::Object(fields) => ["subject", "predicate", "object"]
+                    .iter()
+                    .map(|key| fields.get(*key).and_then(|v| v.as_str()).unwrap_or_default().trim().to_string())
+                    .collect(),
+                _ => return None,
+            };
+            match parts.as_slice() {
+                [subject, predicate, object] if parts.iter().all(|p| !p.is_empty()) => Some(Relation {
+                    subject: subject.clone(),
+                    predicate: predicate.to_lowercase(),
+                    object: object.clone(),
+                }),
+                _ => None,
+            }
+        })
+        .collect())
+}
+
+/// The outermost `{ ... }` block in text that may have prose around it
+fn find_json_object(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in text[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..=start + i]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Drop commas that directly precede `}` or `]` (outside strings)
+fn remove_trailing_commas(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let chars: Vec<char> = json.chars().collect();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        out.push(c);
+    }
+
+    out
+}
+
+/// Reduce step: ask the model to merge per-segment results, then dedupe.
+///
+/// Falls back to a plain concatenation if the merge call fails, so a long
+/// session is never lost to one bad response; the merge error is returned
+/// alongside so the caller can report it.
+async fn merge_partials(
+    client: &OpenRouterClient,
+    partials: Vec<ExtractedLearning>,
+    model: &str,
+    categories: &CategorySet,
+    schema: &serde_json::Value,
+) -> Result<(ExtractedLearning, Option<anyhow::Error>)> {
+    let prompt = format!("{}{}", MERGE_PROMPT, serde_json::to_string_pretty(&partials)?);
+
+    let mut merge_error = None;
+    let mut merged = match extract(client, &prompt, model, categories, schema).await {
+        Ok(merged) => merged,
+        Err(e) => {
+            merge_error = Some(e);
+            ExtractedLearning {
+                topics: partials.iter().flat_map(|p| p.topics.clone()).collect(),
+                decisions: partials.iter().flat_map(|p| p.decisions.clone()).collect(),
+                facts_learned: partials.iter().flat_map(|p| p.facts_learned.clone()).collect(),
+                action_items: partials.iter().flat_map(|p| p.action_items.clone()).collect(),
+                categories: categories
+                    .custom()
+                    .map(|c| {
+                        let items = partials
+                            .iter()
+                            .flat_map(|p| p.categories.get(&c.name).cloned().unwrap_or_default())
+                            .collect();
+                        (c.name.clone(), items)
+                    })
+                    .collect(),
+                summary: partials
+                    .iter()
+                    .map(|p| p.summary.trim())
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                entities: partials.iter().flat_map(|p| p.entities.clone()).collect(),
+                relations: partials.iter().flat_map(|p| p.relations.clone()).collect(),
+                completed_action_items: Vec::new(),
+                provenance: Vec::new(),
+            }
+        }
+    };
+
+    // Completion is per part; an item finished in any part is finished
+    merged.completed_action_items = partials
+        .iter()
+        .flat_map(|p| p.completed_action_items.clone())
+        .collect();
+
+    // The model usually dedupes, but exact repeats still slip through
+    dedupe(&mut merged.topics);
+    dedupe(&mut merged.decisions);
+    dedupe(&mut merged.facts_learned);
+    dedupe(&mut merged.action_items);
+    dedupe(&mut merged.completed_action_items);
+    for items in merged.categories.values_mut() {
+        dedupe(items);
+    }
+    // Merged items may be reworded; give each the turns of the item it came from
+    let sources: Vec<Provenance> = partials.iter().flat_map(|p| p.provenance.clone()).collect();
+    merged.provenance = trace::TRACED
+        .iter()
+        .flat_map(|&kind| {
+            let items = match kind {
+                "decisions" => &merged.decisions,
+                "facts_learned" => &merged.facts_learned,
+                _ => &merged.action_items,
+            };
+            items.iter().filter_map(|text| trace::match_provenance(kind, text, &sources))
+        })
+        .collect();
+
+    let mut seen = std::collections::HashSet::new();
+    merged.entities.retain(|e| seen.insert(graph::entity_key(&e.name)));
+    let mut seen = std::collections::HashSet::new();
+    merged.relations.retain(|r| {
+        seen.insert((graph::entity_key(&r.subject), r.predicate.clone(), graph::entity_key(&r.object)))
+    });
+
+    Ok((merged, merge_error))
+}
+
+/// Drop items that repeat an earlier one, ignoring case and whitespace
+fn dedupe(items: &mut Vec<String>) {
+    let mut seen = std::collections::HashSet::new();
+    items.retain(|item| {
+        let key = item.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        seen.insert(key)
+    });
+}
+
+/// Transcript characters per segment for a model's context window
+fn segment_chars(context_window: usize) -> usize {
+    // Use at most half the window for input, leaving room for prompt and answer
+    let input_tokens = (context_window / 2).saturating_sub(RESERVED_TOKENS).max(1_000);
+    input_tokens * CHARS_PER_TOKEN
+}
+
+/// Group transcript messages into segments of at most `max_chars` characters.
+///
+/// Segments break between messages; a single oversized message is split on
+/// char boundaries.
+fn split_segments(messages: &[String], max_chars: usize) -> Vec<String> {
+    let mut segments: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+
+    for message in messages {
+        let message_chars = message.chars().count();
+
+        if current_chars > 0 && current_chars + message_chars + 2 > max_chars {
+            segments.push(std::mem::take(&mut current));
+            current_chars = 0;
+        }
+
+        if message_chars > max_chars {
+            let chars: Vec<char> = message.chars().collect();
+            for piece in chars.chunks(max_chars) {
+                segments.push(piece.iter().collect());
+            }
+            continue;
+        }
+
+        if current_chars > 0 {
+            current.push_str("\n\n");
+            current_chars += 2;
+        }
+        current.push_str(message);
+        current_chars += message_chars;
+    }
+
+    if !current.is_empty() || segments.is_empty() {
+        segments.push(current);
+    }
+
+    segments
+}
+
+#[derive(Default, Serialize)]
 struct ExtractedLearning {
     topics: Vec<String>,
     decisions: Vec<String>,
     facts_learned: Vec<String>,
     action_items: Vec<String>,
+    #[serde(flatten)]
+    categories: BTreeMap<String, Vec<String>>,
+    entities: Vec<Entity>,
+    relations: Vec<Relation>,
     summary: String,
+    /// Short ids of earlier open action items the conversation finished
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    completed_action_items: Vec<String>,
+    /// Turn numbers aren't shown to the merge step; they're matched back after it
+    #[serde(skip)]
+    provenance: Vec<Provenance>,
+}
+
+impl From<&Learning> for ExtractedLearning {
+    fn from(learning: &Learning) -> Self {
+        Self {
+            topics: learning.topics.clone(),
+            decisions: learning.decisions.clone(),
+            facts_learned: learning.facts_learned.clone(),
+            action_items: learning.action_items.clone(),
+            categories: learning.categories.clone(),
+            entities: learning.entities.clone(),
+            relations: learning.relations.clone(),
+            summary: learning.summary.clone(),
+            completed_action_items: Vec::new(),
+            provenance: learning.provenance.clone(),
+        }
+    }
 }
 
-/// Format session as readable transcript
-fn format_transcript(session: &Session) -> String {
+/// Format session messages as readable transcript lines (`[turn N] ROLE: text`).
+///
+/// Turns are numbered like `Session::get_turns`: each user message starts
+/// the next one. Messages before the first user message are unlabeled.
+fn transcript_messages(messages: &[SessionMessage]) -> Vec<String> {
     let mut lines = Vec::new();
+    let mut turn: Option<usize> = None;
 
-    for msg in &session.messages {
+    for msg in messages {
         if msg.msg_type != "message" {
             continue;
         }
@@ -266,40 +1165,26 @@ fn format_transcript(session: &Session) -> String {
             })
             .unwrap_or_default();
 
-        if !text.is_empty() {
-            lines.push(format!("{}: {}", role.to_uppercase(), text));
+        if text.is_empty() {
+            continue;
         }
-    }
-
-    lines.join("\n\n")
-}
-
-/// Load session IDs that have already been harvested
-fn load_harvested_ids(path: &Path) -> Result<std::collections::HashSet<String>> {
-    let mut ids = std::collections::HashSet::new();
-
-    if !path.exists() {
-        return Ok(ids);
-    }
-
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-
-    for line in reader.lines() {
-        let line = line?;
-        if let Ok(learning) = serde_json::from_str::<Learning>(&line) {
-            ids.insert(learning.session_id);
+        if role == "user" {
+            turn = Some(turn.map(|t| t + 1).unwrap_or(0));
+        }
+        match turn {
+            Some(turn) => lines.push(format!("[turn {}] {}: {}", turn, role.to_uppercase(), text)),
+            None => lines.push(format!("{}: {}", role.to_uppercase(), text)),
         }
     }
 
-    Ok(ids)
+    lines
 }
 
 /// Discover sessions that need harvesting
 fn discover_sessions_for_harvest(
     sessions_dir: &Path,
     config: &HarvestConfig,
-    already_harvested: &std::collections::HashSet<String>,
+    already_harvested: &HashMap<String, Learning>,
 ) -> Result<Vec<(String, std::path::PathBuf)>> {
     let mut sessions = Vec::new();
 
@@ -325,9 +1210,14 @@ fn discover_sessions_for_harvest(
             .unwrap_or("unknown")
             .to_string();
 
-        // Skip already harvested
-        if already_harvested.contains(&session_id) {
-            continue;
+        // Skip harvested sessions unless the file grew since. Learnings from before
+        // sizes were recorded are checked against their message count once, and
+        // get the current size recorded if the session didn't grow.
+        if let Some(learning) = already_harvested.get(&session_id) {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            if learning.harvested_bytes != 0 && size <= learning.harvested_bytes {
+                continue;
+            }
         }
 
         // Check date filter
@@ -361,6 +1251,14 @@ fn discover_sessions_for_harvest(
 #[derive(Default)]
 pub struct HarvestStats {
     pub harvested: usize,
+    /// Previously harvested sessions that grew and were re-harvested
+    pub updated: usize,
     pub skipped: usize,
+    /// Sessions left for a later run because they're still being written
+    pub deferred: usize,
     pub errors: usize,
+    /// Earlier action items marked done because a harvested session finished them
+    pub closed: usize,
+    /// Long sessions whose parts couldn't be merged and were concatenated
+    pub unmerged: usize,
 }
@@ -0,0 +1,253 @@
+//! HTML - a single self-contained page of learnings
+//!
+//! Everything (styles, script, content) is inlined so the page can be opened
+//! from disk or attached anywhere. Sessions are grouped by date with an index
+//! of dates on the side; a search box and per-category checkboxes filter the
+//! items in the browser.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::categories::{self, CategorySet};
+use crate::export::{self, ExportScope, ExportStats, Exporter};
+use crate::harvest::Learning;
+use crate::session::short_id;
+use crate::todo::{self, ActionStatus};
+use crate::Paths;
+
+const STYLE: &str = r#"
+:root { --fg: #1f2328; --muted: #656d76; --line: #d0d7de; --accent: #0969da; --bg: #fff; --soft: #f6f8fa; }
+@media (prefers-color-scheme: dark) {
+  :root { --fg: #e6edf3; --muted: #8d96a0; --line: #30363d; --accent: #4493f8; --bg: #0d1117; --soft: #161b22; }
+}
+* { box-sizing: border-box; }
+body { margin: 0; font: 15px/1.5 -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; color: var(--fg); background: var(--bg); }
+header { position: sticky; top: 0; z-index: 1; padding: 12px 24px; background: var(--soft); border-bottom: 1px solid var(--line); }
+header h1 { margin: 0 0 4px; font-size: 20px; }
+.meta, .count, .date, .topics { color: var(--muted); font-size: 13px; }
+.filters { display: flex; flex-wrap: wrap; gap: 12px; align-items: center; margin-top: 8px; }
+.filters input[type=search] { flex: 1 1 240px; padding: 6px 10px; border: 1px solid var(--line); border-radius: 6px; background: var(--bg); color: var(--fg); }
+.layout { display: flex; }
+nav { flex: 0 0 180px; position: sticky; top: 110px; align-self: flex-start; max-height: calc(100vh - 120px); overflow-y: auto; padding: 16px 24px; }
+nav ul { list-style: none; margin: 0; padding: 0; }
+nav a { color: var(--accent); text-decoration: none; }
+main { flex: 1; min-width: 0; padding: 8px 24px 48px; }
+.day h2 { font-size: 17px; border-bottom: 1px solid var(--line); padding-bottom: 4px; }
+.session { margin: 0 0 20px; }
+.session h3 { margin: 0; font-size: 15px; }
+.summary { margin: 4px 0; }
+.session ul { margin: 4px 0; padding-left: 20px; }
+.kind { display: inline-block; min-width: 92px; color: var(--muted); font-size: 12px; text-transform: uppercase; letter-spacing: .03em; }
+.done { color: var(--muted); }
+.dropped { color: var(--muted); text-decoration: line-through; }
+.transcript h3 { margin: 24px 0 8px; font-size: 15px; border-bottom: 1px solid var(--line); padding-bottom: 4px; }
+.message { margin: 0 0 14px; }
+.message .head { color: var(--muted); font-size: 13px; }
+.role { color: var(--fg); font-weight: 600; }
+.text { white-space: pre-wrap; overflow-wrap: anywhere; }
+details { margin: 4px 0; }
+summary { cursor: pointer; color: var(--muted); font-size: 13px; }
+pre { margin: 4px 0; padding: 8px 12px; background: var(--soft); border: 1px solid var(--line); border-radius: 6px; overflow-x: auto; font-size: 13px; }
+[hidden] { display: none !important; }
+"#;
+
+const SCRIPT: &str = r##"
+const search = document.getElementById('q');
+const boxes = [...document.querySelectorAll('.filters input[type=checkbox]')];
+function apply() {
+  const term = search.value.trim().toLowerCase();
+  const kinds = new Set(boxes.filter(b => b.checked).map(b => b.dataset.kind));
+  let shown = 0;
+  document.querySelectorAll('.session').forEach(session => {
+    const headHit = !term || session.querySelector('.head').textContent.toLowerCase().includes(term);
+    let any = false;
+    session.querySelectorAll('.item').forEach(item => {
+      const ok = kinds.has(item.dataset.kind) && (headHit || item.textContent.toLowerCase().includes(term));
+      item.hidden = !ok;
+      if (ok) { any = true; shown++; }
+    });
+    session.hidden = !(any || (headHit && !term));
+  });
+  document.querySelectorAll('.day').forEach(day => {
+    const visible = [...day.querySelectorAll('.session')].some(s => !s.hidden);
+    day.hidden = !visible;
+    const link = document.querySelector('nav a[href="#' + day.id + '"]');
+    if (link) link.parentElement.hidden = !visible;
+  });
+  document.getElementById('count').textContent = shown + ' items';
+}
+search.addEventListener('input', apply);
+boxes.forEach(b => b.addEventListener('change', apply));
+apply();
+"##;
+
+/// `learnings.html`: all learnings on one filterable page
+pub struct HtmlExporter;
+
+impl Exporter for HtmlExporter {
+    fn default_path(&self, paths: &Paths) -> PathBuf {
+        paths.memory_dir.join("learnings.html")
+    }
+
+    fn export(&self, paths: &Paths, scope: &ExportScope, output: &Path) -> Result<ExportStats> {
+        let learnings = scope.learnings;
+        if learnings.is_empty() {
+            return Ok(ExportStats::default());
+        }
+
+        let categories = CategorySet::load(&paths.categories_path)?;
+        let action_items = export::load_action_items(paths)?;
+
+        let mut by_date: BTreeMap<&str, Vec<&Learning>> = BTreeMap::new();
+        for learning in learnings {
+            by_date.entry(learning.date.as_str()).or_default().push(learning);
+        }
+
+        // Item kinds in display order: built-in ones, then custom categories that occur
+        let mut kinds: Vec<String> = vec!["decisions".into(), "facts_learned".into(), "action_items".into()];
+        for learning in learnings {
+            for name in learning.categories.keys() {
+                if !kinds.contains(name) {
+                    kinds.push(name.clone());
+                }
+            }
+        }
+
+        let mut nav = String::new();
+        let mut days = String::new();
+        for (date, day_learnings) in by_date.iter().rev() {
+            nav.push_str(&format!(
+                "<li><a href=\"#d-{0}\">{0}</a> <span class=\"count\">({1})</span></li>\n",
+                escape(date),
+                day_learnings.len()
+            ));
+
+            days.push_str(&format!("<section class=\"day\" id=\"d-{0}\">\n<h2>{0}</h2>\n", escape(date)));
+            for learning in day_learnings {
+                let short_id = short_id(&learning.session_id);
+                days.push_str("<article class=\"session\">\n<div class=\"head\">\n");
+                days.push_str(&format!(
+                    "<h3><code title=\"{}\">{}</code>{}</h3>\n",
+                    escape(&learning.session_id),
+                    escape(short_id),
+                    if learning.agent == crate::DEFAULT_AGENT {
+                        String::new()
+                    } else {
+                        format!(" <span class=\"topics\">({})</span>", escape(&learning.agent))
+                    }
+                ));
+                if !learning.topics.is_empty() {
+                    days.push_str(&format!("<div class=\"topics\">{}</div>\n", escape(&learning.topics.join(", "))));
+                }
+                if !learning.summary.is_empty() {
+                    days.push_str(&format!("<p class=\"summary\">{}</p>\n", escape(&learning.summary)));
+                }
+                days.push_str("</div>\n<ul>\n");
+
+                let mut item = |kind: &str, text: &str, status: &str| {
+                    days.push_str(&format!(
+                        "<li class=\"item{}\" data-kind=\"{}\"><span class=\"kind\">{}</span> {}</li>\n",
+                        if status.is_empty() { String::new() } else { format!(" {}", status) },
+                        escape(kind),
+                        escape(&categories::label(kind)),
+                        escape(text)
+                    ));
+                };
+                for decision in &learning.decisions {
+                    item("decisions", decision, "");
+                }
+                for fact in &learning.facts_learned {
+                    item("facts_learned", fact, "");
+                }
+                for action in &learning.action_items {
+                    let status = action_items
+                        .get(&todo::item_id(&learning.session_id, action))
+                        .map(|i| i.status)
+                        .unwrap_or(ActionStatus::Open);
+                    match status {
+                        ActionStatus::Open => item("action_items", &format!("☐ {}", action), ""),
+                        ActionStatus::Done => item("action_items", &format!("☑ {}", action), "done"),
+                        ActionStatus::Dropped => item("action_items", action, "dropped"),
+                    }
+                }
+                let configured = categories.custom().map(|c| c.name.as_str());
+                let unconfigured = learning
+                    .categories
+                    .keys()
+                    .map(|k| k.as_str())
+                    .filter(|k| !categories.custom().any(|c| c.name == *k));
+                for name in configured.chain(unconfigured) {
+                    for text in learning.categories.get(name).into_iter().flatten() {
+                        item(name, text, "");
+                    }
+                }
+
+                days.push_str("</ul>\n</article>\n");
+            }
+            days.push_str("</section>\n");
+        }
+
+        let filters: String = kinds
+            .iter()
+            .map(|kind| {
+                format!(
+                    "<label><input type=\"checkbox\" data-kind=\"{}\" checked> {}</label>",
+                    escape(kind),
+                    escape(&categories::label(kind))
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let stats = ExportStats::count(learnings);
+        let body = format!(
+            "<header>\n<h1>Profundo Learnings</h1>\n\
+             <div class=\"meta\">{} sessions, {} decisions, {} facts, {} action items. Generated {}.</div>\n\
+             <div class=\"filters\">\n<input id=\"q\" type=\"search\" placeholder=\"Filter by text, topic or session\" autofocus>\n{}\n<span id=\"count\" class=\"count\"></span>\n</div>\n\
+             </header>\n<div class=\"layout\">\n<nav>\n<ul>\n{}</ul>\n</nav>\n<main>\n{}</main>\n</div>\n<script>{}</script>\n",
+            stats.sessions,
+            stats.decisions,
+            stats.facts,
+            stats.actions,
+            Utc::now().format("%Y-%m-%d %H:%M UTC"),
+            filters,
+            nav,
+            days,
+            SCRIPT
+        );
+
+        fs::write(output, page("Profundo Learnings", &body)).context("Failed to write export file")?;
+        Ok(stats)
+    }
+}
+
+/// A complete HTML document with the shared style inlined
+pub fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body
+    )
+}
+
+/// Escape text for HTML content and attribute values
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -3,29 +3,69 @@
 //! Semantic search and learning extraction from Clawdbot session logs.
 //! Named for "the deep" (Spanish: profundo) - where memories sink and are retrieved from.
 
+pub mod adr;
+pub mod ask;
+pub mod categories;
+pub mod consolidate;
 pub mod db;
+pub mod digest;
 pub mod embed;
+pub mod eval;
 pub mod export;
+pub mod graph;
 pub mod harvest;
+pub mod html;
+pub mod obsidian;
 pub mod openrouter;
+pub mod profile;
 pub mod recall;
+pub mod related;
 pub mod session;
 pub mod stats;
+pub mod tasks;
+pub mod template;
+pub mod todo;
+pub mod trace;
+pub mod transcript;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Agent name used when a session directory isn't under `agents/<name>/sessions`
+pub const DEFAULT_AGENT: &str = "main";
+
+/// Config roots to search, newest layout first
+const CONFIG_ROOTS: &[(&str, &str)] = &[
+    (".openclaw", "openclaw.json"),
+    (".clawdbot", "clawdbot.json"),
+];
+
+/// A session log directory belonging to one agent
+#[derive(Debug, Clone)]
+pub struct AgentSessions {
+    /// Agent name (the `<name>` in `agents/<name>/sessions`)
+    pub name: String,
+    /// Where this agent's session logs live
+    pub sessions_dir: PathBuf,
+}
 
 /// Default paths for Clawdbot integration
 pub struct Paths {
-    /// Where Clawdbot stores session logs
-    pub sessions_dir: PathBuf,
+    /// Session log directories, one per discovered agent
+    pub agents: Vec<AgentSessions>,
     /// Where the workspace memory lives
     pub memory_dir: PathBuf,
     /// Profundo's SQLite database
     pub db_path: PathBuf,
-    /// Extracted learnings JSONL
+    /// Legacy learnings JSONL, imported into the database once
     pub learnings_path: PathBuf,
     /// Cursor for incremental processing
     pub cursor_path: PathBuf,
+    /// Sessions whose harvest failed, retried on the next run
+    pub dead_letter_path: PathBuf,
+    /// Custom learning categories and prompt template
+    pub categories_path: PathBuf,
+    /// User templates for rollups and markdown export
+    pub templates_dir: PathBuf,
 }
 
 impl Default for Paths {
@@ -33,45 +73,134 @@ impl Default for Paths {
         let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
         let home = PathBuf::from(&home);
 
-        // Read workspace from clawdbot.json, fall back to ~/clawd (Clawdbot default)
-        let workspace = read_clawdbot_workspace(&home)
+        // Read workspace from openclaw.json / clawdbot.json, fall back to ~/clawd (Clawdbot default)
+        let workspace = read_workspace(&home)
             .unwrap_or_else(|| home.join("clawd"));
 
-        let memory_dir = workspace.join("memory");
+        let mut agents = discover_agents(&home);
+        if agents.is_empty() {
+            agents.push(AgentSessions {
+                name: DEFAULT_AGENT.to_string(),
+                sessions_dir: home.join(".clawdbot/agents/main/sessions"),
+            });
+        }
 
-        Self {
-            sessions_dir: home.join(".clawdbot/agents/main/sessions"),
-            db_path: memory_dir.join("profundo.sqlite"),
-            learnings_path: memory_dir.join("learnings.jsonl"),
-            cursor_path: memory_dir.join(".profundo-cursor"),
-            memory_dir,
+        Self::with_agents(agents, workspace.join("memory"))
+    }
+}
+
+/// Find every `agents/*/sessions` directory under the OpenClaw and Clawdbot roots
+fn discover_agents(home: &Path) -> Vec<AgentSessions> {
+    let mut agents: Vec<AgentSessions> = Vec::new();
+
+    for (root, _) in CONFIG_ROOTS {
+        let Ok(entries) = std::fs::read_dir(home.join(root).join("agents")) else {
+            continue;
+        };
+
+        let mut found: Vec<AgentSessions> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path().join("sessions"))
+            .filter(|dir| dir.is_dir())
+            .map(|dir| AgentSessions {
+                name: agent_from_sessions_dir(&dir),
+                sessions_dir: dir,
+            })
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for agent in found {
+            // Skip a layout that's symlinked to the other one
+            let canonical = agent.sessions_dir.canonicalize().ok();
+            let duplicate = agents
+                .iter()
+                .any(|a| a.sessions_dir.canonicalize().ok() == canonical);
+            if !duplicate {
+                agents.push(agent);
+            }
         }
     }
+
+    agents
 }
 
-/// Read workspace path from clawdbot.json
-fn read_clawdbot_workspace(home: &PathBuf) -> Option<PathBuf> {
-    let config_path = home.join(".clawdbot/clawdbot.json");
-    let content = std::fs::read_to_string(&config_path).ok()?;
-    let config: serde_json::Value = serde_json::from_str(&content).ok()?;
-
-    config
-        .get("agents")
-        .and_then(|a| a.get("defaults"))
-        .and_then(|d| d.get("workspace"))
-        .and_then(|w| w.as_str())
-        .map(PathBuf::from)
+/// Read workspace path from openclaw.json or clawdbot.json
+fn read_workspace(home: &Path) -> Option<PathBuf> {
+    CONFIG_ROOTS.iter().find_map(|(root, file)| {
+        let config_path = home.join(root).join(file);
+        let content = std::fs::read_to_string(&config_path).ok()?;
+        let config: serde_json::Value = serde_json::from_str(&content).ok()?;
+
+        config
+            .get("agents")
+            .and_then(|a| a.get("defaults"))
+            .and_then(|d| d.get("workspace"))
+            .and_then(|w| w.as_str())
+            .map(PathBuf::from)
+    })
+}
+
+/// Agent name for a session file: `<name>` in `.../agents/<name>/sessions/<id>.jsonl`
+pub fn agent_from_path(path: &Path) -> String {
+    path.parent()
+        .map(agent_from_sessions_dir)
+        .unwrap_or_else(|| DEFAULT_AGENT.to_string())
+}
+
+/// Agent name for a sessions directory: `<name>` in `.../agents/<name>/sessions`
+pub fn agent_from_sessions_dir(dir: &Path) -> String {
+    let agent_dir = dir.parent();
+    let agents_dir = agent_dir.and_then(|p| p.parent());
+
+    let is_agent_layout = dir.file_name().map(|n| n == "sessions").unwrap_or(false)
+        && agents_dir.and_then(|p| p.file_name()).map(|n| n == "agents").unwrap_or(false);
+
+    if is_agent_layout {
+        if let Some(name) = agent_dir.and_then(|p| p.file_name()).and_then(|n| n.to_str()) {
+            return name.to_string();
+        }
+    }
+
+    DEFAULT_AGENT.to_string()
 }
 
 impl Paths {
     /// Create paths with custom base directories
     pub fn with_bases(sessions_dir: PathBuf, memory_dir: PathBuf) -> Self {
-        Self {
+        let agent = AgentSessions {
+            name: agent_from_sessions_dir(&sessions_dir),
             sessions_dir,
+        };
+        Self::with_agents(vec![agent], memory_dir)
+    }
+
+    /// Create paths for a set of agent session directories
+    pub fn with_agents(agents: Vec<AgentSessions>, memory_dir: PathBuf) -> Self {
+        Self {
+            agents,
             db_path: memory_dir.join("profundo.sqlite"),
             learnings_path: memory_dir.join("learnings.jsonl"),
             cursor_path: memory_dir.join(".profundo-cursor"),
+            dead_letter_path: memory_dir.join("harvest-failed.jsonl"),
+            categories_path: memory_dir.join("harvest-categories.json"),
+            templates_dir: memory_dir.join(".profundo/templates"),
             memory_dir,
         }
     }
+
+    /// Session directories, optionally restricted to one agent
+    pub fn session_dirs(&self, agent: Option<&str>) -> Vec<&AgentSessions> {
+        self.agents
+            .iter()
+            .filter(|a| agent.map(|name| a.name == name).unwrap_or(true))
+            .collect()
+    }
+
+    /// Locate a session's JSONL file in any agent's directory
+    pub fn find_session_file(&self, session_id: &str) -> Option<PathBuf> {
+        self.agents
+            .iter()
+            .map(|a| a.sessions_dir.join(format!("{}.jsonl", session_id)))
+            .find(|p| p.exists())
+    }
 }
@@ -7,8 +7,10 @@ use chrono::NaiveDate;
 use clap::{Parser, Subcommand};
 use colored::Colorize;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
 use profundo::db::Database;
+use profundo::session::short_id;
 use profundo::Paths;
 
 #[derive(Parser)]
@@ -43,6 +45,10 @@ enum Commands {
         /// Overlap between chunks
         #[arg(long, default_value = "2")]
         overlap: usize,
+
+        /// Only this agent's sessions (default: all agents)
+        #[arg(long)]
+        agent: Option<String>,
     },
 
     /// Search memory for similar content
@@ -69,6 +75,118 @@ enum Commands {
         /// Expand query with LLM-generated synonyms/variants before searching
         #[arg(long)]
         expand: bool,
+
+        /// Only search this agent's memory (default: all agents)
+        #[arg(long)]
+        agent: Option<String>,
+    },
+
+    /// Answer a question from memory, citing the sessions it came from
+    Ask {
+        /// Question to answer
+        question: String,
+
+        /// Number of conversation chunks to retrieve
+        #[arg(short = 'n', long, default_value = "8")]
+        top_k: usize,
+
+        /// Approximate token budget for the retrieved context
+        #[arg(long, default_value = "6000")]
+        budget: usize,
+
+        /// Model to answer with
+        #[arg(long, default_value = "deepseek/deepseek-v3.2")]
+        model: String,
+
+        /// Expand query with LLM-generated synonyms/variants before searching
+        #[arg(long)]
+        expand: bool,
+
+        /// Print the raw context sent to the model
+        #[arg(long)]
+        show_context: bool,
+    },
+
+    /// Find sessions and chunks similar to a given session or chunk
+    Related {
+        /// Session id (or unique prefix) or chunk id
+        target: String,
+
+        /// Number of sessions and chunks to return
+        #[arg(short = 'n', long, default_value = "5")]
+        top_k: usize,
+
+        /// How to score session similarity
+        #[arg(long, value_enum, default_value = "centroid")]
+        scoring: profundo::related::SessionScoring,
+
+        /// Print results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Evaluate retrieval quality against labeled queries
+    Eval {
+        /// Labeled queries (JSONL: {"query", "relevant": [{"session_id", "turns": [first, last]}]})
+        labels: PathBuf,
+
+        /// Session corpus to index (default: sessions/ next to the labels file)
+        #[arg(long)]
+        corpus: Option<PathBuf>,
+
+        /// Cutoff for recall@k and nDCG@k
+        #[arg(short, long, default_value = "5")]
+        k: usize,
+
+        /// Use OpenRouter embeddings and query expansion instead of the offline hash embedder
+        #[arg(long)]
+        online: bool,
+
+        /// Minimum similarity threshold (default: 0.0 offline, 0.3 online)
+        #[arg(short, long)]
+        threshold: Option<f32>,
+
+        /// Print results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Merge near-duplicate facts and decisions across sessions
+    Consolidate {
+        /// Minimum similarity for two items to be merged
+        #[arg(short, long, default_value = "0.85")]
+        threshold: f32,
+
+        /// Chat model used to detect reversed decisions
+        #[arg(long, default_value = "deepseek/deepseek-v3.2")]
+        model: String,
+
+        /// Most contradiction checks per run
+        #[arg(long, default_value = "200")]
+        max_checks: usize,
+
+        /// Use the offline hash embedder and skip contradiction checks
+        #[arg(long)]
+        offline: bool,
+
+        /// List consolidated entries instead of rebuilding them
+        #[arg(long)]
+        list: bool,
+    },
+
+    /// Build a profile of the user from harvested facts
+    Profile {
+        /// Output file path (default: memory/profile.md)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Chat model that sorts facts and writes the profile
+        #[arg(long, default_value = "deepseek/deepseek-v3.2")]
+        model: String,
+
+        /// Rewrite every section, even ones whose facts haven't changed
+        #[arg(long)]
+        rebuild: bool,
     },
 
     /// Extract learnings from sessions
@@ -84,6 +202,101 @@ enum Commands {
         /// Minimum messages to process a session
         #[arg(long, default_value = "4")]
         min_messages: usize,
+
+        /// Only this agent's sessions (default: all agents)
+        #[arg(long)]
+        agent: Option<String>,
+
+        /// Model context window in tokens, used to split long sessions (default: per model)
+        #[arg(long)]
+        context_window: Option<usize>,
+
+        /// Categories file (default: harvest-categories.json in the memory dir)
+        #[arg(long)]
+        categories: Option<PathBuf>,
+
+        /// Defer sessions modified within this many minutes (0 harvests everything)
+        #[arg(long, default_value = "30")]
+        settle_minutes: i64,
+    },
+
+    /// List and close harvested action items
+    Todo {
+        #[command(subcommand)]
+        action: Option<TodoAction>,
+
+        /// Only items with this status (default: open)
+        #[arg(long, value_enum, conflicts_with = "all")]
+        status: Option<profundo::todo::ActionStatus>,
+
+        /// List items of every status
+        #[arg(long)]
+        all: bool,
+
+        /// Only items raised at least N days ago
+        #[arg(long)]
+        older_than: Option<i64>,
+
+        /// Only items raised at most N days ago
+        #[arg(long)]
+        newer_than: Option<i64>,
+
+        /// Only this agent's items (default: all agents)
+        #[arg(long)]
+        agent: Option<String>,
+
+        /// Print items as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show everything known about an entity (person, project, service, tool...)
+    Entity {
+        /// Entity name (or part of one); lists all entities if omitted
+        name: Option<String>,
+
+        /// Print the entity and its relations as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Export the entity graph
+    Graph {
+        /// Output format
+        #[arg(long, value_enum, default_value = "dot")]
+        format: profundo::graph::GraphFormat,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Show the conversation turns a decision, fact or action item came from
+    Trace {
+        /// The item's text (or part of it)
+        item: String,
+
+        /// Extra turns to show before and after
+        #[arg(short, long, default_value = "0")]
+        context: usize,
+
+        /// Only look in this session (id or prefix)
+        #[arg(long)]
+        session: Option<String>,
+    },
+
+    /// Render a session as a readable transcript, with its learnings first
+    Show {
+        /// Session id (or unique prefix)
+        session: String,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: profundo::transcript::TranscriptFormat,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 
     /// Show memory status
@@ -98,6 +311,10 @@ enum Commands {
         /// Only include sessions until this date (YYYY-MM-DD)
         #[arg(long)]
         until: Option<String>,
+
+        /// Only this agent's sessions (default: all agents)
+        #[arg(long)]
+        agent: Option<String>,
     },
 
     /// Search extracted learnings
@@ -108,48 +325,177 @@ enum Commands {
         /// Show last N entries
         #[arg(short = 'n', long, default_value = "10")]
         last: usize,
+
+        /// Import learnings from a JSONL file (newer versions replace stored ones)
+        #[arg(long, conflicts_with = "export")]
+        import: Option<PathBuf>,
+
+        /// Export all learnings to a JSONL file
+        #[arg(long)]
+        export: Option<PathBuf>,
     },
 
     /// Export learnings to markdown for Clawdbot indexing
     Export {
-        /// Output file path (default: memory/learnings.md)
+        /// Output path (default: memory/learnings.<ext>; memory/decisions/ for adr,
+        /// memory/decisions.md for adr-log, memory/vault/ for obsidian)
         #[arg(short, long)]
         output: Option<std::path::PathBuf>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: profundo::export::ExportFormat,
+
+        /// Only sessions on or after this date (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only sessions on or before this date (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Only sessions with this topic (repeatable)
+        #[arg(long = "topic")]
+        topics: Vec<String>,
+
+        /// Only this session, by id or prefix (repeatable)
+        #[arg(long = "session")]
+        sessions: Vec<String>,
+
+        /// Only this agent's sessions
+        #[arg(long)]
+        agent: Option<String>,
+
+        /// Only items of this kind: decisions, facts, actions or a custom category (repeatable)
+        #[arg(long = "category")]
+        categories: Vec<String>,
+
+        /// Write one export per day, week, month or topic into the output directory
+        #[arg(long, value_enum)]
+        split_by: Option<profundo::export::SplitBy>,
     },
 
     /// Write daily rollup to memory log (learnings + stats)
     Rollup {
         /// Date to rollup (YYYY-MM-DD, default: yesterday)
-        #[arg(long)]
+        #[arg(long, conflicts_with = "since")]
         date: Option<String>,
+
+        /// Backfill every day from this date (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Last day to backfill (YYYY-MM-DD, default: yesterday)
+        #[arg(long, requires = "since")]
+        until: Option<String>,
+
+        /// When backfilling, also write days with no sessions or learnings
+        #[arg(long, requires = "since")]
+        include_empty: bool,
+
+        /// Only this agent's sessions (default: all agents)
+        #[arg(long)]
+        agent: Option<String>,
+
+        /// Show what would change instead of writing (exits 1 if out of date)
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Write a weekly or monthly digest (default: last week)
+    Digest {
+        /// ISO week to digest (YYYY-Www, e.g. 2026-W41)
+        #[arg(long, conflicts_with = "month")]
+        week: Option<String>,
+
+        /// Month to digest (YYYY-MM)
+        #[arg(long)]
+        month: Option<String>,
+
+        /// Only this agent's sessions (default: all agents)
+        #[arg(long)]
+        agent: Option<String>,
+    },
+
+    /// List the rollup and export templates, or copy the built-ins to edit
+    Templates {
+        /// Write the built-in templates to memory/.profundo/templates/ (existing files are kept)
+        #[arg(long)]
+        init: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum TodoAction {
+    /// Mark an action item done
+    Done {
+        /// Item id (or unique prefix)
+        id: String,
+
+        /// Why or how it was closed
+        #[arg(long)]
+        note: Option<String>,
+    },
+
+    /// Mark an action item as no longer going to happen
+    Drop {
+        /// Item id (or unique prefix)
+        id: String,
+
+        /// Why it was dropped
+        #[arg(long)]
+        note: Option<String>,
+    },
+
+    /// Reopen a done or dropped action item
+    Reopen {
+        /// Item id (or unique prefix)
+        id: String,
     },
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     let cli = Cli::parse();
 
-    // Set up paths
+    // Set up paths (discovered agents unless a sessions dir is given)
     let paths = match (&cli.sessions_dir, &cli.memory_dir) {
         (Some(s), Some(m)) => Paths::with_bases(s.clone(), m.clone()),
-        _ => Paths::default(),
+        (Some(s), None) => Paths::with_bases(s.clone(), Paths::default().memory_dir),
+        (None, Some(m)) => Paths::with_agents(Paths::default().agents, m.clone()),
+        (None, None) => Paths::default(),
     };
 
+    // Move learnings.jsonl into the database before any command reads it
+    if paths.learnings_path.exists() {
+        let mut db = Database::open(&paths.db_path)?;
+        if let Some(stats) = db.migrate_learnings(&paths.learnings_path)? {
+            println!(
+                "{} Moved {} learnings from {} into the database",
+                "→".blue(),
+                stats.imported.to_string().cyan(),
+                paths.learnings_path.display()
+            );
+            if stats.skipped > 0 {
+                println!("  {} invalid lines skipped", stats.skipped.to_string().yellow());
+            }
+        }
+    }
+
     match cli.command {
         Commands::Embed {
             full,
             chunk_size,
             overlap,
+            agent,
         } => {
-            println!(
-                "\n{} Profundo Embed\n",
-                "🌊".to_string()
-            );
+            println!("\n🌊 Profundo Embed\n");
 
             let config = profundo::embed::EmbedConfig {
                 chunk_size,
                 overlap,
                 force_reprocess: full,
+                agent,
             };
 
             profundo::embed::run(&paths, config).await?;
@@ -162,6 +508,7 @@ async fn main() -> Result<()> {
             full,
             context,
             expand,
+            agent,
         } => {
             let config = profundo::recall::RecallConfig {
                 top_k,
@@ -170,21 +517,184 @@ async fn main() -> Result<()> {
                 show_full: full,
                 context_turns: context,
                 expand,
+                agent,
+                ..Default::default()
             };
             let display_config = config.clone();
             let results = profundo::recall::search(&paths, &query, config).await?;
             profundo::recall::display_results(&paths, &paths.learnings_path, &results, &query, &display_config);
         }
 
+        Commands::Ask {
+            question,
+            top_k,
+            budget,
+            model,
+            expand,
+            show_context,
+        } => {
+            let config = profundo::ask::AskConfig {
+                top_k,
+                token_budget: budget,
+                model,
+                expand,
+                ..Default::default()
+            };
+
+            let pack = profundo::ask::retrieve(&paths, &question, &config).await?;
+            if show_context {
+                profundo::ask::display_context(&pack);
+            }
+
+            if pack.sources.is_empty() {
+                println!(
+                    "{} Nothing in memory matches: {}",
+                    "→".yellow(),
+                    question.italic()
+                );
+            } else {
+                let client = profundo::openrouter::OpenRouterClient::from_env()?;
+                let answer = profundo::ask::answer(&client, &question, &pack, &config.model).await?;
+                profundo::ask::display_answer(&answer, &pack);
+            }
+        }
+
+        Commands::Related {
+            target,
+            top_k,
+            scoring,
+            json,
+        } => {
+            let config = profundo::related::RelatedConfig { top_k, scoring };
+            let results = profundo::related::find(&paths, &target, config)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&results)?);
+            } else {
+                profundo::related::display(&results);
+            }
+        }
+
+        Commands::Eval {
+            labels,
+            corpus,
+            k,
+            online,
+            threshold,
+            json,
+        } => {
+            use profundo::openrouter::OpenRouterClient;
+
+            let corpus = corpus.unwrap_or_else(|| {
+                labels
+                    .parent()
+                    .unwrap_or_else(|| std::path::Path::new("."))
+                    .join("sessions")
+            });
+            let cases = profundo::eval::load_cases(&labels)?;
+
+            // Hash-embedder similarities aren't calibrated, so don't threshold them by default
+            let default_threshold = if online { 0.3 } else { 0.0 };
+            let variants = profundo::eval::default_variants(k, threshold.unwrap_or(default_threshold));
+
+            let reports = if online {
+                let client = OpenRouterClient::from_env()?;
+                profundo::eval::run(&corpus, &cases, &variants, &client, Some(&client)).await?
+            } else {
+                let embedder = profundo::embed::HashEmbedder::default();
+                profundo::eval::run(&corpus, &cases, &variants, &embedder, None::<&OpenRouterClient>)
+                    .await?
+            };
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&reports)?);
+            } else {
+                profundo::eval::display(&reports, k);
+            }
+        }
+
+        Commands::Consolidate {
+            threshold,
+            model,
+            max_checks,
+            offline,
+            list,
+        } => {
+            use profundo::openrouter::OpenRouterClient;
+
+            let mut db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+
+            if list {
+                let items = db.load_canonical_items()?;
+                if items.is_empty() {
+                    println!(
+                        "{} Nothing consolidated yet. Run {} first.",
+                        "→".yellow(),
+                        "profundo consolidate".cyan()
+                    );
+                } else {
+                    profundo::consolidate::display_items(&items);
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            println!("\n🌊 Profundo Consolidate\n");
+
+            let config = profundo::consolidate::ConsolidateConfig {
+                threshold,
+                max_checks,
+                model,
+                ..Default::default()
+            };
+
+            let stats = if offline {
+                let embedder = profundo::embed::HashEmbedder::default();
+                profundo::consolidate::run(&mut db, &embedder, None::<&OpenRouterClient>, &config).await?
+            } else {
+                let client = OpenRouterClient::from_env()?;
+                profundo::consolidate::run(&mut db, &client, Some(&client), &config).await?
+            };
+
+            profundo::consolidate::display_stats(&stats);
+        }
+
+        Commands::Profile { output, model, rebuild } => {
+            use profundo::openrouter::OpenRouterClient;
+
+            println!("\n🌊 Profundo Profile\n");
+
+            let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+            if db.learnings_count()? == 0 {
+                println!(
+                    "{} No learnings yet. Run {} first.",
+                    "→".yellow(),
+                    "profundo harvest".cyan()
+                );
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let output_path = output.unwrap_or_else(|| paths.memory_dir.join("profile.md"));
+            let config = profundo::profile::ProfileConfig {
+                model,
+                rebuild,
+                ..Default::default()
+            };
+
+            let client = OpenRouterClient::from_env()?;
+            let stats = profundo::profile::run(&db, &client, &config, &output_path).await?;
+            profundo::profile::display_stats(&stats, &output_path);
+        }
+
         Commands::Harvest {
             since,
             model,
             min_messages,
+            agent,
+            context_window,
+            categories,
+            settle_minutes,
         } => {
-            println!(
-                "\n{} Profundo Harvest\n",
-                "🌊".to_string()
-            );
+            println!("\n🌊 Profundo Harvest\n");
 
             let since_date = since
                 .map(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d"))
@@ -195,21 +705,213 @@ async fn main() -> Result<()> {
                 since: since_date,
                 model,
                 min_messages,
+                agent,
+                context_window,
+                categories: profundo::categories::CategorySet::load(
+                    categories.as_deref().unwrap_or(&paths.categories_path),
+                )?,
+                settle_minutes,
             };
 
             profundo::harvest::run(&paths, config).await?;
         }
 
+        Commands::Todo {
+            action,
+            status,
+            all,
+            older_than,
+            newer_than,
+            agent,
+            json,
+        } => {
+            use profundo::todo::{ActionStatus, TodoFilter};
+
+            let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+
+            if let Some(action) = action {
+                let items = db.load_action_items(None)?;
+                let (id, status, note) = match &action {
+                    TodoAction::Done { id, note } => (id, ActionStatus::Done, note.as_deref()),
+                    TodoAction::Drop { id, note } => (id, ActionStatus::Dropped, note.as_deref()),
+                    TodoAction::Reopen { id } => (id, ActionStatus::Open, None),
+                };
+                let item = profundo::todo::resolve(&items, id)?;
+                db.set_action_status(&item.id, status, None, note)?;
+
+                println!(
+                    "{} Marked {} {}: {}",
+                    "✓".green(),
+                    item.short_id().dimmed(),
+                    status.as_str().cyan(),
+                    item.text
+                );
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let filter = TodoFilter {
+                status: if all { None } else { Some(status.unwrap_or(ActionStatus::Open)) },
+                agent,
+                older_than,
+                newer_than,
+            };
+            let items: Vec<_> = db
+                .load_action_items(None)?
+                .into_iter()
+                .filter(|i| filter.matches(i))
+                .collect();
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&items)?);
+            } else if items.is_empty() {
+                println!("{} No matching action items", "→".yellow());
+            } else {
+                println!(
+                    "\n{} {} action items\n",
+                    "→".blue(),
+                    items.len().to_string().cyan()
+                );
+                profundo::todo::display(&items);
+            }
+        }
+
+        Commands::Entity { name, json } => {
+            let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+            let graph = db.load_graph()?;
+
+            if graph.entities.is_empty() {
+                println!(
+                    "{} No entities yet. Run {} to extract them.",
+                    "→".yellow(),
+                    "profundo harvest".cyan()
+                );
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let Some(name) = name else {
+                println!("\n{} {} entities\n", "→".blue(), graph.entities.len().to_string().cyan());
+                profundo::graph::display_entities(&graph);
+                return Ok(ExitCode::SUCCESS);
+            };
+
+            match graph.find(&name).as_slice() {
+                [] => println!("{} No entity matches '{}'", "→".yellow(), name),
+                [entity] if json => {
+                    let key = profundo::graph::entity_key(&entity.name);
+                    let relations: Vec<_> = graph
+                        .relations
+                        .iter()
+                        .filter(|r| {
+                            profundo::graph::entity_key(&r.subject) == key
+                                || profundo::graph::entity_key(&r.object) == key
+                        })
+                        .collect();
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&serde_json::json!({
+                            "entity": entity,
+                            "relations": relations,
+                        }))?
+                    );
+                }
+                [entity] => {
+                    let learnings = db.load_learnings(None)?;
+                    profundo::graph::display_entity(&graph, entity, &learnings);
+                }
+                matches => {
+                    println!(
+                        "{} {} entities match '{}':\n",
+                        "→".yellow(),
+                        matches.len().to_string().cyan(),
+                        name
+                    );
+                    let subgraph = profundo::graph::Graph {
+                        entities: matches.iter().map(|e| (*e).clone()).collect(),
+                        relations: Vec::new(),
+                    };
+                    profundo::graph::display_entities(&subgraph);
+                }
+            }
+        }
+
+        Commands::Graph { format, output } => {
+            use profundo::graph::GraphFormat;
+
+            let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+            let graph = db.load_graph()?;
+            let rendered = match format {
+                GraphFormat::Dot => graph.to_dot(),
+                GraphFormat::Json => graph.to_json()?,
+            };
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, rendered)?;
+                    println!(
+                        "{} Wrote {} entities and {} relations to {}",
+                        "✓".green(),
+                        graph.entities.len().to_string().cyan(),
+                        graph.relations.len().to_string().cyan(),
+                        path.display().to_string().dimmed()
+                    );
+                }
+                None => print!("{}", rendered),
+            }
+        }
+
+        Commands::Show { session, format, output } => {
+            let transcript = profundo::transcript::render(&paths, &session, format)?;
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, &transcript.content)?;
+                    println!(
+                        "{} Wrote {} messages of session {} to {}",
+                        "✓".green(),
+                        transcript.messages.to_string().cyan(),
+                        transcript.session_id.cyan(),
+                        path.display().to_string().dimmed()
+                    );
+                }
+                None => print!("{}", transcript.content),
+            }
+        }
+
+        Commands::Trace { item, context, session } => {
+            let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+            let learnings = db.load_learnings(None)?;
+
+            match profundo::trace::find(&learnings, &item, session.as_deref()).as_slice() {
+                [] => println!("{} No decision, fact or action item matches '{}'", "→".yellow(), item),
+                [found] => profundo::trace::display(&paths, found, context)?,
+                matches => {
+                    println!(
+                        "{} {} items match '{}':\n",
+                        "→".yellow(),
+                        matches.len().to_string().cyan(),
+                        item
+                    );
+                    profundo::trace::display_matches(matches);
+                    println!(
+                        "\n{} Use more of the item's text, or {} to pick a session",
+                        "→".dimmed(),
+                        "--session".cyan()
+                    );
+                }
+            }
+        }
+
         Commands::Status => {
-            println!(
-                "\n{} Profundo Status\n",
-                "🌊".to_string()
-            );
+            println!("\n🌊 Profundo Status\n");
 
             show_status(&paths)?;
         }
 
-        Commands::Stats { since, until } => {
+        Commands::Stats {
+            since,
+            until,
+            agent,
+        } => {
             let since_date = since
                 .map(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d"))
                 .transpose()
@@ -223,27 +925,77 @@ async fn main() -> Result<()> {
             let config = profundo::stats::StatsConfig {
                 since: since_date,
                 until: until_date,
+                agent,
             };
 
             let stats = profundo::stats::collect(&paths, config)?;
             profundo::stats::display(&stats);
         }
 
-        Commands::Learnings { query, last } => {
-            show_learnings(&paths, query.as_deref(), last)?;
+        Commands::Learnings {
+            query,
+            last,
+            import,
+            export,
+        } => {
+            if let Some(path) = import {
+                let mut db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+                let stats = db.import_learnings_jsonl(&path)?;
+                println!(
+                    "{} Imported {} learnings from {}",
+                    "✓".green(),
+                    stats.imported.to_string().cyan(),
+                    path.display().to_string().dimmed()
+                );
+                if stats.skipped > 0 {
+                    println!("  {} invalid lines skipped", stats.skipped.to_string().yellow());
+                }
+            } else if let Some(path) = export {
+                let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+                let count = db.export_learnings_jsonl(&path)?;
+                println!(
+                    "{} Exported {} learnings to {}",
+                    "✓".green(),
+                    count.to_string().cyan(),
+                    path.display().to_string().dimmed()
+                );
+            } else {
+                show_learnings(&paths, query.as_deref(), last)?;
+            }
         }
 
-        Commands::Export { output } => {
-            println!(
-                "\n{} Profundo Export\n",
-                "🌊".to_string()
-            );
+        Commands::Export {
+            output,
+            format,
+            since,
+            until,
+            topics,
+            sessions,
+            agent,
+            categories,
+            split_by,
+        } => {
+            println!("\n🌊 Profundo Export\n");
 
-            let output_path = output.unwrap_or_else(|| paths.memory_dir.join("learnings.md"));
+            let parse_date = |d: &str| {
+                NaiveDate::parse_from_str(d, "%Y-%m-%d").map_err(|e| anyhow::anyhow!("Invalid date format: {}", e))
+            };
+            let filter = profundo::export::ExportFilter {
+                since: since.as_deref().map(parse_date).transpose()?,
+                until: until.as_deref().map(parse_date).transpose()?,
+                agent,
+                sessions,
+                topics,
+                categories,
+            };
 
-            let stats = profundo::export::export_to_markdown(&paths, &output_path)?;
+            let (output_path, stats) = profundo::export::run(&paths, format, output, &filter, split_by)?;
 
-            if stats.sessions == 0 {
+            if stats.sessions == 0 && !stats.excluded.is_empty() {
+                println!("{} No learnings match the filters", "→".yellow());
+            } else if stats.sessions == 0 && matches!(format, profundo::export::ExportFormat::Todotxt | profundo::export::ExportFormat::Ics) {
+                println!("{} No action items to export", "→".yellow());
+            } else if stats.sessions == 0 {
                 println!(
                     "{} No learnings to export. Run {} first.",
                     "→".yellow(),
@@ -256,45 +1008,186 @@ async fn main() -> Result<()> {
                     stats.sessions.to_string().cyan(),
                     output_path.display().to_string().dimmed()
                 );
-                println!(
-                    "  {} decisions, {} facts, {} action items",
-                    stats.decisions.to_string().cyan(),
-                    stats.facts.to_string().cyan(),
-                    stats.actions.to_string().cyan()
-                );
+                if stats.files > 0 {
+                    println!("  {} files, one per {}", stats.files.to_string().cyan(), split_by.map(|s| s.as_str()).unwrap_or(""));
+                }
+                let counts: Vec<String> = [
+                    (stats.decisions, "decisions"),
+                    (stats.facts, "facts"),
+                    (stats.actions, "action items"),
+                ]
+                .iter()
+                .filter(|(n, _)| *n > 0)
+                .map(|(n, what)| format!("{} {}", n.to_string().cyan(), what))
+                .collect();
+                if !counts.is_empty() {
+                    println!("  {}", counts.join(", "));
+                }
+                if stats.superseded > 0 {
+                    println!("  {} superseded by later decisions", stats.superseded.to_string().yellow());
+                }
+                if stats.unchanged > 0 {
+                    println!("  {} files already up to date", stats.unchanged.to_string().dimmed());
+                }
+            }
+            for (filter, excluded) in &stats.excluded {
+                println!("  {} items excluded by --{}", excluded.to_string().yellow(), filter);
             }
         }
 
-        Commands::Rollup { date } => {
+        Commands::Rollup {
+            date,
+            since,
+            until,
+            include_empty,
+            agent,
+            check,
+        } => return rollup(&paths, date, since, until, include_empty, agent, check),
+
+        Commands::Digest { week, month, agent } => {
+            use profundo::digest::Period;
+
+            println!("\n🌊 Profundo Digest\n");
+
+            let period = match (week, month) {
+                (Some(week), _) => Period::week(&week)?,
+                (None, Some(month)) => Period::month(&month)?,
+                // Default to last week, like rollup defaults to yesterday
+                (None, None) => Period::week_of(chrono::Utc::now().date_naive()).previous(),
+            };
+
+            let stats = profundo::digest::write_digest(&paths, &period, agent.as_deref())?;
+
             println!(
-                "\n{} Profundo Rollup\n",
-                "🌊".to_string()
+                "{} Wrote digest for {} to {}",
+                "✓".green(),
+                period.label().cyan(),
+                stats.path.display().to_string().dimmed()
             );
+            println!(
+                "  {} sessions, {} decisions, {} action items open, {} completed",
+                stats.sessions.to_string().cyan(),
+                stats.decisions.to_string().cyan(),
+                stats.open.to_string().cyan(),
+                stats.completed.to_string().cyan()
+            );
+        }
 
-            // Default to yesterday (for morning review of previous day)
-            let target_date = match date {
-                Some(d) => NaiveDate::parse_from_str(&d, "%Y-%m-%d")
-                    .map_err(|e| anyhow::anyhow!("Invalid date format: {}", e))?,
-                None => chrono::Utc::now().date_naive() - chrono::Duration::days(1),
-            };
+        Commands::Templates { init } => {
+            use profundo::template;
 
-            let stats = profundo::export::write_rollup(&paths, target_date)?;
+            println!("\n🌊 Profundo Templates\n");
+
+            if init {
+                let written = template::init(&paths)?;
+                println!("{} Wrote {} built-in templates", "✓".green(), written.len().to_string().cyan());
+            }
+
+            // Loading checks the user's templates for syntax errors
+            template::Templates::load(&paths)?;
+            let custom = template::user_templates(&paths.templates_dir)?;
+
+            println!("{}", paths.templates_dir.display().to_string().dimmed());
+            for (name, _) in template::BUILTIN {
+                let source = if custom.iter().any(|(c, _)| c == name) { "custom".green() } else { "built-in".dimmed() };
+                println!("  {:<14} {}", name, source);
+            }
+            for (name, _) in custom.iter().filter(|(c, _)| !template::BUILTIN.iter().any(|(b, _)| b == c)) {
+                println!("  {:<14} {}", name, "custom (include only)".green());
+            }
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
 
+/// Write daily logs; with `check`, fail if any would change
+fn rollup(
+    paths: &Paths,
+    date: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    include_empty: bool,
+    agent: Option<String>,
+    check: bool,
+) -> Result<ExitCode> {
+    use profundo::export::RollupOptions;
+
+    println!("\n🌊 Profundo Rollup\n");
+
+    let parse_date = |d: &str| {
+        NaiveDate::parse_from_str(d, "%Y-%m-%d").map_err(|e| anyhow::anyhow!("Invalid date format: {}", e))
+    };
+    // Default to yesterday (for morning review of previous day)
+    let yesterday = chrono::Utc::now().date_naive() - chrono::Duration::days(1);
+    let options = RollupOptions {
+        agent: agent.as_deref(),
+        check,
+        include_empty,
+    };
+
+    let rollups = match since {
+        Some(since) => {
+            let since = parse_date(&since)?;
+            let until = until.as_deref().map(parse_date).transpose()?.unwrap_or(yesterday);
+            if until < since {
+                anyhow::bail!("--until {} is before --since {}", until, since);
+            }
+            profundo::export::write_rollups(paths, since, until, options)?
+        }
+        None => {
+            let date = date.as_deref().map(parse_date).transpose()?.unwrap_or(yesterday);
+            vec![profundo::export::write_rollup(paths, date, options.agent, check)?]
+        }
+    };
+
+    let mut out_of_date = 0;
+    for stats in &rollups {
+        let path = stats.path.display().to_string();
+        if let Some(diff) = &stats.diff {
+            for line in diff.lines() {
+                if line.starts_with("+++") || line.starts_with("---") {
+                    println!("{}", line.bold());
+                } else if line.starts_with('+') {
+                    println!("{}", line.green());
+                } else if line.starts_with('-') {
+                    println!("{}", line.red());
+                } else if line.starts_with("@@") {
+                    println!("{}", line.cyan());
+                } else {
+                    println!("{}", line);
+                }
+            }
+            println!();
+            out_of_date += 1;
+        } else if !stats.changed {
             println!(
-                "{} Wrote rollup for {} to {}",
+                "{} {} is up to date ({} learnings, {} sessions)",
                 "✓".green(),
-                target_date.to_string().cyan(),
-                stats.path.display().to_string().dimmed()
+                path.dimmed(),
+                stats.sessions.to_string().cyan(),
+                stats.stats_sessions.to_string().cyan()
             );
+        } else {
             println!(
-                "  {} learnings from {} sessions",
+                "{} Wrote {} ({} learnings, {} sessions)",
+                "✓".green(),
+                path.dimmed(),
                 stats.sessions.to_string().cyan(),
                 stats.stats_sessions.to_string().cyan()
             );
         }
     }
 
-    Ok(())
+    if rollups.is_empty() {
+        println!("{} No activity in that range", "→".yellow());
+    }
+    if out_of_date > 0 {
+        println!("{} {} daily logs would change", "→".yellow(), out_of_date.to_string().yellow());
+        return Ok(ExitCode::FAILURE);
+    }
+
+    Ok(ExitCode::SUCCESS)
 }
 
 fn show_status(paths: &Paths) -> Result<()> {
@@ -324,17 +1217,32 @@ fn show_status(paths: &Paths) -> Result<()> {
     println!();
 
     // Learnings stats
-    if paths.learnings_path.exists() {
-        let count = std::fs::read_to_string(&paths.learnings_path)?
-            .lines()
-            .count();
+    let (learnings_count, open_actions) = if paths.db_path.exists() || paths.learnings_path.exists() {
+        let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+        let open = db.load_action_items(Some(profundo::todo::ActionStatus::Open))?.len();
+        (db.learnings_count()?, open)
+    } else {
+        (0, 0)
+    };
 
+    if learnings_count > 0 {
         println!("{}", "Learnings".bold());
-        println!("  {} entries", count.to_string().cyan());
+        println!("  {} entries", learnings_count.to_string().cyan());
+        if open_actions > 0 {
+            println!("  {} open action items", open_actions.to_string().cyan());
+        }
         println!(
             "  Path: {}",
-            paths.learnings_path.display().to_string().dimmed()
+            paths.db_path.display().to_string().dimmed()
         );
+
+        let failed = profundo::harvest::load_dead_letters(&paths.dead_letter_path)?;
+        if !failed.is_empty() {
+            println!(
+                "  {} sessions failed to harvest (retried on next run)",
+                failed.len().to_string().yellow()
+            );
+        }
     } else {
         println!(
             "{} No learnings yet. Run {} to create.",
@@ -345,9 +1253,20 @@ fn show_status(paths: &Paths) -> Result<()> {
 
     println!();
 
-    // Sessions directory
-    if paths.sessions_dir.exists() {
-        let session_count = std::fs::read_dir(&paths.sessions_dir)?
+    // Session directories, one per agent
+    println!("{}", "Session Logs".bold());
+    for agent in &paths.agents {
+        if !agent.sessions_dir.exists() {
+            println!(
+                "  {} {}: directory not found: {}",
+                "✗".red(),
+                agent.name.cyan(),
+                agent.sessions_dir.display()
+            );
+            continue;
+        }
+
+        let session_count = std::fs::read_dir(&agent.sessions_dir)?
             .filter_map(|e| e.ok())
             .filter(|e| {
                 e.path()
@@ -357,27 +1276,21 @@ fn show_status(paths: &Paths) -> Result<()> {
             })
             .count();
 
-        let total_size: u64 = std::fs::read_dir(&paths.sessions_dir)?
+        let total_size: u64 = std::fs::read_dir(&agent.sessions_dir)?
             .filter_map(|e| e.ok())
             .filter_map(|e| e.metadata().ok())
             .map(|m| m.len())
             .sum();
 
-        println!("{}", "Session Logs".bold());
         println!(
-            "  {} sessions ({:.1} MB)",
+            "  {}: {} sessions ({:.1} MB)",
+            agent.name.cyan(),
             session_count.to_string().cyan(),
             total_size as f64 / 1_000_000.0
         );
         println!(
-            "  Path: {}",
-            paths.sessions_dir.display().to_string().dimmed()
-        );
-    } else {
-        println!(
-            "{} Sessions directory not found: {}",
-            "✗".red(),
-            paths.sessions_dir.display()
+            "    Path: {}",
+            agent.sessions_dir.display().to_string().dimmed()
         );
     }
 
@@ -385,10 +1298,10 @@ fn show_status(paths: &Paths) -> Result<()> {
 }
 
 fn show_learnings(paths: &Paths, query: Option<&str>, last: usize) -> Result<()> {
-    use profundo::harvest::Learning;
-    use std::io::BufRead;
+    let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+    let mut learnings = db.load_learnings(None)?;
 
-    if !paths.learnings_path.exists() {
+    if learnings.is_empty() {
         println!(
             "{} No learnings yet. Run {} to create.",
             "→".yellow(),
@@ -397,15 +1310,6 @@ fn show_learnings(paths: &Paths, query: Option<&str>, last: usize) -> Result<()>
         return Ok(());
     }
 
-    let file = std::fs::File::open(&paths.learnings_path)?;
-    let reader = std::io::BufReader::new(file);
-
-    let mut learnings: Vec<Learning> = reader
-        .lines()
-        .filter_map(|l| l.ok())
-        .filter_map(|l| serde_json::from_str(&l).ok())
-        .collect();
-
     // Filter by query if provided
     if let Some(q) = query {
         let q_lower = q.to_lowercase();
@@ -421,6 +1325,10 @@ fn show_learnings(paths: &Paths, query: Option<&str>, last: usize) -> Result<()>
                 || l.action_items
                     .iter()
                     .any(|a| a.to_lowercase().contains(&q_lower))
+                || l.categories
+                    .values()
+                    .flatten()
+                    .any(|i| i.to_lowercase().contains(&q_lower))
         });
     }
 
@@ -444,43 +1352,58 @@ fn show_learnings(paths: &Paths, query: Option<&str>, last: usize) -> Result<()>
         query.map(|q| format!(" matching '{}'", q)).unwrap_or_default()
     );
 
+    let terms = query.map(profundo::recall::query_terms).unwrap_or_default();
+    let hl = |text: &str| profundo::recall::highlight(text, &terms);
+
     for learning in learnings {
         println!(
             "{} {} [{}]",
             "●".cyan(),
             learning.date.bold(),
-            if learning.session_id.len() >= 8 { &learning.session_id[..8] } else { &learning.session_id }.dimmed()
+            short_id(&learning.session_id).dimmed()
         );
 
         if !learning.topics.is_empty() {
             println!(
                 "  Topics: {}",
-                learning.topics.join(", ").italic()
+                hl(&learning.topics.join(", ")).italic()
             );
         }
 
         if !learning.decisions.is_empty() {
             println!("  Decisions:");
             for d in &learning.decisions {
-                println!("    • {}", d);
+                println!("    • {}", hl(d));
             }
         }
 
         if !learning.facts_learned.is_empty() {
             println!("  Facts:");
             for f in &learning.facts_learned {
-                println!("    • {}", f);
+                println!("    • {}", hl(f));
             }
         }
 
         if !learning.action_items.is_empty() {
             println!("  Actions:");
             for a in &learning.action_items {
-                println!("    • {}", a);
+                println!("    • {}", hl(a));
             }
         }
 
-        println!("  Summary: {}", learning.summary.dimmed());
+        for (name, items) in learning.categories.iter().filter(|(_, items)| !items.is_empty()) {
+            println!("  {}:", profundo::categories::label(name));
+            for item in items {
+                println!("    • {}", hl(item));
+            }
+        }
+
+        if terms.is_empty() {
+            println!("  Summary: {}", learning.summary.dimmed());
+        } else {
+            let summary = profundo::recall::snippet_around_terms(&learning.summary, &terms, 300);
+            println!("  Summary: {}", profundo::recall::render_highlights(&summary));
+        }
         println!();
     }
 
@@ -0,0 +1,329 @@
+//! Obsidian - learnings as a linked vault
+//!
+//! Writes one note per session, one per topic and one per day, linked with
+//! `[[wikilinks]]`:
+//!
+//! ```text
+//! vault/
+//! ├── Sessions/2026-10-06 a1b2c3d4.md   # frontmatter, summary, decisions, facts, actions
+//! ├── Topics/billing.md                 # sessions about the topic, related topics
+//! └── Daily/2026-10-06.md               # that day's sessions and usage
+//! ```
+//!
+//! Generated text sits between the `export::GENERATED_BEGIN`/`GENERATED_END`
+//! markers and is the only part a rerun replaces. Frontmatter carries a
+//! `profundo` hash of its generated fields; once a user edits the
+//! frontmatter the hash no longer matches and it is left alone. Notes whose
+//! content would not change are not rewritten.
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::categories::{self, CategorySet};
+use crate::embed::fnv1a;
+use crate::export::{self, ExportScope, ExportStats, Exporter};
+use crate::harvest::Learning;
+use crate::session::{short_id, Session};
+use crate::stats::{self, StatsConfig};
+use crate::todo::{self, ActionItem, ActionStatus};
+use crate::Paths;
+
+/// Most related topics listed on a topic note
+const MAX_RELATED_TOPICS: usize = 10;
+
+/// Vault export
+pub struct ObsidianExporter;
+
+impl Exporter for ObsidianExporter {
+    fn default_path(&self, paths: &Paths) -> PathBuf {
+        paths.memory_dir.join("vault")
+    }
+
+    fn export(&self, paths: &Paths, scope: &ExportScope, output: &Path) -> Result<ExportStats> {
+        let mut learnings = scope.learnings.to_vec();
+        if learnings.is_empty() {
+            return Ok(ExportStats::default());
+        }
+        learnings.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.session_id.cmp(&b.session_id)));
+
+        let categories = CategorySet::load(&paths.categories_path)?;
+        let action_items = export::load_action_items(paths)?;
+        let usage = stats::collect(paths, StatsConfig::default())?;
+
+        let mut vault = Vault {
+            root: output.to_path_buf(),
+            unchanged: 0,
+        };
+
+        // Topics by key, with the first spelling seen as the note name
+        let mut topics: BTreeMap<String, (String, Vec<&Learning>)> = BTreeMap::new();
+        for learning in &learnings {
+            for topic in learning.topics.iter().filter(|t| !t.trim().is_empty()) {
+                let entry = topics
+                    .entry(topic_key(topic))
+                    .or_insert_with(|| (note_name(topic), Vec::new()));
+                if !entry.1.iter().any(|l| l.session_id == learning.session_id) {
+                    entry.1.push(learning);
+                }
+            }
+        }
+        let topic_link = |topic: &str| {
+            topics
+                .get(&topic_key(topic))
+                .map(|(name, _)| format!("[[{}]]", name))
+                .unwrap_or_else(|| topic.to_string())
+        };
+
+        for learning in &learnings {
+            let session = paths
+                .find_session_file(&learning.session_id)
+                .and_then(|path| Session::from_file(&path).ok());
+            let (frontmatter, body) = session_note(learning, session.as_ref(), &categories, &action_items, &topic_link);
+            vault.write(&format!("Sessions/{}.md", session_note_name(learning)), &frontmatter, &body)?;
+        }
+
+        for (name, members) in topics.values() {
+            let (frontmatter, body) = topic_note(name, members, &topic_link);
+            vault.write(&format!("Topics/{}.md", name), &frontmatter, &body)?;
+        }
+
+        let mut by_date: BTreeMap<&str, Vec<&Learning>> = BTreeMap::new();
+        for learning in &learnings {
+            by_date.entry(learning.date.as_str()).or_default().push(learning);
+        }
+        for (date, day_learnings) in &by_date {
+            let day_usage = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| usage.by_date.get(&d));
+            let mut frontmatter = format!("date: {}\ntags: [profundo/daily]\nsessions: {}\n", date, day_learnings.len());
+            let mut body = String::from("## Profundo\n\n");
+            if let Some(day_usage) = day_usage {
+                frontmatter.push_str(&format!("cost: {:.4}\n", day_usage.total_cost));
+                body.push_str(&format!("{}\n\n", export::format_stats_summary(day_usage)));
+            }
+            for learning in day_learnings {
+                body.push_str(&format!("- [[{}]] {}\n", session_note_name(learning), learning.summary));
+            }
+            vault.write(&format!("Daily/{}.md", date), &frontmatter, &body)?;
+        }
+
+        Ok(ExportStats {
+            unchanged: vault.unchanged,
+            ..ExportStats::count(&learnings)
+        })
+    }
+}
+
+/// Writes notes, keeping what users changed outside the generated parts
+struct Vault {
+    root: PathBuf,
+    /// Notes left as they were
+    unchanged: usize,
+}
+
+impl Vault {
+    /// Create or update a note. `frontmatter` is YAML lines without the `---`
+    /// fences; `body` goes in the generated block.
+    fn write(&mut self, relative: &str, frontmatter: &str, body: &str) -> Result<()> {
+        let path = self.root.join(relative);
+        let generated_frontmatter = format!("---\n{}profundo: {:016x}\n---\n", frontmatter, fnv1a(frontmatter.as_bytes()));
+        let block = export::generated_block(body);
+
+        let content = match fs::read_to_string(&path) {
+            Err(_) => format!("{}\n{}", generated_frontmatter, block),
+            Ok(existing) => {
+                let (frontmatter, rest) = match split_frontmatter(&existing) {
+                    // Ours and untouched since: refresh it
+                    Some((old, rest)) if frontmatter_is_generated(old) => (generated_frontmatter.as_str(), rest),
+                    Some((old, rest)) => (old, rest),
+                    None => (generated_frontmatter.as_str(), existing.as_str()),
+                };
+                let content = format!(
+                    "{}\n{}",
+                    frontmatter,
+                    export::splice_generated(rest.trim_start_matches('\n'), &block)
+                );
+                if content == existing {
+                    self.unchanged += 1;
+                    return Ok(());
+                }
+                content
+            }
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        export::write_atomic(&path, &content)
+    }
+}
+
+/// Split `---` fenced frontmatter (fences included) from the rest of a note
+fn split_frontmatter(text: &str) -> Option<(&str, &str)> {
+    if !text.starts_with("---\n") {
+        return None;
+    }
+    let end = text[3..].find("\n---\n").map(|i| 3 + i + "\n---\n".len())?;
+    Some((&text[..end], &text[end..]))
+}
+
+/// Whether frontmatter still matches the `profundo` hash written with it
+fn frontmatter_is_generated(frontmatter: &str) -> bool {
+    let inner = frontmatter
+        .strip_prefix("---\n")
+        .and_then(|f| f.strip_suffix("---\n"))
+        .unwrap_or("");
+    let mut hash = None;
+    let mut fields = String::new();
+    for line in inner.lines() {
+        match line.strip_prefix("profundo: ") {
+            Some(h) => hash = Some(h.trim().to_string()),
+            None => {
+                fields.push_str(line);
+                fields.push('\n');
+            }
+        }
+    }
+    hash.map(|h| h == format!("{:016x}", fnv1a(fields.as_bytes()))).unwrap_or(false)
+}
+
+/// Frontmatter and body of a session note
+fn session_note(
+    learning: &Learning,
+    session: Option<&Session>,
+    categories: &CategorySet,
+    action_items: &HashMap<String, ActionItem>,
+    topic_link: &dyn Fn(&str) -> String,
+) -> (String, String) {
+    let mut models: Vec<&String> = session.map(|s| s.models_used.iter().collect()).unwrap_or_default();
+    models.sort();
+    let cost = session.map(|s| s.total_cost).unwrap_or(learning.cost);
+
+    let frontmatter = format!(
+        "date: {}\nsession: {}\nagent: {}\nmodels: {}\ncost: {:.4}\ntopics: {}\ntags: [profundo/session]\n",
+        learning.date,
+        yaml_string(&learning.session_id),
+        yaml_string(&learning.agent),
+        yaml_list(&models),
+        cost,
+        yaml_list(&learning.topics.iter().collect::<Vec<_>>())
+    );
+
+    let mut body = format!("# {}\n\n", session_note_name(learning));
+    let mut links = vec![format!("Day: [[{}]]", learning.date)];
+    if !learning.topics.is_empty() {
+        let topics: Vec<String> = learning.topics.iter().map(|t| topic_link(t)).collect();
+        links.push(format!("Topics: {}", topics.join(", ")));
+    }
+    body.push_str(&format!("{}\n\n", links.join(" | ")));
+    if !learning.summary.is_empty() {
+        body.push_str(&format!("{}\n\n", learning.summary));
+    }
+
+    let mut section = |heading: &str, lines: Vec<String>| {
+        if !lines.is_empty() {
+            body.push_str(&format!("## {}\n\n{}\n\n", heading, lines.join("\n")));
+        }
+    };
+    section("Decisions", learning.decisions.iter().map(|d| format!("- {}", d)).collect());
+    section("Facts", learning.facts_learned.iter().map(|f| format!("- {}", f)).collect());
+    section(
+        "Action Items",
+        learning
+            .action_items
+            .iter()
+            .map(|text| action_line(text, action_items.get(&todo::item_id(&learning.session_id, text))))
+            .collect(),
+    );
+    let configured = categories.custom().map(|c| c.name.as_str());
+    let unconfigured = learning
+        .categories
+        .keys()
+        .map(|k| k.as_str())
+        .filter(|k| !categories.custom().any(|c| c.name == *k));
+    for name in configured.chain(unconfigured) {
+        let items = learning.categories.get(name).into_iter().flatten();
+        section(&categories::label(name), items.map(|i| format!("- {}", i)).collect());
+    }
+
+    (frontmatter, body)
+}
+
+/// Frontmatter and body of a topic note: its sessions, newest first, and the
+/// topics it most often comes up with
+fn topic_note(name: &str, members: &[&Learning], topic_link: &dyn Fn(&str) -> String) -> (String, String) {
+    let first = members.first().map(|l| l.date.as_str()).unwrap_or("");
+    let last = members.last().map(|l| l.date.as_str()).unwrap_or("");
+    let frontmatter = format!(
+        "tags: [profundo/topic]\nsessions: {}\nfirst_seen: {}\nlast_seen: {}\n",
+        members.len(),
+        first,
+        last
+    );
+
+    let mut body = format!("# {}\n\n## Sessions\n\n", name);
+    for learning in members.iter().rev() {
+        body.push_str(&format!("- [[{}]] {}\n", session_note_name(learning), learning.summary));
+    }
+
+    let mut related: HashMap<String, (String, usize)> = HashMap::new();
+    for learning in members {
+        for topic in learning.topics.iter().filter(|t| topic_key(t) != topic_key(name)) {
+            related.entry(topic_key(topic)).or_insert_with(|| (topic.clone(), 0)).1 += 1;
+        }
+    }
+    let mut related: Vec<(String, usize)> = related.into_values().collect();
+    related.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    if !related.is_empty() {
+        let links: Vec<String> = related
+            .iter()
+            .take(MAX_RELATED_TOPICS)
+            .map(|(topic, n)| format!("{} ({})", topic_link(topic), n))
+            .collect();
+        body.push_str(&format!("\n## Related\n\n{}\n", links.join(", ")));
+    }
+
+    (frontmatter, body)
+}
+
+/// Checkbox line for an action item with its tracked status
+fn action_line(text: &str, tracked: Option<&ActionItem>) -> String {
+    let closed_on = |item: &ActionItem| item.closed_on().map(|d| format!(" {}", d)).unwrap_or_default();
+    match tracked {
+        Some(item) if item.status == ActionStatus::Done => format!("- [x] {} _(done{})_", text, closed_on(item)),
+        Some(item) if item.status == ActionStatus::Dropped => format!("- ~~{}~~ _(dropped{})_", text, closed_on(item)),
+        _ => format!("- [ ] {}", text),
+    }
+}
+
+/// `2026-10-06 a1b2c3d4`
+fn session_note_name(learning: &Learning) -> String {
+    format!("{} {}", learning.date, short_id(&learning.session_id))
+}
+
+fn topic_key(topic: &str) -> String {
+    topic.trim().to_lowercase()
+}
+
+/// A topic as a note name: characters Obsidian doesn't allow in links or
+/// file names become dashes
+fn note_name(topic: &str) -> String {
+    topic
+        .trim()
+        .chars()
+        .map(|c| if "[]#^|\\/:*?\"<>".contains(c) { '-' } else { c })
+        .collect::<String>()
+        .trim_matches('.')
+        .to_string()
+}
+
+/// Double-quoted YAML string (JSON strings are valid YAML)
+fn yaml_string(text: &str) -> String {
+    serde_json::Value::String(text.to_string()).to_string()
+}
+
+fn yaml_list(items: &[&String]) -> String {
+    format!("[{}]", items.iter().map(|i| yaml_string(i)).collect::<Vec<_>>().join(", "))
+}
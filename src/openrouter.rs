@@ -4,6 +4,53 @@
 
 use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+/// An embedding backend.
+///
+/// `OpenRouterClient` is the production implementation; `embed::HashEmbedder`
+/// is a deterministic offline stand-in for evaluation.
+pub trait Embedder {
+    fn embed_batch(&self, texts: &[String]) -> impl Future<Output = Result<Vec<Vec<f32>>>> + Send;
+
+    /// Names the embedding space, so vectors cached from another backend aren't mixed in
+    fn model(&self) -> String;
+}
+
+/// A chat completion backend.
+///
+/// `OpenRouterClient` is the production implementation; anything else
+/// (a local model, `CannedChat` in tests) can stand in for it where callers
+/// are generic over this trait.
+pub trait ChatModel {
+    fn chat(
+        &self,
+        system_prompt: &str,
+        user_prompt: &str,
+        model: &str,
+    ) -> impl Future<Output = Result<String>> + Send;
+}
+
+/// A chat backend that answers every prompt with the same text, for running
+/// chat-dependent code offline
+#[derive(Clone, Debug, Default)]
+pub struct CannedChat {
+    pub response: String,
+}
+
+impl CannedChat {
+    pub fn new(response: impl Into<String>) -> Self {
+        Self {
+            response: response.into(),
+        }
+    }
+}
+
+impl ChatModel for CannedChat {
+    fn chat(&self, _system_prompt: &str, _user_prompt: &str, _model: &str) -> impl Future<Output = Result<String>> + Send {
+        std::future::ready(Ok(self.response.clone()))
+    }
+}
 
 /// OpenRouter client configuration
 #[derive(Clone)]
@@ -23,7 +70,6 @@ struct EmbeddingRequest {
 #[derive(Deserialize)]
 struct EmbeddingResponse {
     data: Vec<EmbeddingData>,
-    usage: Option<EmbeddingUsage>,
 }
 
 #[derive(Deserialize)]
@@ -32,18 +78,14 @@ struct EmbeddingData {
     index: usize,
 }
 
-#[derive(Deserialize)]
-struct EmbeddingUsage {
-    prompt_tokens: Option<i32>,
-    total_tokens: Option<i32>,
-}
-
 /// Chat completion request for harvest
 #[derive(Serialize)]
 struct ChatRequest {
     model: String,
     messages: Vec<ChatMessage>,
     temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 #[derive(Serialize)]
@@ -52,6 +94,32 @@ struct ChatMessage {
     content: String,
 }
 
+/// Model id prefixes whose providers accept `response_format: json_schema` on OpenRouter
+const STRUCTURED_OUTPUT_MODELS: &[&str] = &["openai/", "google/gemini", "mistralai/", "x-ai/"];
+
+/// Whether a chat model supports JSON-schema structured output
+pub fn supports_structured_output(model: &str) -> bool {
+    STRUCTURED_OUTPUT_MODELS.iter().any(|prefix| model.starts_with(prefix))
+}
+
+/// A chat request the API answered with an error status
+#[derive(Debug, thiserror::Error)]
+#[error("Chat API error {status}: {body}")]
+pub struct ChatApiError {
+    pub status: reqwest::StatusCode,
+    pub body: String,
+}
+
+impl ChatApiError {
+    /// Whether the provider refused the `response_format` parameter, rather
+    /// than failing the request for another reason (auth, rate limits, outages)
+    pub fn rejects_response_format(&self) -> bool {
+        let body = self.body.to_lowercase();
+        self.status == reqwest::StatusCode::BAD_REQUEST
+            && (body.contains("response_format") || body.contains("json_schema"))
+    }
+}
+
 #[derive(Deserialize)]
 struct ChatResponse {
     choices: Vec<ChatChoice>,
@@ -181,6 +249,39 @@ impl OpenRouterClient {
 
     /// Call chat completion for harvesting (uses cheaper model)
     pub async fn chat(&self, system_prompt: &str, user_prompt: &str, model: &str) -> Result<String> {
+        self.complete(system_prompt, user_prompt, model, None).await
+    }
+
+    /// Call chat completion constrained to a JSON schema.
+    ///
+    /// Only use with models where `supports_structured_output` is true; other
+    /// providers reject or ignore the `response_format` parameter.
+    pub async fn chat_structured(
+        &self,
+        system_prompt: &str,
+        user_prompt: &str,
+        model: &str,
+        schema_name: &str,
+        schema: &serde_json::Value,
+    ) -> Result<String> {
+        let response_format = serde_json::json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": schema_name,
+                "strict": true,
+                "schema": schema,
+            }
+        });
+        self.complete(system_prompt, user_prompt, model, Some(response_format)).await
+    }
+
+    async fn complete(
+        &self,
+        system_prompt: &str,
+        user_prompt: &str,
+        model: &str,
+        response_format: Option<serde_json::Value>,
+    ) -> Result<String> {
         let request = ChatRequest {
             model: model.to_string(),
             messages: vec![
@@ -194,6 +295,7 @@ impl OpenRouterClient {
                 },
             ],
             temperature: 0.3,
+            response_format,
         };
 
         let response = self
@@ -209,7 +311,7 @@ impl OpenRouterClient {
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(anyhow!("Chat API error {}: {}", status, body));
+            return Err(ChatApiError { status, body }.into());
         }
 
         let result: ChatResponse = response
@@ -225,3 +327,24 @@ impl OpenRouterClient {
             .ok_or_else(|| anyhow!("No response from chat API"))
     }
 }
+
+impl Embedder for OpenRouterClient {
+    fn embed_batch(&self, texts: &[String]) -> impl Future<Output = Result<Vec<Vec<f32>>>> + Send {
+        OpenRouterClient::embed_batch(self, texts)
+    }
+
+    fn model(&self) -> String {
+        self.model.clone()
+    }
+}
+
+impl ChatModel for OpenRouterClient {
+    fn chat(
+        &self,
+        system_prompt: &str,
+        user_prompt: &str,
+        model: &str,
+    ) -> impl Future<Output = Result<String>> + Send {
+        OpenRouterClient::chat(self, system_prompt, user_prompt, model)
+    }
+}
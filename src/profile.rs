@@ -0,0 +1,395 @@
+//! Profile - a standing document about the user, built from harvested facts
+//!
+//! `facts_learned` is new information about the user, scattered across every
+//! learning. `profundo profile` sorts the facts into sections, has the chat
+//! model write each section with newer facts overriding older ones, and writes
+//! `profile.md` to the memory directory with every line citing its sessions.
+//!
+//! Both steps are cached in the database: a rerun only sorts facts it hasn't
+//! seen and only rewrites sections whose facts changed.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use crate::consolidate::{CanonicalItem, ItemSource};
+use crate::db::Database;
+use crate::embed::fnv1a;
+use crate::harvest::Learning;
+use crate::openrouter::ChatModel;
+use crate::session::short_id;
+
+/// Profile sections: (name, heading, what belongs there)
+pub const SECTIONS: &[(&str, &str, &str)] = &[
+    (
+        "preferences",
+        "Preferences",
+        "Tools, languages, libraries, styles and choices the user prefers or avoids",
+    ),
+    (
+        "background",
+        "Background",
+        "Role, experience, employer, projects, location and other personal context",
+    ),
+    (
+        "environment",
+        "Environment",
+        "Machines, operating systems, editors, shells, services and setups the user works with",
+    ),
+    (
+        "working_style",
+        "Working Style",
+        "How the user likes to work, plan, review and communicate",
+    ),
+];
+
+/// Section for facts that aren't about the user; left out of the profile
+const OTHER: &str = "other";
+
+/// Configuration for building the profile
+#[derive(Clone, Debug)]
+pub struct ProfileConfig {
+    /// Chat model that sorts facts and writes sections
+    pub model: String,
+    /// Facts sorted per chat request
+    pub batch_size: usize,
+    /// Ignore cached sections and rewrite all of them
+    pub rebuild: bool,
+}
+
+impl Default for ProfileConfig {
+    fn default() -> Self {
+        Self {
+            model: "deepseek/deepseek-v3.2".to_string(),
+            batch_size: 50,
+            rebuild: false,
+        }
+    }
+}
+
+/// A fact and the sessions that stated it, oldest first
+#[derive(Debug, Clone)]
+pub struct ProfileFact {
+    pub text: String,
+    pub sources: Vec<ItemSource>,
+}
+
+impl ProfileFact {
+    fn last_seen(&self) -> &str {
+        self.sources.last().map(|s| s.date.as_str()).unwrap_or("")
+    }
+}
+
+/// One line of the profile and the sessions behind it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileLine {
+    pub text: String,
+    pub sources: Vec<ItemSource>,
+}
+
+/// A written profile section
+#[derive(Debug, Clone)]
+pub struct ProfileSection {
+    pub name: String,
+    pub heading: String,
+    pub lines: Vec<ProfileLine>,
+}
+
+/// Summary of a profile run
+#[derive(Debug, Default)]
+pub struct ProfileStats {
+    pub facts: usize,
+    /// Facts sorted into sections this run
+    pub sorted: usize,
+    /// Facts left out as not about the user
+    pub skipped: usize,
+    pub sections_written: usize,
+    pub sections_cached: usize,
+    pub lines: usize,
+    /// Lines the model wrote without a valid citation (dropped)
+    pub uncited: usize,
+}
+
+const SORT_PROMPT: &str = "You sort facts about a user into the sections of their profile. \
+Answer with one line per fact in the form \"N: section\" and nothing else.";
+
+const SECTION_PROMPT: &str = "You maintain one section of a user's profile from facts learned in their \
+conversations. Write short, specific statements about the user as markdown bullets. \
+Facts are numbered oldest first: when facts disagree, the newer one wins and the older one is left out. \
+Merge facts that say the same thing. End every bullet with the numbers of the facts it is based on, \
+like \"- Prefers pnpm over npm [3, 7]\". Output only the bullets.";
+
+/// Build the profile and write it to `output`
+pub async fn run<C: ChatModel>(
+    db: &Database,
+    chat: &C,
+    config: &ProfileConfig,
+    output: &Path,
+) -> Result<ProfileStats> {
+    let learnings = db.load_learnings(None)?;
+    let canonical = db.load_canonical_items()?;
+    let facts = collect_facts(&learnings, &canonical);
+
+    let mut stats = ProfileStats {
+        facts: facts.len(),
+        ..Default::default()
+    };
+
+    // Sort facts into sections, asking only about ones not seen before
+    let mut sections: HashMap<String, String> = HashMap::new();
+    let mut unsorted = Vec::new();
+    for fact in &facts {
+        match db.profile_fact_section(&fact.text)? {
+            Some(section) => {
+                sections.insert(fact.text.clone(), section);
+            }
+            None => unsorted.push(fact.text.as_str()),
+        }
+    }
+    for batch in unsorted.chunks(config.batch_size.max(1)) {
+        let sorted = sort_facts(chat, batch, &config.model).await?;
+        for (text, section) in sorted {
+            db.store_profile_fact_section(text, &section)?;
+            sections.insert(text.to_string(), section);
+            stats.sorted += 1;
+        }
+    }
+
+    // Write each section whose facts changed since last time
+    let mut written = Vec::new();
+    for &(name, heading, description) in SECTIONS {
+        let section_facts: Vec<&ProfileFact> = facts
+            .iter()
+            .filter(|f| sections.get(&f.text).map(|s| s == name).unwrap_or(false))
+            .collect();
+        let numbered = number_facts(&section_facts);
+        let input_hash = format!("{:016x}", fnv1a(format!("{}\n{}", config.model, numbered).as_bytes()));
+
+        let lines = match db.profile_section(name)? {
+            _ if section_facts.is_empty() => Vec::new(),
+            Some((hash, lines)) if hash == input_hash && !config.rebuild => {
+                stats.sections_cached += 1;
+                lines
+            }
+            _ => {
+                let prompt = format!("Section: {} ({})\n\nFacts:\n{}", heading, description, numbered);
+                let answer = chat.chat(SECTION_PROMPT, &prompt, &config.model).await?;
+                let (lines, uncited) = parse_section(&answer, &section_facts);
+                stats.sections_written += 1;
+                stats.uncited += uncited;
+                lines
+            }
+        };
+        db.store_profile_section(name, &input_hash, &lines)?;
+
+        stats.lines += lines.len();
+        written.push(ProfileSection {
+            name: name.to_string(),
+            heading: heading.to_string(),
+            lines,
+        });
+    }
+    stats.skipped = facts
+        .iter()
+        .filter(|f| sections.get(&f.text).map(|s| s == OTHER).unwrap_or(false))
+        .count();
+
+    let profile_facts: Vec<&ProfileFact> = facts
+        .iter()
+        .filter(|f| sections.get(&f.text).map(|s| s != OTHER).unwrap_or(false))
+        .collect();
+    std::fs::write(output, render(&written, &profile_facts)).context("Failed to write profile")?;
+
+    Ok(stats)
+}
+
+/// Facts to build the profile from, least recently stated first.
+///
+/// Once consolidated, the current canonical facts stand in for the facts
+/// they cover (superseded ones are left out); facts no canonical entry
+/// covers (harvested since, or in no cluster) are added as they are.
+pub fn collect_facts(learnings: &[Learning], canonical: &[CanonicalItem]) -> Vec<ProfileFact> {
+    let covered = crate::consolidate::covered(canonical);
+
+    let mut facts: Vec<ProfileFact> = canonical
+        .iter()
+        .filter(|item| item.kind == "facts_learned" && item.superseded_by.is_none())
+        .map(|item| ProfileFact {
+            text: item.text.clone(),
+            sources: item.sources.clone(),
+        })
+        .collect();
+
+    for learning in learnings {
+        let uncovered = |t: &&String| !covered.contains(&("facts_learned", learning.session_id.as_str(), t.as_str()));
+        for text in learning.facts_learned.iter().filter(|t| !t.trim().is_empty()).filter(uncovered) {
+            facts.push(ProfileFact {
+                text: text.clone(),
+                sources: vec![ItemSource {
+                    session_id: learning.session_id.clone(),
+                    date: learning.date.clone(),
+                    text: text.clone(),
+                }],
+            });
+        }
+    }
+
+    facts.sort_by(|a, b| a.last_seen().cmp(b.last_seen()));
+    facts
+}
+
+/// Ask the chat model which section each fact belongs to.
+///
+/// Facts it doesn't answer for are left unsorted and asked about next run.
+async fn sort_facts<'a, C: ChatModel>(chat: &C, facts: &[&'a str], model: &str) -> Result<Vec<(&'a str, String)>> {
+    let mut prompt = String::from("Sections:\n");
+    for (name, _, description) in SECTIONS {
+        prompt.push_str(&format!("- {}: {}\n", name, description));
+    }
+    prompt.push_str(&format!("- {}: Not about the user (project details, general knowledge)\n\nFacts:\n", OTHER));
+    for (i, fact) in facts.iter().enumerate() {
+        prompt.push_str(&format!("{}. {}\n", i + 1, fact));
+    }
+
+    let answer = chat.chat(SORT_PROMPT, &prompt, model).await?;
+
+    let mut sorted = Vec::new();
+    for line in answer.lines() {
+        let Some((number, section)) = line.split_once(':') else {
+            continue;
+        };
+        let number = number.trim().trim_start_matches(['-', '*']).trim().trim_end_matches('.');
+        let Some(fact) = number.parse::<usize>().ok().and_then(|n| n.checked_sub(1)).and_then(|i| facts.get(i)) else {
+            continue;
+        };
+        let section = section.trim().trim_matches(|c: char| c == '`' || c == '"' || c == '*').to_lowercase();
+        let section = if SECTIONS.iter().any(|(name, _, _)| *name == section) {
+            section
+        } else {
+            OTHER.to_string()
+        };
+        if !sorted.iter().any(|(f, _)| f == fact) {
+            sorted.push((*fact, section));
+        }
+    }
+
+    Ok(sorted)
+}
+
+/// Number facts for the section prompt, with the date each was last stated
+fn number_facts(facts: &[&ProfileFact]) -> String {
+    facts
+        .iter()
+        .enumerate()
+        .map(|(i, fact)| format!("{}. ({}) {}", i + 1, fact.last_seen(), fact.text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parse the model's bullets, resolving `[n, m]` citations to the facts'
+/// sessions. Returns the lines and how many were dropped for citing nothing valid.
+fn parse_section(answer: &str, facts: &[&ProfileFact]) -> (Vec<ProfileLine>, usize) {
+    let mut lines = Vec::new();
+    let mut uncited = 0;
+
+    for line in answer.lines() {
+        let line = line.trim();
+        let Some(line) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) else {
+            continue;
+        };
+
+        let (text, cited) = match line.rfind('[') {
+            Some(open) if line.ends_with(']') => (line[..open].trim(), &line[open + 1..line.len() - 1]),
+            _ => (line, ""),
+        };
+        let numbers: BTreeSet<usize> = cited
+            .split(',')
+            .filter_map(|n| n.trim().parse::<usize>().ok())
+            .filter(|n| (1..=facts.len()).contains(n))
+            .collect();
+
+        if text.is_empty() || numbers.is_empty() {
+            uncited += 1;
+            continue;
+        }
+
+        let mut sources: Vec<ItemSource> = numbers.iter().flat_map(|&n| facts[n - 1].sources.clone()).collect();
+        sources.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.session_id.cmp(&b.session_id)));
+        sources.dedup_by(|a, b| a.session_id == b.session_id);
+
+        lines.push(ProfileLine {
+            text: text.to_string(),
+            sources,
+        });
+    }
+
+    (lines, uncited)
+}
+
+/// Render the profile as markdown
+pub fn render(sections: &[ProfileSection], facts: &[&ProfileFact]) -> String {
+    let sessions: HashSet<&str> = facts
+        .iter()
+        .flat_map(|f| f.sources.iter().map(|s| s.session_id.as_str()))
+        .collect();
+
+    let mut content = String::from("# User Profile\n\n");
+    content.push_str(&format!(
+        "Synthesized by profundo from {} facts in {} sessions on {}. Newer facts override older ones; \
+         each line cites the sessions it comes from.\n\n",
+        facts.len(),
+        sessions.len(),
+        Utc::now().format("%Y-%m-%d")
+    ));
+
+    for section in sections.iter().filter(|s| !s.lines.is_empty()) {
+        content.push_str(&format!("## {}\n\n", section.heading));
+        for line in &section.lines {
+            content.push_str(&format!("- {} _({})_\n", line.text, format_sources(&line.sources)));
+        }
+        content.push('\n');
+    }
+
+    format!("{}\n", content.trim_end())
+}
+
+/// `2026-10-02 a1b2c3d4, 2026-10-10 9f8e7d6c`, newest first
+fn format_sources(sources: &[ItemSource]) -> String {
+    sources
+        .iter()
+        .rev()
+        .map(|s| format!("{} {}", s.date, short_id(&s.session_id)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Print a profile run summary
+pub fn display_stats(stats: &ProfileStats, output: &Path) {
+    println!(
+        "{} {} facts → {} profile lines in {}",
+        "✓".green(),
+        stats.facts.to_string().cyan(),
+        stats.lines.to_string().cyan(),
+        output.display().to_string().dimmed()
+    );
+    println!(
+        "  {} facts newly sorted, {} not about the user",
+        stats.sorted.to_string().cyan(),
+        stats.skipped.to_string().cyan()
+    );
+    println!(
+        "  {} sections rewritten, {} unchanged",
+        stats.sections_written.to_string().cyan(),
+        stats.sections_cached.to_string().cyan()
+    );
+    if stats.uncited > 0 {
+        println!(
+            "  {} {} lines dropped for citing no facts",
+            "⚠".yellow(),
+            stats.uncited.to_string().yellow()
+        );
+    }
+}
@@ -8,10 +8,19 @@ use std::collections::HashMap;
 use std::path::Path;
 
 use crate::db::{Database, StoredChunk};
-use crate::openrouter::OpenRouterClient;
-use crate::session::Session;
+use crate::openrouter::{ChatModel, Embedder, OpenRouterClient};
+use crate::session::{short_id, Session};
 use crate::Paths;
 
+/// Marks the start of a matched term in snippet text
+pub const HIGHLIGHT_START: &str = "\u{2}";
+/// Marks the end of a matched term in snippet text
+pub const HIGHLIGHT_END: &str = "\u{3}";
+
+/// Approximate snippet length (FTS5 tokens / characters for the fallback)
+const SNIPPET_TOKENS: usize = 48;
+const SNIPPET_CHARS: usize = 300;
+
 /// Search result with similarity score
 #[derive(Debug)]
 pub struct SearchResult {
@@ -34,6 +43,20 @@ pub struct RecallConfig {
     pub context_turns: Option<usize>,
     /// Use LLM to expand query with synonyms/variants before searching
     pub expand: bool,
+    /// Reciprocal Rank Fusion constant (higher flattens rank differences)
+    pub rrf_k: f32,
+    /// Only search this agent's memory (all agents if None)
+    pub agent: Option<String>,
+}
+
+impl RecallConfig {
+    /// Apply overrides from the environment (`PROFUNDO_SEMANTIC_ONLY=1`)
+    pub fn with_env(mut self) -> Self {
+        if std::env::var("PROFUNDO_SEMANTIC_ONLY").ok().as_deref() == Some("1") {
+            self.semantic_only = true;
+        }
+        self
+    }
 }
 
 impl Default for RecallConfig {
@@ -45,12 +68,14 @@ impl Default for RecallConfig {
             show_full: false,
             context_turns: None,
             expand: false,
+            rrf_k: 60.0,
+            agent: None,
         }
     }
 }
 
 /// Expand a query using LLM to generate synonyms/variants
-async fn expand_query(client: &OpenRouterClient, query: &str) -> Result<Vec<String>> {
+async fn expand_query<C: ChatModel>(client: &C, query: &str) -> Result<Vec<String>> {
     let system_prompt = "You generate alternative search queries. Return only the queries, one per line. No explanations, no numbering.";
     let user_prompt = format!(
         "Generate 2 alternative search queries for: {}\n\nReturn only the queries, one per line.",
@@ -79,41 +104,60 @@ async fn expand_query(client: &OpenRouterClient, query: &str) -> Result<Vec<Stri
 }
 
 /// Search for similar content in memory
-pub async fn search(paths: &Paths, query: &str, mut config: RecallConfig) -> Result<Vec<SearchResult>> {
-    if std::env::var("PROFUNDO_SEMANTIC_ONLY").ok().as_deref() == Some("1") {
-        config.semantic_only = true;
-    }
-
+pub async fn search(paths: &Paths, query: &str, config: RecallConfig) -> Result<Vec<SearchResult>> {
+    let config = config.with_env();
     let client = OpenRouterClient::from_env()?;
     let db = Database::open(&paths.db_path)?;
 
+    search_with(&db, &client, Some(&client), query, config).await
+}
+
+/// Search with explicit embedding and query-expansion backends.
+///
+/// Expansion is skipped when no chat backend is given.
+pub async fn search_with<E: Embedder, C: ChatModel>(
+    db: &Database,
+    embedder: &E,
+    chat: Option<&C>,
+    query: &str,
+    config: RecallConfig,
+) -> Result<Vec<SearchResult>> {
     // Optionally expand the query with LLM-generated variants
-    let queries: Vec<String> = if config.expand {
-        let mut all_queries = vec![query.to_string()];
-        eprintln!("  {} Expanding query...", "→".blue());
-        let variants = expand_query(&client, query).await?;
-        if !variants.is_empty() {
-            eprintln!("  {} Variants: {}", "✓".green(), variants.join(", "));
+    let queries: Vec<String> = match chat {
+        Some(chat) if config.expand => {
+            let mut all_queries = vec![query.to_string()];
+            eprintln!("  {} Expanding query...", "→".blue());
+            let variants = expand_query(chat, query).await?;
+            if !variants.is_empty() {
+                eprintln!("  {} Variants: {}", "✓".green(), variants.join(", "));
+            }
+            all_queries.extend(variants);
+            all_queries
         }
-        all_queries.extend(variants);
-        all_queries
-    } else {
-        vec![query.to_string()]
+        _ => vec![query.to_string()],
     };
 
     // Embed the primary query for semantic scoring
-    let query_embedding = client.embed(query).await?;
+    let query_embedding = embedder
+        .embed_batch(&[query.to_string()])
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("No embedding returned"))?;
 
     if config.semantic_only {
         // Legacy path: load all chunks, brute-force cosine similarity
-        let chunks = db.load_all_chunks()?;
+        let mut chunks = db.load_all_chunks()?;
+        if let Some(ref agent) = config.agent {
+            chunks.retain(|c| &c.agent == agent);
+        }
         if chunks.is_empty() {
             return Ok(Vec::new());
         }
         return semantic_only_search(chunks, &query_embedding, config);
     }
 
-    hybrid_search_expanded(&db, &query_embedding, &queries, config)
+    hybrid_search_expanded(db, &query_embedding, &queries, config)
 }
 
 fn semantic_only_search(
@@ -152,7 +196,9 @@ fn hybrid_search_expanded(
     // Merge BM25 results from all query variants
     let mut all_bm25: HashMap<i64, f32> = HashMap::new();
     for q in queries {
-        let lexical = db.bm25_search(q, candidate_pool_size_per_query).unwrap_or_default();
+        let lexical = db
+            .bm25_search(q, candidate_pool_size_per_query, config.agent.as_deref())
+            .unwrap_or_default();
         for (rowid, rank) in lexical {
             // Keep the best (lowest) rank for each rowid
             all_bm25
@@ -176,7 +222,11 @@ fn hybrid_search_expanded(
     // Load only candidate chunks (embeddings included) for semantic scoring
     let candidates = if bm25_rowids.is_empty() {
         // BM25 returned nothing (e.g., query terms not in corpus) — fall back to full scan
-        db.load_all_chunks()?
+        let mut all = db.load_all_chunks()?;
+        if let Some(ref agent) = config.agent {
+            all.retain(|c| &c.agent == agent);
+        }
+        all
     } else {
         db.load_chunks_by_rowids(&bm25_rowids)?
     };
@@ -213,7 +263,7 @@ fn hybrid_search_expanded(
     }
 
     // Reciprocal Rank Fusion
-    const RRF_K: f32 = 60.0;
+    let rrf_k = config.rrf_k;
     const FALLBACK_RANK: f32 = 10_000.0;
 
     let mut fused: Vec<(f32, i64)> = Vec::new();
@@ -226,7 +276,7 @@ fn hybrid_search_expanded(
     {
         let sr = sem_rank.get(&rowid).copied().map(|r| r as f32).unwrap_or(FALLBACK_RANK);
         let br = lex_rank.get(&rowid).copied().map(|r| r as f32).unwrap_or(FALLBACK_RANK);
-        let score = 1.0 / (RRF_K + sr) + 1.0 / (RRF_K + br);
+        let score = 1.0 / (rrf_k + sr) + 1.0 / (rrf_k + br);
         fused.push((score, rowid));
     }
 
@@ -270,6 +320,13 @@ pub fn display_results(
     // Cache parsed sessions to avoid re-parsing the same file per result
     let mut session_cache: HashMap<String, Vec<crate::session::Turn>> = HashMap::new();
 
+    // Passages around the matched terms, from FTS5 where the chunk matched lexically
+    let terms = query_terms(query);
+    let rowids: Vec<i64> = results.iter().map(|r| r.chunk.rowid).collect();
+    let snippets = Database::open(&paths.db_path)
+        .and_then(|db| db.chunk_snippets(query, &rowids, SNIPPET_TOKENS, (HIGHLIGHT_START, HIGHLIGHT_END)))
+        .unwrap_or_default();
+
     for (i, result) in results.iter().enumerate() {
         let date = result
             .chunk
@@ -289,31 +346,37 @@ pub fn display_results(
 
         let id_display = {
             let sid = &result.chunk.session_id;
-            if sid.len() >= 8 { &sid[..8] } else { sid.as_str() }
+            let short = short_id(sid);
+            if result.chunk.agent == crate::DEFAULT_AGENT {
+                short.to_string()
+            } else {
+                format!("{}/{}", result.chunk.agent, short)
+            }
         };
 
         println!(
-            "{}. {} [{}] ({})",
+            "{}. {} [{}] ({}) {}",
             (i + 1).to_string().bold(),
             date.cyan(),
             id_display.dimmed(),
-            similarity_color
+            similarity_color,
+            result.chunk.id.dimmed()
         );
 
         if let Some(context) = config.context_turns {
             display_with_context(paths, result, context, &mut session_cache);
         } else if config.show_full {
             for line in result.chunk.text.lines() {
-                println!("   {}", line);
+                println!("   {}", highlight(line, &terms));
             }
         } else {
-            // Truncate and display text preview
-            let preview = truncate_text(&result.chunk.text, 300);
-            for line in preview.lines().take(6) {
-                println!("   {}", line.dimmed());
-            }
-            if result.chunk.text.lines().count() > 6 || result.chunk.text.len() > 300 {
-                println!("   {}", "...".dimmed());
+            // Show the passage around the matched terms; semantic-only hits
+            // fall back to a term search over the chunk body
+            let snippet = snippets.get(&result.chunk.rowid).cloned().unwrap_or_else(|| {
+                snippet_around_terms(strip_chunk_header(&result.chunk.text), &terms, SNIPPET_CHARS)
+            });
+            for line in snippet.lines().filter(|l| !l.trim().is_empty()).take(6) {
+                println!("   {}", render_highlights(line));
             }
         }
 
@@ -321,33 +384,31 @@ pub fn display_results(
     }
 
     if let Ok(db) = Database::open_with_learnings(&paths.db_path, learnings_path) {
-        if let Ok(learnings) = db.search_learnings(query, 3) {
+        if let Ok(learnings) = db.search_learnings(query, 3, config.agent.as_deref()) {
             if !learnings.is_empty() {
                 println!("📝 Related Learnings:\n");
 
                 for (learning, _rank) in learnings {
-                    let id_display = if learning.session_id.len() >= 8 {
-                        &learning.session_id[..8]
-                    } else {
-                        learning.session_id.as_str()
-                    };
-
-                    println!("● {} [{}]", learning.date.cyan(), id_display.dimmed());
+                    println!("● {} [{}]", learning.date.cyan(), short_id(&learning.session_id).dimmed());
 
                     if !learning.topics.is_empty() {
-                        println!("  Topics: {}", learning.topics.join(", "));
+                        println!("  Topics: {}", highlight(&learning.topics.join(", "), &terms));
                     }
                     if !learning.facts_learned.is_empty() {
-                        println!("  Facts: {}", learning.facts_learned.join(" "));
+                        println!("  Facts: {}", highlight(&learning.facts_learned.join(" "), &terms));
                     }
                     if !learning.decisions.is_empty() {
-                        println!("  Decisions: {}", learning.decisions.join(" "));
+                        println!("  Decisions: {}", highlight(&learning.decisions.join(" "), &terms));
                     }
                     if !learning.action_items.is_empty() {
-                        println!("  Action Items: {}", learning.action_items.join(" "));
+                        println!("  Action Items: {}", highlight(&learning.action_items.join(" "), &terms));
+                    }
+                    for (name, items) in learning.categories.iter().filter(|(_, items)| !items.is_empty()) {
+                        println!("  {}: {}", crate::categories::label(name), highlight(&items.join(" "), &terms));
                     }
                     if !learning.summary.trim().is_empty() {
-                        println!("  Summary: {}", learning.summary);
+                        let summary = snippet_around_terms(&learning.summary, &terms, SNIPPET_CHARS);
+                        println!("  Summary: {}", render_highlights(&summary));
                     }
 
                     println!();
@@ -389,15 +450,14 @@ fn display_with_context(
     let turns = match session_cache.get(session_id) {
         Some(cached) => cached,
         None => {
-            let session_path = paths.sessions_dir.join(format!("{}.jsonl", session_id));
-            if !session_path.exists() {
+            let Some(session_path) = paths.find_session_file(session_id) else {
                 println!(
                     "   {}: session file not found, showing stored chunk.",
                     "Warning".yellow()
                 );
                 display_chunk_indented(&result.chunk.text);
                 return;
-            }
+            };
             match Session::from_file(&session_path) {
                 Ok(session) => {
                     session_cache.insert(session_id.clone(), session.get_turns());
@@ -461,7 +521,7 @@ fn display_chunk_indented(text: &str) {
 }
 
 /// Compute cosine similarity between two vectors
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     if a.len() != b.len() || a.is_empty() {
         return 0.0;
     }
@@ -477,11 +537,127 @@ fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     dot_product / (norm_a * norm_b)
 }
 
-/// Truncate text to a maximum length
-fn truncate_text(text: &str, max_len: usize) -> String {
-    if text.len() <= max_len {
-        text.to_string()
-    } else {
-        format!("{}...", &text[..max_len])
+/// Truncate text to a maximum number of characters
+fn truncate_text(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}…", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+/// Split a query into lowercase terms for highlighting
+pub fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= 2)
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+/// Byte ranges of words in `text` that start with one of `terms` (case-insensitive).
+///
+/// Ranges always fall on char boundaries, so slicing with them is safe.
+fn match_ranges(text: &str, terms: &[String]) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    if terms.is_empty() {
+        return ranges;
+    }
+
+    let mut word_start: Option<usize> = None;
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        if c.is_alphanumeric() {
+            word_start.get_or_insert(i);
+        } else if let Some(start) = word_start.take() {
+            let word = text[start..i].to_lowercase();
+            if terms.iter().any(|t| word.starts_with(t.as_str())) {
+                ranges.push((start, i));
+            }
+        }
+    }
+
+    ranges
+}
+
+/// Wrap every matched term in highlight markers
+pub fn mark_terms(text: &str, terms: &[String]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, end) in match_ranges(text, terms) {
+        out.push_str(&text[last..start]);
+        out.push_str(HIGHLIGHT_START);
+        out.push_str(&text[start..end]);
+        out.push_str(HIGHLIGHT_END);
+        last = end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Extract roughly `max_chars` characters around the first matched term,
+/// with matches marked. Falls back to the start of the text if nothing matches.
+pub fn snippet_around_terms(text: &str, terms: &[String], max_chars: usize) -> String {
+    let Some(&(first_match, _)) = match_ranges(text, terms).first() else {
+        return truncate_text(text, max_chars);
+    };
+
+    let chars: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+    let match_idx = chars.partition_point(|&i| i < first_match);
+
+    // Keep a third of the window as lead-in before the match
+    let start_idx = match_idx.saturating_sub(max_chars / 3);
+    let end_idx = (start_idx + max_chars).min(chars.len());
+
+    let mut start = chars[start_idx];
+    let end = chars.get(end_idx).copied().unwrap_or(text.len());
+
+    // Don't open mid-word
+    if start > 0 {
+        if let Some(ws) = text[start..first_match].find(char::is_whitespace) {
+            start += ws + 1;
+        }
+    }
+
+    let mut out = String::new();
+    if start > 0 {
+        out.push('…');
+    }
+    out.push_str(&mark_terms(&text[start..end], terms));
+    if end < text.len() {
+        out.push('…');
+    }
+    out
+}
+
+/// Style highlight markers for the terminal
+pub fn render_highlights(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, part) in text.split(HIGHLIGHT_START).enumerate() {
+        if i == 0 {
+            out.push_str(part);
+            continue;
+        }
+        match part.split_once(HIGHLIGHT_END) {
+            Some((matched, rest)) => {
+                out.push_str(&matched.yellow().bold().to_string());
+                out.push_str(rest);
+            }
+            None => out.push_str(&part.yellow().bold().to_string()),
+        }
+    }
+    out
+}
+
+/// Highlight matched terms in `text` for terminal output
+pub fn highlight(text: &str, terms: &[String]) -> String {
+    render_highlights(&mark_terms(text, terms))
+}
+
+/// Drop the `[date | id]` header that prefixes every embedded chunk
+fn strip_chunk_header(text: &str) -> &str {
+    if text.starts_with('[') {
+        if let Some(end) = text.find("]\n\n") {
+            return &text[end + 3..];
+        }
     }
+    text
 }
@@ -0,0 +1,296 @@
+//! Related - "more like this" over stored embeddings
+//!
+//! Finds sessions and chunks similar to a given session or chunk, without a query.
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::db::{Database, StoredChunk};
+use crate::recall::cosine_similarity;
+use crate::Paths;
+
+/// How to score similarity between two sessions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SessionScoring {
+    /// Cosine similarity between the mean chunk vectors of each session
+    #[default]
+    Centroid,
+    /// Best cosine similarity over all chunk pairs
+    MaxSim,
+}
+
+/// Configuration for related search
+#[derive(Clone, Debug)]
+pub struct RelatedConfig {
+    /// Number of sessions and chunks to return
+    pub top_k: usize,
+    /// Session scoring method
+    pub scoring: SessionScoring,
+}
+
+impl Default for RelatedConfig {
+    fn default() -> Self {
+        Self {
+            top_k: 5,
+            scoring: SessionScoring::Centroid,
+        }
+    }
+}
+
+/// What the search was anchored on
+#[derive(Debug, Serialize)]
+pub struct RelatedSource {
+    /// "session" or "chunk"
+    pub kind: &'static str,
+    pub id: String,
+    pub session_id: String,
+    pub chunks: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RelatedSession {
+    pub session_id: String,
+    pub date: Option<String>,
+    pub score: f32,
+    pub chunks: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RelatedChunk {
+    pub id: String,
+    pub session_id: String,
+    pub turn_start: i32,
+    pub turn_end: i32,
+    pub timestamp: Option<String>,
+    pub score: f32,
+    pub text: String,
+}
+
+/// Results of a related search
+#[derive(Debug, Serialize)]
+pub struct RelatedResults {
+    pub source: RelatedSource,
+    pub sessions: Vec<RelatedSession>,
+    pub chunks: Vec<RelatedChunk>,
+}
+
+/// Find sessions and chunks similar to a session id (or unique prefix) or chunk id
+pub fn find(paths: &Paths, target: &str, config: RelatedConfig) -> Result<RelatedResults> {
+    let db = Database::open(&paths.db_path)?;
+    let chunks = db.load_all_chunks()?;
+
+    if chunks.is_empty() {
+        return Err(anyhow!("No embeddings yet. Run `profundo embed` first."));
+    }
+
+    let (source, source_chunks) = resolve_target(&chunks, target)?;
+
+    // Everything outside the source session is a candidate
+    let mut by_session: HashMap<&str, Vec<&StoredChunk>> = HashMap::new();
+    for chunk in &chunks {
+        if chunk.session_id != source.session_id {
+            by_session.entry(chunk.session_id.as_str()).or_default().push(chunk);
+        }
+    }
+
+    let source_centroid = centroid(&source_chunks);
+
+    let mut sessions: Vec<RelatedSession> = by_session
+        .iter()
+        .map(|(session_id, session_chunks)| {
+            let score = match config.scoring {
+                SessionScoring::Centroid => {
+                    cosine_similarity(&source_centroid, &centroid(session_chunks))
+                }
+                SessionScoring::MaxSim => session_chunks
+                    .iter()
+                    .map(|c| max_similarity(&source_chunks, c))
+                    .fold(f32::MIN, f32::max),
+            };
+
+            let date = session_chunks
+                .iter()
+                .filter_map(|c| c.timestamp.as_deref())
+                .min()
+                .and_then(|t| t.split('T').next())
+                .map(|d| d.to_string());
+
+            RelatedSession {
+                session_id: session_id.to_string(),
+                date,
+                score,
+                chunks: session_chunks.len(),
+            }
+        })
+        .collect();
+
+    sessions.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    sessions.truncate(config.top_k);
+
+    let mut scored_chunks: Vec<(f32, &StoredChunk)> = by_session
+        .values()
+        .flatten()
+        .map(|c| (max_similarity(&source_chunks, c), *c))
+        .collect();
+
+    scored_chunks.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+
+    let chunks = scored_chunks
+        .into_iter()
+        .take(config.top_k)
+        .map(|(score, c)| RelatedChunk {
+            id: c.id.clone(),
+            session_id: c.session_id.clone(),
+            turn_start: c.turn_start,
+            turn_end: c.turn_end,
+            timestamp: c.timestamp.clone(),
+            score,
+            text: c.text.clone(),
+        })
+        .collect();
+
+    Ok(RelatedResults {
+        source,
+        sessions,
+        chunks,
+    })
+}
+
+/// Resolve a chunk id, session id or unique session id prefix to its chunks
+fn resolve_target<'a>(
+    chunks: &'a [StoredChunk],
+    target: &str,
+) -> Result<(RelatedSource, Vec<&'a StoredChunk>)> {
+    if let Some(chunk) = chunks.iter().find(|c| c.id == target) {
+        let source = RelatedSource {
+            kind: "chunk",
+            id: chunk.id.clone(),
+            session_id: chunk.session_id.clone(),
+            chunks: 1,
+        };
+        return Ok((source, vec![chunk]));
+    }
+
+    let mut session_ids: Vec<&str> = chunks
+        .iter()
+        .map(|c| c.session_id.as_str())
+        .filter(|id| *id == target)
+        .collect();
+
+    if session_ids.is_empty() {
+        session_ids = chunks
+            .iter()
+            .map(|c| c.session_id.as_str())
+            .filter(|id| id.starts_with(target))
+            .collect();
+    }
+
+    session_ids.sort_unstable();
+    session_ids.dedup();
+
+    let session_id = match session_ids.as_slice() {
+        [id] => *id,
+        [] => return Err(anyhow!("No embedded session or chunk matches '{}'", target)),
+        _ => {
+            return Err(anyhow!(
+                "'{}' is ambiguous ({} sessions match); use a longer prefix",
+                target,
+                session_ids.len()
+            ))
+        }
+    };
+
+    let source_chunks: Vec<&StoredChunk> = chunks
+        .iter()
+        .filter(|c| c.session_id == session_id)
+        .collect();
+
+    let source = RelatedSource {
+        kind: "session",
+        id: session_id.to_string(),
+        session_id: session_id.to_string(),
+        chunks: source_chunks.len(),
+    };
+
+    Ok((source, source_chunks))
+}
+
+/// Mean of the (normalized) chunk vectors
+fn centroid(chunks: &[&StoredChunk]) -> Vec<f32> {
+    let dim = chunks.first().map(|c| c.embedding.len()).unwrap_or(0);
+    let mut sum = vec![0.0f32; dim];
+
+    for chunk in chunks {
+        if chunk.embedding.len() != dim {
+            continue;
+        }
+        let norm = chunk.embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm == 0.0 {
+            continue;
+        }
+        for (s, x) in sum.iter_mut().zip(&chunk.embedding) {
+            *s += x / norm;
+        }
+    }
+
+    sum
+}
+
+/// Best similarity between a candidate chunk and any source chunk
+fn max_similarity(source: &[&StoredChunk], candidate: &StoredChunk) -> f32 {
+    source
+        .iter()
+        .map(|s| cosine_similarity(&s.embedding, &candidate.embedding))
+        .fold(f32::MIN, f32::max)
+}
+
+/// Display related results in a readable format
+pub fn display(results: &RelatedResults) {
+    println!(
+        "\n{} Related to {} {}\n",
+        "→".blue(),
+        results.source.kind,
+        results.source.id.cyan()
+    );
+
+    if results.sessions.is_empty() {
+        println!("{} No other sessions embedded yet", "→".yellow());
+        return;
+    }
+
+    println!("{}", "Similar Sessions".bold());
+    for (i, session) in results.sessions.iter().enumerate() {
+        println!(
+            "  {}. {} {} ({}%, {} chunks)",
+            (i + 1).to_string().bold(),
+            session.date.as_deref().unwrap_or("unknown").cyan(),
+            session.session_id.dimmed(),
+            (session.score * 100.0) as i32,
+            session.chunks
+        );
+    }
+    println!();
+
+    println!("{}", "Similar Chunks".bold());
+    for (i, chunk) in results.chunks.iter().enumerate() {
+        let date = chunk
+            .timestamp
+            .as_deref()
+            .and_then(|t| t.split('T').next())
+            .unwrap_or("unknown");
+        println!(
+            "  {}. {} {} ({}%)",
+            (i + 1).to_string().bold(),
+            date.cyan(),
+            chunk.id.dimmed(),
+            (chunk.score * 100.0) as i32
+        );
+
+        let body = chunk.text.split_once("]\n\n").map(|(_, b)| b).unwrap_or(&chunk.text);
+        for line in body.lines().filter(|l| !l.trim().is_empty()).take(3) {
+            println!("     {}", line.chars().take(120).collect::<String>().dimmed());
+        }
+    }
+}
@@ -24,6 +24,9 @@ pub struct MessageContent {
     pub content: Option<Vec<ContentBlock>>,
     pub model: Option<String>,
     pub usage: Option<Usage>,
+    /// Tool that produced a `toolResult` message
+    #[serde(rename = "toolName")]
+    pub tool_name: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,13 +40,15 @@ pub enum ContentBlock {
     ToolCall {
         #[serde(rename = "type")]
         block_type: String,
-        name: Option<String>,
+        name: String,
+        #[serde(alias = "arguments")]
         input: Option<serde_json::Value>,
     },
     ToolResult {
         #[serde(rename = "type")]
         block_type: String,
-        content: Option<String>,
+        /// A string, or a list of content blocks
+        content: serde_json::Value,
     },
     Other(serde_json::Value),
 }
@@ -73,7 +78,7 @@ pub struct Cost {
 }
 
 /// Aggregated token statistics
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct TokenStats {
     pub input_tokens: u64,
     pub output_tokens: u64,
@@ -110,6 +115,8 @@ pub struct ModelStats {
 #[derive(Debug)]
 pub struct Session {
     pub id: String,
+    /// Agent that owns the session (from its `agents/<name>/sessions` directory)
+    pub agent: String,
     pub messages: Vec<SessionMessage>,
     pub first_timestamp: Option<DateTime<Utc>>,
     pub last_timestamp: Option<DateTime<Utc>>,
@@ -198,6 +205,7 @@ impl Session {
 
         Ok(Self {
             id,
+            agent: crate::agent_from_path(path),
             messages,
             first_timestamp,
             last_timestamp,
@@ -252,11 +260,11 @@ impl Session {
                 })
                 .unwrap_or_else(|| "unknown-date".to_string());
 
-            let short_id = if self.id.len() >= 8 { &self.id[..8] } else { &self.id };
-            let text = format!("[{} | {}]\n\n{}", date, short_id, body_text);
+            let text = format!("[{} | {}]\n\n{}", date, short_id(&self.id), body_text);
 
             chunks.push(TextChunk {
                 session_id: self.id.clone(),
+                agent: self.agent.clone(),
                 turn_start: i,
                 turn_end: end,
                 timestamp,
@@ -345,7 +353,7 @@ impl Session {
         Ok(text)
     }
 
-    fn extract_text_from_content(content: &MessageContent) -> String {
+    pub(crate) fn extract_text_from_content(content: &MessageContent) -> String {
         let Some(ref blocks) = content.content else {
             return String::new();
         };
@@ -372,8 +380,26 @@ pub struct Turn {
 #[derive(Debug, Clone)]
 pub struct TextChunk {
     pub session_id: String,
+    pub agent: String,
     pub turn_start: usize,
     pub turn_end: usize,
     pub timestamp: Option<String>,
     pub text: String,
 }
+
+impl TextChunk {
+    /// Stable chunk id (`<session>:<turn_start>-<turn_end>`), unchanged across re-embeds
+    pub fn id(&self) -> String {
+        chunk_id(&self.session_id, self.turn_start, self.turn_end)
+    }
+}
+
+/// Build the stable id for a chunk covering `[turn_start, turn_end)` of a session
+pub fn chunk_id(session_id: &str, turn_start: usize, turn_end: usize) -> String {
+    format!("{}:{}-{}", session_id, turn_start, turn_end)
+}
+
+/// First 8 characters of a session (or action item) id, for display and citations
+pub fn short_id(session_id: &str) -> &str {
+    session_id.get(..8).unwrap_or(session_id)
+}
@@ -5,6 +5,7 @@
 use anyhow::Result;
 use chrono::{NaiveDate, Utc};
 use colored::Colorize;
+use serde::Serialize;
 use std::collections::HashMap;
 use walkdir::WalkDir;
 
@@ -12,41 +13,54 @@ use crate::session::{Session, TokenStats};
 use crate::Paths;
 
 /// Aggregated stats across all sessions
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct AggregatedStats {
     pub total: TokenStats,
     pub by_model: HashMap<String, TokenStats>,
     pub by_date: HashMap<NaiveDate, TokenStats>,
+    pub by_agent: HashMap<String, TokenStats>,
     pub session_count: usize,
     pub date_range: Option<(NaiveDate, NaiveDate)>,
+    /// Each session's usage, in the order found
+    pub sessions: Vec<SessionUsage>,
+}
+
+/// Usage for one session
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionUsage {
+    pub id: String,
+    pub agent: String,
+    pub date: NaiveDate,
+    pub models: Vec<String>,
+    pub stats: TokenStats,
 }
 
 /// Configuration for stats command
+#[derive(Clone, Debug, Default)]
 pub struct StatsConfig {
     /// Only include sessions since this date
     pub since: Option<NaiveDate>,
     /// Only include sessions until this date
     pub until: Option<NaiveDate>,
-}
-
-impl Default for StatsConfig {
-    fn default() -> Self {
-        Self {
-            since: None,
-            until: None,
-        }
-    }
+    /// Only include this agent's sessions (all agents if None)
+    pub agent: Option<String>,
 }
 
 /// Collect stats from all sessions
 pub fn collect(paths: &Paths, config: StatsConfig) -> Result<AggregatedStats> {
     let mut stats = AggregatedStats::default();
 
-    for entry in WalkDir::new(&paths.sessions_dir)
-        .max_depth(1)
+    let entries = paths
+        .session_dirs(config.agent.as_deref())
         .into_iter()
-        .filter_map(|e| e.ok())
-    {
+        .flat_map(|agent| {
+            WalkDir::new(&agent.sessions_dir)
+                .max_depth(1)
+                .into_iter()
+                .filter_map(|e| e.ok())
+        });
+
+    for entry in entries {
         let path = entry.path();
 
         // Only .jsonl files
@@ -95,9 +109,22 @@ pub fn collect(paths: &Paths, config: StatsConfig) -> Result<AggregatedStats> {
         let date_stats = stats.by_date.entry(session_date).or_default();
         add_stats(date_stats, &session.token_stats);
 
+        // Aggregate by agent
+        let agent_stats = stats.by_agent.entry(session.agent.clone()).or_default();
+        add_stats(agent_stats, &session.token_stats);
+
         // Aggregate by model from individual messages (not session level)
         collect_per_model_stats(&session, &mut stats.by_model);
 
+        let mut models = session.models_used.clone();
+        models.sort();
+        stats.sessions.push(SessionUsage {
+            id: session.id.clone(),
+            agent: session.agent.clone(),
+            date: session_date,
+            models,
+            stats: session.token_stats.clone(),
+        });
         stats.session_count += 1;
     }
 
@@ -236,6 +263,24 @@ pub fn display(stats: &AggregatedStats) {
         println!();
     }
 
+    // By agent (only interesting with more than one)
+    if stats.by_agent.len() > 1 {
+        println!("{}", "Usage by Agent".bold());
+        let mut agents: Vec<_> = stats.by_agent.iter().collect();
+        agents.sort_by(|a, b| b.1.total_cost.partial_cmp(&a.1.total_cost).unwrap());
+
+        for (agent, agent_stats) in agents {
+            let pct = (agent_stats.total_cost / stats.total.total_cost * 100.0) as u32;
+            println!(
+                "  {:<30} {:>10} ({:>2}%)",
+                agent.cyan(),
+                format_cost(agent_stats.total_cost),
+                pct
+            );
+        }
+        println!();
+    }
+
     // Recent daily trend (last 7 days)
     if stats.by_date.len() > 1 {
         println!("{}", "Recent Daily Cost".bold());
@@ -257,7 +302,7 @@ pub fn display(stats: &AggregatedStats) {
     }
 }
 
-fn format_tokens(tokens: u64) -> String {
+pub(crate) fn format_tokens(tokens: u64) -> String {
     if tokens >= 1_000_000 {
         format!("{:.2}M", tokens as f64 / 1_000_000.0)
     } else if tokens >= 1_000 {
@@ -267,6 +312,6 @@ fn format_tokens(tokens: u64) -> String {
     }
 }
 
-fn format_cost(cost: f64) -> String {
+pub(crate) fn format_cost(cost: f64) -> String {
     format!("${:.4}", cost)
 }
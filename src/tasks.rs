@@ -0,0 +1,278 @@
+//! Tasks - action items for todo.txt and calendar apps
+//!
+//! Exports harvested action items as todo.txt lines or iCalendar VTODOs.
+//! Each task carries the item's stable id (see `todo::item_id`) so reruns
+//! update tasks instead of adding them again: todo.txt lines are matched by
+//! their `profundo:<id>` tag and updated in place, and VTODOs use the id as
+//! their UID, which calendar apps deduplicate on.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::adr::file_url;
+use crate::export::{self, ExportScope, ExportStats, Exporter};
+use crate::todo::{self, ActionStatus};
+use crate::Paths;
+
+/// Longest iCalendar content line in octets, before folding
+const ICS_LINE_OCTETS: usize = 75;
+
+/// One action item as a task
+struct Task<'a> {
+    id: String,
+    text: &'a str,
+    status: ActionStatus,
+    /// `YYYY-MM-DD` of the session that raised it
+    date: &'a str,
+    /// When it was first harvested, if tracked
+    created_at: Option<DateTime<Utc>>,
+    /// When it was closed
+    closed_at: Option<DateTime<Utc>>,
+    agent: &'a str,
+    topics: &'a [String],
+    session_id: &'a str,
+    summary: &'a str,
+    /// The source session's log
+    source: Option<PathBuf>,
+}
+
+/// Action items of the learnings in scope, oldest first
+fn collect_tasks<'a>(
+    paths: &Paths,
+    scope: &ExportScope<'a>,
+    tracked: &'a HashMap<String, todo::ActionItem>,
+) -> Vec<Task<'a>> {
+    let mut learnings: Vec<_> = scope.learnings.iter().filter(|l| !l.action_items.is_empty()).collect();
+    learnings.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.session_id.cmp(&b.session_id)));
+
+    let mut tasks = Vec::new();
+    for learning in learnings {
+        let source = paths.find_session_file(&learning.session_id);
+        for text in learning.action_items.iter().filter(|t| !t.trim().is_empty()) {
+            let id = todo::item_id(&learning.session_id, text);
+            let item = tracked.get(&id);
+            tasks.push(Task {
+                text,
+                status: item.map(|i| i.status).unwrap_or(ActionStatus::Open),
+                date: &learning.date,
+                created_at: item.and_then(|i| timestamp(&i.created_at)),
+                closed_at: item.and_then(|i| i.closed_at.as_deref()).and_then(timestamp),
+                agent: &learning.agent,
+                topics: &learning.topics,
+                session_id: &learning.session_id,
+                summary: &learning.summary,
+                source: source.clone(),
+                id,
+            });
+        }
+    }
+    tasks
+}
+
+fn task_stats(scope: &ExportScope, tasks: &[Task]) -> ExportStats {
+    ExportStats {
+        sessions: scope.learnings.iter().filter(|l| !l.action_items.is_empty()).count(),
+        actions: tasks.len(),
+        ..Default::default()
+    }
+}
+
+/// `todo.txt`: one line per action item, merged into an existing file
+pub struct TodoTxtExporter;
+
+impl Exporter for TodoTxtExporter {
+    fn default_path(&self, paths: &Paths) -> PathBuf {
+        paths.memory_dir.join("todo.txt")
+    }
+
+    /// Lines already tagged with an item's id are replaced where they are,
+    /// except that a task the user completed stays completed. Other lines
+    /// are kept, and new items are appended.
+    fn export(&self, paths: &Paths, scope: &ExportScope, output: &Path) -> Result<ExportStats> {
+        let tracked = export::load_action_items(paths)?;
+        let tasks = collect_tasks(paths, scope, &tracked);
+        let mut stats = task_stats(scope, &tasks);
+        if tasks.is_empty() {
+            return Ok(stats);
+        }
+
+        let mut lines: Vec<(String, String)> = tasks.iter().map(|t| (t.id.clone(), todotxt_line(t))).collect();
+        let existing = if output.exists() {
+            fs::read_to_string(output).with_context(|| format!("Failed to read {}", output.display()))?
+        } else {
+            String::new()
+        };
+
+        let mut content = String::new();
+        for line in existing.lines() {
+            let id = line.split_whitespace().find_map(|token| token.strip_prefix("profundo:"));
+            match id.and_then(|id| lines.iter().position(|(task_id, _)| task_id == id)) {
+                Some(i) => {
+                    let (_, generated) = lines.remove(i);
+                    let completed_by_user = line.starts_with("x ") && !generated.starts_with("x ");
+                    content.push_str(if completed_by_user { line } else { &generated });
+                }
+                None => content.push_str(line),
+            }
+            content.push('\n');
+        }
+        for (_, line) in lines {
+            content.push_str(&line);
+            content.push('\n');
+        }
+
+        if content == existing {
+            stats.unchanged = 1;
+        } else {
+            export::write_atomic(output, &content)?;
+        }
+        Ok(stats)
+    }
+}
+
+/// `x 2026-10-18 2026-10-06 Write migration +billing @main profundo:<id> session:<id> file:///...`
+fn todotxt_line(task: &Task) -> String {
+    let created = task
+        .created_at
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| task.date.to_string());
+    let mut parts = Vec::new();
+    if task.status != ActionStatus::Open {
+        let closed = task.closed_at.map(|t| t.format("%Y-%m-%d").to_string());
+        parts.push(format!("x {}", closed.as_deref().unwrap_or(&created)));
+    }
+    parts.push(created);
+    parts.push(task.text.split_whitespace().collect::<Vec<_>>().join(" "));
+
+    let mut projects: Vec<String> = task.topics.iter().map(|t| tag(t)).filter(|t| !t.is_empty()).collect();
+    projects.dedup();
+    parts.extend(projects.into_iter().map(|p| format!("+{}", p)));
+    parts.push(format!("@{}", tag(task.agent)));
+
+    parts.push(format!("profundo:{}", task.id));
+    parts.push(format!("session:{}", task.session_id));
+    if task.status == ActionStatus::Dropped {
+        parts.push("status:dropped".to_string());
+    }
+    if let Some(source) = &task.source {
+        parts.push(file_url(source));
+    }
+    parts.join(" ")
+}
+
+/// A topic or agent as a todo.txt project or context: no spaces
+fn tag(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+/// `action-items.ics`: a calendar of VTODOs
+pub struct IcsExporter;
+
+impl Exporter for IcsExporter {
+    fn default_path(&self, paths: &Paths) -> PathBuf {
+        paths.memory_dir.join("action-items.ics")
+    }
+
+    fn export(&self, paths: &Paths, scope: &ExportScope, output: &Path) -> Result<ExportStats> {
+        let tracked = export::load_action_items(paths)?;
+        let tasks = collect_tasks(paths, scope, &tracked);
+        let stats = task_stats(scope, &tasks);
+        if tasks.is_empty() {
+            return Ok(stats);
+        }
+
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//Profundo//Action Items//EN".to_string(),
+            "CALSCALE:GREGORIAN".to_string(),
+        ];
+        for task in &tasks {
+            let created = task.created_at.map(ics_timestamp).unwrap_or_else(|| ics_date(task.date));
+            let closed = task.closed_at.map(ics_timestamp);
+
+            lines.push("BEGIN:VTODO".to_string());
+            lines.push(format!("UID:{}@profundo", task.id));
+            // Changes when the item is closed, so calendar apps pick up the update
+            lines.push(format!("DTSTAMP:{}", closed.as_deref().unwrap_or(&created)));
+            lines.push(format!("CREATED:{}", created));
+            lines.push(format!("SUMMARY:{}", ics_text(task.text)));
+            lines.push(format!(
+                "STATUS:{}",
+                match task.status {
+                    ActionStatus::Open => "NEEDS-ACTION",
+                    ActionStatus::Done => "COMPLETED",
+                    ActionStatus::Dropped => "CANCELLED",
+                }
+            ));
+            if task.status == ActionStatus::Done {
+                if let Some(closed) = &closed {
+                    lines.push(format!("COMPLETED:{}", closed));
+                }
+            }
+            if !task.topics.is_empty() {
+                let categories: Vec<String> = task.topics.iter().map(|t| ics_text(t)).collect();
+                lines.push(format!("CATEGORIES:{}", categories.join(",")));
+            }
+            let mut description = format!("From session {} ({}, agent {})", task.session_id, task.date, task.agent);
+            if !task.summary.is_empty() {
+                description.push_str(&format!(": {}", task.summary));
+            }
+            lines.push(format!("DESCRIPTION:{}", ics_text(&description)));
+            if let Some(source) = &task.source {
+                lines.push(format!("URL:{}", file_url(source)));
+            }
+            lines.push("END:VTODO".to_string());
+        }
+        lines.push("END:VCALENDAR".to_string());
+
+        let content: String = lines.iter().map(|line| fold(line) + "\r\n").collect();
+        fs::write(output, content).context("Failed to write export file")?;
+        Ok(stats)
+    }
+}
+
+fn timestamp(rfc3339: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(rfc3339).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// An iCalendar UTC date-time
+fn ics_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Midnight UTC of a `YYYY-MM-DD` date (or the epoch if it doesn't parse)
+fn ics_date(date: &str) -> String {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|d| d.format("%Y%m%dT000000Z").to_string())
+        .unwrap_or_else(|_| "19700101T000000Z".to_string())
+}
+
+/// Escape a TEXT value
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+/// Fold a content line into chunks of at most 75 octets, continuation lines
+/// starting with a space, without splitting a character
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / ICS_LINE_OCTETS * 3);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > ICS_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space counts toward the continuation line
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
@@ -0,0 +1,411 @@
+//! Templates - user-editable layouts for rollups and markdown export
+//!
+//! Rollup sections and `learnings.md` are rendered with Jinja-style templates
+//! (the syntax Tera also uses). Built-in templates reproduce the default
+//! layout; any file in `memory/.profundo/templates/` replaces the built-in
+//! of the same name, and other `.md` files there can be included or
+//! imported from them.
+//!
+//! | Template      | Renders                                  | Context |
+//! |---------------|------------------------------------------|---------|
+//! | `rollup.md`   | A daily log's `## Profundo` section body | `date`, `weekday`, `agent`, `stats`, `sessions`, `learnings` |
+//! | `export.md`   | `learnings.md`                           | `stats`, `consolidated`, `canonical`, `days` |
+//! | `learning.md` | One session's bullets (included by both) | `learning` |
+//! | `stats.md`    | The usage line (included by `rollup.md`) | `stats` |
+//!
+//! Every context also has `default_agent`. Two filters are added to the
+//! built-in ones: `bullet(kind)` formats an item with its category's export
+//! format, and `fixed(digits)` formats a number with that many decimals.
+//! `learning.md` also heads a markdown transcript from `profundo show`.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
+use minijinja::Environment;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::categories::{self, CategorySet};
+use crate::consolidate::CanonicalItem;
+use crate::export::{self, ExportStats};
+use crate::harvest::Learning;
+use crate::session::{short_id, TokenStats};
+use crate::stats::SessionUsage;
+use crate::todo::{self, ActionItem};
+use crate::Paths;
+
+const LEARNING: &str = r#"{% if learning.topics %}
+- **Topics**: {{ learning.topics | join(", ") }}
+{% endif %}
+{% if learning.summary %}
+- {{ learning.summary }}
+{% endif %}
+{% for decision in learning.decisions %}
+{{ decision | bullet("decisions") }}
+{% endfor %}
+{% for fact in learning.facts %}
+{{ fact | bullet("facts_learned") }}
+{% endfor %}
+{% for action in learning.action_items %}
+{{ action.line }}
+{% endfor %}
+{% for category in learning.categories %}
+{% for item in category.items %}
+{{ item | bullet(category.name) }}
+{% endfor %}
+{% endfor %}
+"#;
+
+const STATS: &str = r#"**Tokens**: {{ (stats.input_tokens / 1000) | fixed(1) }}K in / {{ (stats.output_tokens / 1000) | fixed(1) }}K out | **Cache**: {{ (stats.cache_hit_rate * 100) | fixed(0) }}% | **Cost**: ${{ stats.total_cost | fixed(4) }}
+"#;
+
+const ROLLUP: &str = r#"{% if stats %}
+{% include "stats.md" %} ({{ stats.sessions }} sessions)
+
+{% endif %}
+{% for learning in learnings %}
+### Session `{{ learning.short_id }}`{{ " (" ~ learning.agent ~ ")" if learning.agent != default_agent }}
+
+{% include "learning.md" %}
+
+{% else %}
+_No learnings harvested for this date._
+{% endfor %}
+"#;
+
+const EXPORT: &str = r#"# Profundo Learnings
+
+Extracted insights from conversation sessions.
+
+{% if canonical.facts %}
+## Known Facts
+
+{% for item in canonical.facts %}
+- {{ item.text }} _({{ item.sessions }} sessions, last {{ item.last_seen }})_
+{% endfor %}
+
+{% endif %}
+{% if canonical.decisions %}
+## Decisions
+
+{% for item in canonical.decisions %}
+- {{ item.text }} _({{ item.sessions }} sessions, last {{ item.last_seen }})_
+{% endfor %}
+
+{% endif %}
+{% if canonical.superseded %}
+## Superseded
+
+{% for item in canonical.superseded %}
+- ~~{{ item.text }}~~ ({{ item.last_seen }}) → {{ item.superseded_by or "a later entry" }}
+{% endfor %}
+
+{% endif %}
+{% for day in days %}
+## {{ day.date }}
+
+{% for learning in day.learnings %}
+{% include "learning.md" %}
+
+{% endfor %}
+{% endfor %}
+"#;
+
+/// Built-in templates by name
+pub const BUILTIN: &[(&str, &str)] = &[
+    ("rollup.md", ROLLUP),
+    ("export.md", EXPORT),
+    ("learning.md", LEARNING),
+    ("stats.md", STATS),
+];
+
+/// Loaded templates, built-ins overridden by the user's
+pub struct Templates {
+    env: Environment<'static>,
+}
+
+impl Templates {
+    /// Built-in templates plus any in `paths.templates_dir`
+    pub fn load(paths: &Paths) -> Result<Self> {
+        let categories = CategorySet::load(&paths.categories_path)?;
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.add_filter("bullet", move |item: String, kind: String| categories.format_item(&kind, &item));
+        env.add_filter("fixed", |value: f64, digits: usize| format!("{:.*}", digits, value));
+
+        for (name, source) in BUILTIN {
+            env.add_template(name, source)?;
+        }
+        for (name, source) in user_templates(&paths.templates_dir)? {
+            env.add_template_owned(name.clone(), source)
+                .map_err(|e| anyhow!("Invalid template {}: {}", name, e))?;
+        }
+
+        Ok(Self { env })
+    }
+
+    fn render<S: Serialize>(&self, name: &str, context: S) -> Result<String> {
+        self.env
+            .get_template(name)?
+            .render(context)
+            .map_err(|e| anyhow!("Failed to render template {}: {}", name, e))
+    }
+
+    /// Body of a daily log's Profundo section
+    pub fn rollup(&self, context: &RollupContext) -> Result<String> {
+        self.render("rollup.md", context)
+    }
+
+    /// Contents of `learnings.md`
+    pub fn export(&self, context: &ExportContext) -> Result<String> {
+        self.render("export.md", context)
+    }
+
+    /// One session's bullets, for a markdown transcript
+    pub fn learning(&self, learning: &LearningView) -> Result<String> {
+        self.render("learning.md", minijinja::context! { learning, default_agent => crate::DEFAULT_AGENT })
+    }
+}
+
+/// `.md` files in the templates directory, by file name
+pub fn user_templates(dir: &Path) -> Result<Vec<(String, String)>> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut templates = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.ends_with(".md") {
+            continue;
+        }
+        let source = fs::read_to_string(entry.path())
+            .with_context(|| format!("Failed to read template {}", entry.path().display()))?;
+        templates.push((name, source));
+    }
+    templates.sort();
+    Ok(templates)
+}
+
+/// Token usage, with the cache hit rate and session count
+#[derive(Serialize)]
+pub struct StatsView<'a> {
+    #[serde(flatten)]
+    pub tokens: &'a TokenStats,
+    pub cache_hit_rate: f64,
+    pub sessions: usize,
+}
+
+impl<'a> StatsView<'a> {
+    pub fn new(tokens: &'a TokenStats, sessions: usize) -> Self {
+        Self {
+            tokens,
+            cache_hit_rate: tokens.cache_hit_rate(),
+            sessions,
+        }
+    }
+}
+
+/// One session's learning
+#[derive(Serialize)]
+pub struct LearningView<'a> {
+    pub session_id: &'a str,
+    /// First 8 characters of the session id
+    pub short_id: &'a str,
+    pub agent: &'a str,
+    pub date: &'a str,
+    pub summary: &'a str,
+    pub topics: &'a [String],
+    pub decisions: Vec<&'a str>,
+    pub facts: Vec<&'a str>,
+    pub action_items: Vec<ActionView<'a>>,
+    /// User-defined categories with items, configured ones first
+    pub categories: Vec<CategoryView<'a>>,
+    /// The session's usage, when its log was found
+    pub usage: Option<&'a SessionUsage>,
+}
+
+/// An action item and its tracked status
+#[derive(Serialize)]
+pub struct ActionView<'a> {
+    pub id: String,
+    pub text: &'a str,
+    /// `open`, `done` or `dropped`
+    pub status: &'static str,
+    /// Date it was closed, for done and dropped items
+    pub closed_on: Option<&'a str>,
+    /// The default bullet: a checkbox, ticked when done, struck through when dropped
+    pub line: String,
+}
+
+/// Items in one user-defined category
+#[derive(Serialize)]
+pub struct CategoryView<'a> {
+    pub name: &'a str,
+    pub label: String,
+    pub items: &'a [String],
+}
+
+impl<'a> LearningView<'a> {
+    pub fn new(
+        learning: &'a Learning,
+        categories: &CategorySet,
+        action_items: &'a HashMap<String, ActionItem>,
+        usage: Option<&'a SessionUsage>,
+    ) -> Self {
+        let action_items = learning
+            .action_items
+            .iter()
+            .map(|text| {
+                let id = todo::item_id(&learning.session_id, text);
+                let tracked = action_items.get(&id);
+                ActionView {
+                    status: tracked.map(|i| i.status.as_str()).unwrap_or("open"),
+                    closed_on: tracked.and_then(|i| i.closed_on()),
+                    line: export::format_action_item(text, tracked, categories),
+                    id,
+                    text,
+                }
+            })
+            .collect();
+
+        let configured = categories.custom().map(|c| c.name.as_str());
+        let unconfigured = learning
+            .categories
+            .keys()
+            .map(|k| k.as_str())
+            .filter(|k| !categories.custom().any(|c| c.name == *k));
+        let custom = configured
+            .chain(unconfigured)
+            .filter_map(|name| learning.categories.get_key_value(name))
+            .filter(|(_, items)| !items.is_empty())
+            .map(|(name, items)| CategoryView {
+                name,
+                label: categories::label(name),
+                items,
+            })
+            .collect();
+
+        Self {
+            session_id: &learning.session_id,
+            short_id: short_id(&learning.session_id),
+            agent: &learning.agent,
+            date: &learning.date,
+            summary: &learning.summary,
+            topics: &learning.topics,
+            decisions: learning.decisions.iter().map(String::as_str).collect(),
+            facts: learning.facts_learned.iter().map(String::as_str).collect(),
+            action_items,
+            categories: custom,
+            usage,
+        }
+    }
+}
+
+/// Context for `rollup.md`
+#[derive(Serialize)]
+pub struct RollupContext<'a> {
+    pub date: NaiveDate,
+    pub weekday: String,
+    /// Agent the rollup is limited to
+    pub agent: Option<&'a str>,
+    /// The day's usage, when it had sessions
+    pub stats: Option<StatsView<'a>>,
+    /// Each of the day's sessions
+    pub sessions: &'a [&'a SessionUsage],
+    pub learnings: Vec<LearningView<'a>>,
+    pub default_agent: &'static str,
+}
+
+/// Context for `export.md`
+#[derive(Serialize)]
+pub struct ExportContext<'a> {
+    pub stats: &'a ExportStats,
+    /// Whether there are consolidated entries; if so, `canonical` lists them
+    /// and the learnings under `days` leave out the facts and decisions they
+    /// cover (items consolidation hasn't seen are still listed per session)
+    pub consolidated: bool,
+    pub canonical: CanonicalView<'a>,
+    /// Newest first
+    pub days: Vec<DayView<'a>>,
+    pub default_agent: &'static str,
+}
+
+/// Consolidated facts and decisions
+#[derive(Serialize, Default)]
+pub struct CanonicalView<'a> {
+    /// Current facts, most recently seen first
+    pub facts: Vec<CanonicalEntry<'a>>,
+    /// Current decisions, most recently seen first
+    pub decisions: Vec<CanonicalEntry<'a>>,
+    pub superseded: Vec<CanonicalEntry<'a>>,
+}
+
+/// One consolidated entry
+#[derive(Serialize)]
+pub struct CanonicalEntry<'a> {
+    pub kind: &'a str,
+    pub text: &'a str,
+    /// Number of sessions it came from
+    pub sessions: usize,
+    pub first_seen: &'a str,
+    pub last_seen: &'a str,
+    /// Text of the entry that reverses it
+    pub superseded_by: Option<&'a str>,
+}
+
+impl<'a> CanonicalView<'a> {
+    pub fn new(items: &'a [CanonicalItem]) -> Self {
+        let entry = |item: &'a CanonicalItem| CanonicalEntry {
+            kind: &item.kind,
+            text: &item.text,
+            sessions: item.sources.len(),
+            first_seen: &item.first_seen,
+            last_seen: &item.last_seen,
+            superseded_by: item
+                .superseded_by
+                .and_then(|id| items.iter().find(|i| i.id == id))
+                .map(|i| i.text.as_str()),
+        };
+        let current = |kind: &str| {
+            let mut current: Vec<&CanonicalItem> = items
+                .iter()
+                .filter(|i| i.kind == kind && i.superseded_by.is_none())
+                .collect();
+            current.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+            current.into_iter().map(entry).collect()
+        };
+
+        Self {
+            facts: current("facts_learned"),
+            decisions: current("decisions"),
+            superseded: items.iter().filter(|i| i.superseded_by.is_some()).map(entry).collect(),
+        }
+    }
+}
+
+/// One day's learnings
+#[derive(Serialize)]
+pub struct DayView<'a> {
+    pub date: &'a str,
+    pub learnings: Vec<LearningView<'a>>,
+}
+
+/// Write the built-in templates into the templates directory, skipping any
+/// that already exist. Returns the names written.
+pub fn init(paths: &Paths) -> Result<Vec<&'static str>> {
+    fs::create_dir_all(&paths.templates_dir)
+        .with_context(|| format!("Failed to create {}", paths.templates_dir.display()))?;
+
+    let mut written = Vec::new();
+    for (name, source) in BUILTIN {
+        let path = paths.templates_dir.join(name);
+        if !path.exists() {
+            fs::write(&path, source).with_context(|| format!("Failed to write {}", path.display()))?;
+            written.push(*name);
+        }
+    }
+    Ok(written)
+}
@@ -0,0 +1,193 @@
+//! Todo - action item lifecycle
+//!
+//! Every harvested action item gets a stable id (derived from its session and
+//! text) and a status. Items start open and are closed with `profundo todo`,
+//! or by harvest when a later session clearly finishes the task.
+
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDate, Utc};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::session::short_id;
+
+/// Where an action item is in its lifecycle
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionStatus {
+    /// Still to do
+    Open,
+    /// Finished
+    Done,
+    /// No longer going to happen
+    Dropped,
+}
+
+impl ActionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionStatus::Open => "open",
+            ActionStatus::Done => "done",
+            ActionStatus::Dropped => "dropped",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "open" => Ok(ActionStatus::Open),
+            "done" => Ok(ActionStatus::Done),
+            "dropped" => Ok(ActionStatus::Dropped),
+            other => Err(anyhow!("Unknown action item status '{}'", other)),
+        }
+    }
+}
+
+/// A tracked action item
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionItem {
+    /// Stable id, see `item_id`
+    pub id: String,
+    pub session_id: String,
+    pub agent: String,
+    pub text: String,
+    /// Date of the session that raised it
+    pub date: String,
+    pub status: ActionStatus,
+    /// When it was first harvested
+    pub created_at: String,
+    /// When it was marked done or dropped
+    pub closed_at: Option<String>,
+    /// Session that finished it, when harvest detected the completion
+    pub closed_by: Option<String>,
+    /// Why it was closed, if given
+    pub note: Option<String>,
+}
+
+impl ActionItem {
+    /// Short id for display and prompts, also accepted as a prefix
+    pub fn short_id(&self) -> &str {
+        short_id(&self.id)
+    }
+
+    /// `YYYY-MM-DD` it was marked done or dropped
+    pub fn closed_on(&self) -> Option<&str> {
+        self.closed_at.as_deref().map(|t| &t[..10.min(t.len())])
+    }
+
+    /// Days since the session that raised it (None if its date is unknown)
+    pub fn age_days(&self) -> Option<i64> {
+        NaiveDate::parse_from_str(&self.date, "%Y-%m-%d")
+            .ok()
+            .map(|date| (Utc::now().date_naive() - date).num_days())
+    }
+}
+
+/// Stable id for an action item: a hash of its session and text, ignoring
+/// case and whitespace, so re-harvesting the same item keeps its status
+pub fn item_id(session_id: &str, text: &str) -> String {
+    let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    let hash = crate::embed::fnv1a(format!("{}\n{}", session_id, normalized).as_bytes());
+    format!("{:016x}", hash)
+}
+
+/// Filters for listing action items
+#[derive(Clone, Debug, Default)]
+pub struct TodoFilter {
+    /// Only items with this status (all if None)
+    pub status: Option<ActionStatus>,
+    /// Only this agent's items
+    pub agent: Option<String>,
+    /// Only items raised at least this many days ago
+    pub older_than: Option<i64>,
+    /// Only items raised at most this many days ago
+    pub newer_than: Option<i64>,
+}
+
+impl TodoFilter {
+    pub fn matches(&self, item: &ActionItem) -> bool {
+        if self.status.map(|s| s != item.status).unwrap_or(false) {
+            return false;
+        }
+        if self.agent.as_deref().map(|a| a != item.agent).unwrap_or(false) {
+            return false;
+        }
+        if self.older_than.is_none() && self.newer_than.is_none() {
+            return true;
+        }
+
+        match item.age_days() {
+            Some(age) => {
+                self.older_than.map(|d| age >= d).unwrap_or(true)
+                    && self.newer_than.map(|d| age <= d).unwrap_or(true)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Find the one item whose id starts with `prefix`
+pub fn resolve<'a>(items: &'a [ActionItem], prefix: &str) -> Result<&'a ActionItem> {
+    let matches: Vec<&ActionItem> = items.iter().filter(|i| i.id.starts_with(prefix)).collect();
+    match matches.as_slice() {
+        [item] => Ok(item),
+        [] => Err(anyhow!("No action item with id '{}'", prefix)),
+        _ => Err(anyhow!(
+            "Action item id '{}' is ambiguous ({} matches); use more characters",
+            prefix,
+            matches.len()
+        )),
+    }
+}
+
+/// Print action items, oldest first
+pub fn display(items: &[ActionItem]) {
+    for item in items {
+        let age = item
+            .age_days()
+            .map(|d| format!("{}d", d))
+            .unwrap_or_else(|| "?".to_string());
+        let meta = if item.agent == crate::DEFAULT_AGENT {
+            format!("{} ({})", item.date, age)
+        } else {
+            format!("{} ({}, {})", item.date, age, item.agent)
+        };
+
+        match item.status {
+            ActionStatus::Open => println!(
+                "  {} {} {} {}",
+                "○".cyan(),
+                item.short_id().dimmed(),
+                item.text,
+                meta.dimmed()
+            ),
+            ActionStatus::Done => println!(
+                "  {} {} {} {}",
+                "✓".green(),
+                item.short_id().dimmed(),
+                item.text.dimmed(),
+                meta.dimmed()
+            ),
+            ActionStatus::Dropped => println!(
+                "  {} {} {} {}",
+                "✗".red(),
+                item.short_id().dimmed(),
+                item.text.strikethrough().dimmed(),
+                meta.dimmed()
+            ),
+        }
+
+        if item.status != ActionStatus::Open {
+            let closed = item.closed_on().unwrap_or("?");
+            let by = item
+                .closed_by
+                .as_deref()
+                .map(|s| format!(" by session {}", short_id(s)))
+                .unwrap_or_default();
+            let note = item.note.as_deref().map(|n| format!(": {}", n)).unwrap_or_default();
+            println!(
+                "      {}",
+                format!("{} {}{}{}", item.status.as_str(), closed, by, note).dimmed()
+            );
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Trace - where in a session each harvested item came from
+//!
+//! Harvest labels the transcript with turn numbers and asks the model to cite
+//! the turns (and a short quote) behind every decision, fact and action item.
+//! `profundo trace` finds an item and prints those turns from the session.
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::categories;
+use crate::harvest::Learning;
+use crate::session::{short_id, Session};
+use crate::Paths;
+
+/// Learning fields whose items carry provenance
+pub const TRACED: &[&str] = &["decisions", "facts_learned", "action_items"];
+
+/// The turns a decision, fact or action item was taken from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Learning field the item is in (one of `TRACED`)
+    pub kind: String,
+    /// The item's text
+    pub text: String,
+    /// First supporting turn, as numbered by `Session::get_turns`
+    pub turn_start: usize,
+    /// One past the last supporting turn
+    pub turn_end: usize,
+    /// Short verbatim excerpt, when the model gave one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote: Option<String>,
+}
+
+impl Provenance {
+    /// Citation label in the same `<session>:<start>-<end>` form as chunk ids
+    pub fn label(&self, session_id: &str) -> String {
+        format!("{}:{}-{}", short_id(session_id), self.turn_start, self.turn_end)
+    }
+}
+
+/// Build provenance from a model-supplied `turns` value: `[first, last]`,
+/// a single number, or a list of numbers
+pub fn from_turns(kind: &str, text: &str, turns: &serde_json::Value, quote: Option<&str>) -> Option<Provenance> {
+    let numbers: Vec<usize> = match turns {
+        serde_json::Value::Number(n) => n.as_u64().map(|n| vec![n as usize]).unwrap_or_default(),
+        serde_json::Value::Array(items) => items.iter().filter_map(|n| n.as_u64()).map(|n| n as usize).collect(),
+        _ => Vec::new(),
+    };
+    let (first, last) = (*numbers.iter().min()?, *numbers.iter().max()?);
+
+    Some(Provenance {
+        kind: kind.to_string(),
+        text: text.to_string(),
+        turn_start: first,
+        turn_end: last + 1,
+        quote: quote.map(str::trim).filter(|q| !q.is_empty()).map(str::to_string),
+    })
+}
+
+/// Provenance for a (possibly reworded) item from earlier provenance of the
+/// same kind: an exact match if there is one, else the closest by shared words
+pub fn match_provenance(kind: &str, text: &str, candidates: &[Provenance]) -> Option<Provenance> {
+    let key = normalize(text);
+    let same_kind = || candidates.iter().filter(|p| p.kind == kind);
+
+    let found = same_kind().find(|p| normalize(&p.text) == key).or_else(|| {
+        let words = word_set(text);
+        same_kind()
+            .map(|p| (p, jaccard(&words, &word_set(&p.text))))
+            .filter(|(_, score)| *score >= 0.5)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(p, _)| p)
+    })?;
+
+    Some(Provenance {
+        text: text.to_string(),
+        ..found.clone()
+    })
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn word_set(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f32 / union as f32
+}
+
+/// A harvested item matching a trace query
+pub struct TraceMatch<'a> {
+    pub learning: &'a Learning,
+    pub kind: &'static str,
+    pub text: &'a str,
+    pub provenance: Option<&'a Provenance>,
+}
+
+/// Find decisions, facts and action items containing `query` (ignoring case
+/// and whitespace), optionally in one session only. An item matching the
+/// query exactly is preferred over partial matches.
+pub fn find<'a>(learnings: &'a [Learning], query: &str, session: Option<&str>) -> Vec<TraceMatch<'a>> {
+    let key = normalize(query);
+    let mut matches = Vec::new();
+
+    for learning in learnings {
+        if session.map(|s| !learning.session_id.starts_with(s)).unwrap_or(false) {
+            continue;
+        }
+        for &kind in TRACED {
+            let items = match kind {
+                "decisions" => &learning.decisions,
+                "facts_learned" => &learning.facts_learned,
+                _ => &learning.action_items,
+            };
+            for text in items.iter().filter(|t| normalize(t).contains(&key)) {
+                matches.push(TraceMatch {
+                    learning,
+                    kind,
+                    text,
+                    provenance: learning
+                        .provenance
+                        .iter()
+                        .find(|p| p.kind == kind && normalize(&p.text) == normalize(text)),
+                });
+            }
+        }
+    }
+
+    if let Some(i) = matches.iter().position(|m| normalize(m.text) == key) {
+        return vec![matches.swap_remove(i)];
+    }
+    matches
+}
+
+/// Print a matched item and the turns it came from, with `context` extra
+/// turns on each side
+pub fn display(paths: &Paths, found: &TraceMatch, context: usize) -> Result<()> {
+    let learning = found.learning;
+    println!(
+        "\n{} {}: {}",
+        "●".cyan(),
+        categories::label(found.kind).bold(),
+        found.text
+    );
+
+    let Some(provenance) = found.provenance else {
+        println!(
+            "  {} {}",
+            learning.date.dimmed(),
+            format!("session {} (no turn range recorded; harvested before provenance tracking)", learning.session_id)
+                .dimmed()
+        );
+        return Ok(());
+    };
+
+    println!(
+        "  {} {}",
+        learning.date.dimmed(),
+        provenance.label(&learning.session_id).dimmed()
+    );
+    if let Some(quote) = &provenance.quote {
+        println!("  {}", format!("\"{}\"", quote).italic());
+    }
+
+    let path = paths
+        .find_session_file(&learning.session_id)
+        .ok_or_else(|| anyhow!("Session file for {} not found", learning.session_id))?;
+    let text = Session::load_turn_range(&path, provenance.turn_start, provenance.turn_end, context)?;
+
+    println!("\n{}", "─".repeat(60).dimmed());
+    if text.is_empty() {
+        println!("{}", "(turns not found; the session file may have been rewritten)".dimmed());
+    } else {
+        let turns: Vec<&str> = text.split("\n\n---\n\n").collect();
+        println!("{}", turns.join(&format!("\n{}\n", "─".repeat(60).dimmed())));
+    }
+    println!("{}\n", "─".repeat(60).dimmed());
+
+    Ok(())
+}
+
+/// Print several matches so the user can narrow the query
+pub fn display_matches(matches: &[TraceMatch]) {
+    for found in matches {
+        println!(
+            "  {} {} {} {}",
+            found.learning.date.cyan(),
+            short_id(&found.learning.session_id).dimmed(),
+            format!("{}:", categories::label(found.kind)).dimmed(),
+            found.text
+        );
+    }
+}
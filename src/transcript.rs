@@ -0,0 +1,441 @@
+//! Transcript - one session, readable
+//!
+//! `profundo show` renders a session log as markdown or HTML: the session's
+//! learnings first, then every message with its time, and the model, tokens
+//! and cost of each assistant reply. Tool calls and their results are folded
+//! into `<details>` blocks, which browsers and most markdown viewers collapse.
+//! Turns are numbered as harvest numbers them, so `profundo trace` citations
+//! (`a1b2c3d4:3-5`) point at the same headings.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::categories::{self, CategorySet};
+use crate::db::Database;
+use crate::export;
+use crate::harvest::Learning;
+use crate::html::{escape, page};
+use crate::session::{ContentBlock, Session, Usage};
+use crate::stats::{format_cost, format_tokens};
+use crate::template::{LearningView, Templates};
+use crate::Paths;
+
+/// Output format for `profundo show`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TranscriptFormat {
+    /// Markdown, with tool calls in `<details>` blocks
+    #[default]
+    Markdown,
+    /// A self-contained HTML page
+    Html,
+}
+
+/// A rendered session
+pub struct Transcript {
+    pub session_id: String,
+    pub messages: usize,
+    pub content: String,
+}
+
+/// One message, ready to render
+struct Entry<'a> {
+    role: &'a str,
+    /// Set on the first shown message of a turn
+    turn: Option<usize>,
+    /// Time of day, with the date when it differs from the previous message's
+    when: Option<String>,
+    model: Option<&'a str>,
+    usage: Option<&'a Usage>,
+    parts: Vec<Part<'a>>,
+}
+
+enum Part<'a> {
+    Text(&'a str),
+    ToolCall { name: &'a str, input: Option<&'a serde_json::Value> },
+    ToolResult { name: Option<&'a str>, content: String },
+}
+
+/// Render a session, by id or unique id prefix
+pub fn render(paths: &Paths, target: &str, format: TranscriptFormat) -> Result<Transcript> {
+    let path = find_session(paths, target)?;
+    let session = Session::from_file(&path)?;
+
+    let db = Database::open_with_learnings(&paths.db_path, &paths.learnings_path)?;
+    let learning = db.load_learnings(None)?.into_iter().find(|l| l.session_id == session.id);
+
+    let entries = entries(&session);
+    let content = match format {
+        TranscriptFormat::Markdown => markdown(paths, &session, learning.as_ref(), &entries)?,
+        TranscriptFormat::Html => html(paths, &session, learning.as_ref(), &entries)?,
+    };
+
+    Ok(Transcript {
+        session_id: session.id.clone(),
+        messages: entries.len(),
+        content,
+    })
+}
+
+/// The log of the session with this id, or the only one whose id starts with it
+fn find_session(paths: &Paths, target: &str) -> Result<PathBuf> {
+    if let Some(path) = paths.find_session_file(target) {
+        return Ok(path);
+    }
+
+    let mut matches = Vec::new();
+    for agent in &paths.agents {
+        let Ok(dir) = fs::read_dir(&agent.sessions_dir) else {
+            continue;
+        };
+        for entry in dir.flatten() {
+            let path = entry.path();
+            let is_match = path.extension().map(|e| e == "jsonl").unwrap_or(false)
+                && path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(|s| s.starts_with(target))
+                    .unwrap_or(false);
+            if is_match {
+                matches.push(path);
+            }
+        }
+    }
+
+    match matches.len() {
+        1 => Ok(matches.remove(0)),
+        0 => Err(anyhow!("No session matches '{}'", target)),
+        n => Err(anyhow!("'{}' is ambiguous ({} sessions match); use a longer prefix", target, n)),
+    }
+}
+
+fn entries(session: &Session) -> Vec<Entry<'_>> {
+    let mut entries = Vec::new();
+    let mut turn: Option<usize> = None;
+    let mut heading: Option<usize> = None;
+    let mut last_date: Option<NaiveDate> = None;
+
+    for msg in &session.messages {
+        if msg.msg_type != "message" {
+            continue;
+        }
+        let Some(ref content) = msg.message else {
+            continue;
+        };
+        let Some(ref role) = content.role else {
+            continue;
+        };
+        let is_tool_result = matches!(role.as_str(), "toolResult" | "tool");
+
+        // Same numbering as harvest: a user message with text starts a turn,
+        // even if that text is only whitespace and so isn't shown; its
+        // heading then goes on the next entry that is
+        if role == "user" && !Session::extract_text_from_content(content).is_empty() {
+            turn = Some(turn.map(|t| t + 1).unwrap_or(0));
+            heading = turn;
+        }
+
+        let mut parts = Vec::new();
+        for block in content.content.iter().flatten() {
+            match block {
+                ContentBlock::Text { text, .. } if text.trim().is_empty() => {}
+                ContentBlock::Text { text, .. } if is_tool_result => parts.push(Part::ToolResult {
+                    name: content.tool_name.as_deref(),
+                    content: text.clone(),
+                }),
+                ContentBlock::Text { text, .. } => parts.push(Part::Text(text)),
+                ContentBlock::ToolCall { name, input, .. } => parts.push(Part::ToolCall {
+                    name,
+                    input: input.as_ref(),
+                }),
+                ContentBlock::ToolResult { content: result, .. } => parts.push(Part::ToolResult {
+                    name: None,
+                    content: result_text(result),
+                }),
+                // Thinking, images and anything else
+                ContentBlock::Other(_) => {}
+            }
+        }
+        if parts.is_empty() {
+            continue;
+        }
+
+        let timestamp = msg
+            .timestamp
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc));
+        let when = timestamp.map(|t| {
+            if last_date == Some(t.date_naive()) {
+                t.format("%H:%M:%S").to_string()
+            } else {
+                last_date = Some(t.date_naive());
+                t.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+            }
+        });
+
+        entries.push(Entry {
+            role,
+            turn: heading.take(),
+            when,
+            model: content.model.as_deref(),
+            usage: content.usage.as_ref(),
+            parts,
+        });
+    }
+
+    entries
+}
+
+/// A tool result's content: a string, or the text of its content blocks
+fn result_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    }
+}
+
+fn role_label(role: &str) -> &str {
+    match role {
+        "user" => "User",
+        "assistant" => "Assistant",
+        "toolResult" | "tool" => "Tool",
+        "system" => "System",
+        other => other,
+    }
+}
+
+/// `claude-sonnet-4 · 12.3K in / 456 out · 10.0K cached · $0.0123`
+fn annotation(entry: &Entry) -> Vec<String> {
+    let mut notes: Vec<String> = entry.model.map(str::to_string).into_iter().collect();
+    if let Some(usage) = entry.usage {
+        notes.push(format!(
+            "{} in / {} out",
+            format_tokens(usage.input.unwrap_or(0)),
+            format_tokens(usage.output.unwrap_or(0))
+        ));
+        if let Some(read) = usage.cache_read.filter(|n| *n > 0) {
+            notes.push(format!("{} cached", format_tokens(read)));
+        }
+        if let Some(written) = usage.cache_write.filter(|n| *n > 0) {
+            notes.push(format!("{} cache writes", format_tokens(written)));
+        }
+        if let Some(total) = usage.cost.as_ref().and_then(|c| c.total) {
+            notes.push(format_cost(total));
+        }
+    }
+    notes
+}
+
+/// First and last message times, and the models used
+fn session_meta(session: &Session) -> Vec<(&'static str, String)> {
+    let mut meta = vec![("Agent", session.agent.clone())];
+    if let Some(first) = session.first_timestamp {
+        let mut when = first.format("%Y-%m-%d %H:%M").to_string();
+        if let Some(last) = session.last_timestamp.filter(|l| *l != first) {
+            let format = if last.date_naive() == first.date_naive() { "%H:%M" } else { "%Y-%m-%d %H:%M" };
+            when.push_str(&format!(" – {}", last.format(format)));
+        }
+        meta.push(("Time", format!("{} UTC", when)));
+    }
+    if !session.models_used.is_empty() {
+        let mut models = session.models_used.clone();
+        models.sort();
+        meta.push(("Models", models.join(", ")));
+    }
+    meta.push(("Messages", session.message_count.to_string()));
+    meta
+}
+
+fn markdown(paths: &Paths, session: &Session, learning: Option<&Learning>, entries: &[Entry]) -> Result<String> {
+    let mut out = format!("# Session `{}`\n\n", session.id);
+    for (label, value) in session_meta(session) {
+        out.push_str(&format!("- **{}**: {}\n", label, value));
+    }
+    if session.token_stats.message_count > 0 {
+        out.push_str(&format!("- {}\n", export::format_stats_summary(&session.token_stats)));
+    }
+
+    out.push_str("\n## Learnings\n\n");
+    match learning {
+        Some(learning) => {
+            let categories = CategorySet::load(&paths.categories_path)?;
+            let action_items = export::load_action_items(paths)?;
+            let view = LearningView::new(learning, &categories, &action_items, None);
+            out.push_str(Templates::load(paths)?.learning(&view)?.trim_end());
+            out.push('\n');
+        }
+        None => out.push_str("_Not harvested yet._\n"),
+    }
+
+    out.push_str("\n## Transcript\n");
+    for entry in entries {
+        if let Some(turn) = entry.turn {
+            out.push_str(&format!("\n### Turn {}\n", turn));
+        }
+
+        let mut head = vec![format!("**{}**", role_label(entry.role))];
+        head.extend(entry.when.clone());
+        head.extend(annotation(entry).into_iter().map(|note| format!("_{}_", note)));
+        out.push_str(&format!("\n{}\n", head.join(" · ")));
+
+        for part in &entry.parts {
+            out.push('\n');
+            match part {
+                Part::Text(text) => {
+                    out.push_str(text.trim_end());
+                    out.push('\n');
+                }
+                Part::ToolCall { name, input } => {
+                    let input = input.map(|i| serde_json::to_string_pretty(i).unwrap_or_default());
+                    out.push_str(&details(
+                        &format!("Tool call: <code>{}</code>", escape(name)),
+                        &fenced(input.as_deref().unwrap_or(""), "json"),
+                    ));
+                }
+                Part::ToolResult { name, content } => {
+                    let summary = match name {
+                        Some(name) => format!("Result: <code>{}</code>", escape(name)),
+                        None => "Result".to_string(),
+                    };
+                    out.push_str(&details(&summary, &fenced(content, "")));
+                }
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn details(summary: &str, body: &str) -> String {
+    format!("<details>\n<summary>{}</summary>\n\n{}\n</details>\n", summary, body)
+}
+
+/// A code block, fenced with more backticks than the text contains in a row
+fn fenced(text: &str, lang: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}{}\n{}\n{}\n", fence, lang, text.trim_end(), fence)
+}
+
+fn html(paths: &Paths, session: &Session, learning: Option<&Learning>, entries: &[Entry]) -> Result<String> {
+    let mut meta: Vec<String> = session_meta(session)
+        .into_iter()
+        .map(|(label, value)| format!("{}: {}", label, escape(&value)))
+        .collect();
+    if session.token_stats.message_count > 0 {
+        let stats = &session.token_stats;
+        meta.push(format!(
+            "Tokens: {} in / {} out, {:.0}% cached, {}",
+            format_tokens(stats.input_tokens),
+            format_tokens(stats.output_tokens),
+            stats.cache_hit_rate() * 100.0,
+            format_cost(stats.total_cost)
+        ));
+    }
+
+    let mut body = format!(
+        "<header>\n<h1>Session <code>{}</code></h1>\n<div class=\"meta\">{}</div>\n</header>\n<main>\n",
+        escape(&session.id),
+        meta.join(" · ")
+    );
+
+    body.push_str("<section class=\"learnings\">\n<h2>Learnings</h2>\n");
+    match learning {
+        Some(learning) => {
+            let categories = CategorySet::load(&paths.categories_path)?;
+            let action_items = export::load_action_items(paths)?;
+            let view = LearningView::new(learning, &categories, &action_items, None);
+            if !view.topics.is_empty() {
+                body.push_str(&format!("<div class=\"topics\">{}</div>\n", escape(&view.topics.join(", "))));
+            }
+            if !view.summary.is_empty() {
+                body.push_str(&format!("<p class=\"summary\">{}</p>\n", escape(view.summary)));
+            }
+            body.push_str("<ul>\n");
+            let mut item = |kind: &str, text: &str, class: &str| {
+                body.push_str(&format!(
+                    "<li class=\"item{}\"><span class=\"kind\">{}</span> {}</li>\n",
+                    if class.is_empty() { String::new() } else { format!(" {}", class) },
+                    escape(&categories::label(kind)),
+                    escape(text)
+                ));
+            };
+            for decision in view.decisions {
+                item("decisions", decision, "");
+            }
+            for fact in view.facts {
+                item("facts_learned", fact, "");
+            }
+            for action in &view.action_items {
+                match action.status {
+                    "done" => item("action_items", &format!("☑ {}", action.text), "done"),
+                    "dropped" => item("action_items", action.text, "dropped"),
+                    _ => item("action_items", &format!("☐ {}", action.text), ""),
+                }
+            }
+            for category in &view.categories {
+                for text in category.items {
+                    item(category.name, text, "");
+                }
+            }
+            body.push_str("</ul>\n");
+        }
+        None => body.push_str("<p class=\"meta\">Not harvested yet.</p>\n"),
+    }
+    body.push_str("</section>\n<section class=\"transcript\">\n<h2>Transcript</h2>\n");
+
+    for entry in entries {
+        if let Some(turn) = entry.turn {
+            body.push_str(&format!("<h3 id=\"turn-{0}\">Turn {0}</h3>\n", turn));
+        }
+
+        let mut head = vec![format!("<span class=\"role\">{}</span>", escape(role_label(entry.role)))];
+        head.extend(entry.when.iter().map(|w| escape(w)));
+        head.extend(annotation(entry).iter().map(|note| escape(note)));
+        body.push_str(&format!(
+            "<article class=\"message {}\">\n<div class=\"head\">{}</div>\n",
+            escape(entry.role),
+            head.join(" · ")
+        ));
+
+        for part in &entry.parts {
+            match part {
+                Part::Text(text) => body.push_str(&format!("<div class=\"text\">{}</div>\n", escape(text.trim()))),
+                Part::ToolCall { name, input } => {
+                    let input = input.map(|i| serde_json::to_string_pretty(i).unwrap_or_default());
+                    body.push_str(&format!(
+                        "<details>\n<summary>Tool call: <code>{}</code></summary>\n<pre>{}</pre>\n</details>\n",
+                        escape(name),
+                        escape(input.as_deref().unwrap_or(""))
+                    ));
+                }
+                Part::ToolResult { name, content } => {
+                    let summary = match name {
+                        Some(name) => format!("Result: <code>{}</code>", escape(name)),
+                        None => "Result".to_string(),
+                    };
+                    body.push_str(&format!(
+                        "<details>\n<summary>{}</summary>\n<pre>{}</pre>\n</details>\n",
+                        summary,
+                        escape(content.trim_end())
+                    ));
+                }
+            }
+        }
+        body.push_str("</article>\n");
+    }
+    body.push_str("</section>\n</main>\n");
+
+    Ok(page(&format!("Session {}", session.id), &body))
+}